@@ -0,0 +1,502 @@
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+/// Number of 1X2 outcomes (home/draw/away).
+const X12_OUTCOMES: usize = 3;
+
+/// Lines within this distance are treated as the same market line when matching AH/OU
+/// quotes across bookies. Mirrors the tolerance odds-processor's filter DSL uses for
+/// bracket line lookups (see `resolve_line_access` in `odds-processor/src/filters/path.rs`).
+const LINE_TOLERANCE: f64 = 0.001;
+
+/// A detected risk-free betting opportunity for one fixture and market.
+#[derive(Debug, Clone)]
+pub struct ArbitrageOpportunity {
+    pub fixture_id: i64,
+    /// "x12", "ah", or "ou".
+    pub market: &'static str,
+    /// Matched line value for ah/ou opportunities; `None` for x12.
+    pub line: Option<f64>,
+    /// Guaranteed return as a fraction of total stake, e.g. `0.02` for 2%.
+    pub profit_pct: f64,
+    pub legs: Vec<ArbitrageLeg>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArbitrageLeg {
+    pub outcome: &'static str,
+    pub bookie: String,
+    pub decimal_odds: f64,
+    pub stake_fraction: f64,
+    pub max_stake: Option<f64>,
+    /// `stake_fraction * total_stake`, capped at `max_stake` so the recommendation is
+    /// actually fillable against that bookie's quoted liquidity.
+    pub recommended_stake: f64,
+}
+
+/// One bookie's matched-line AH or OU quote: the line value plus decimal odds and
+/// liquidity for each side (home/away for AH, over/under for OU).
+struct LineQuote {
+    line: f64,
+    home: f64,
+    away: f64,
+    max_stake_home: Option<f64>,
+    max_stake_away: Option<f64>,
+}
+
+struct BookQuote {
+    bookie: String,
+    x12: Option<[f64; X12_OUTCOMES]>,
+    max_stake_x12: Option<[f64; X12_OUTCOMES]>,
+    ah: Vec<LineQuote>,
+    ou: Vec<LineQuote>,
+}
+
+/// Scans `football_odds` for cross-bookmaker arbitrage across the 1X2, AH, and OU
+/// markets, i.e. fixtures where the best available price on each outcome of a
+/// mutually-exclusive, exhaustive outcome set, taken across books, guarantees a profit
+/// regardless of result. Runs as its own periodic background service, parallel to
+/// `PinnacleDbService`.
+pub struct ArbitrageService {
+    pool: PgPool,
+    /// Quotes older than this are ignored so a stale, unrefreshed price can't produce a
+    /// phantom arb against a book that's since moved.
+    freshness_window_secs: i64,
+    /// Total stake (in whatever currency unit the caller uses) assumed when computing the
+    /// per-leg stake split.
+    total_stake: f64,
+    /// Opportunities below this guaranteed-return fraction are discarded as noise.
+    min_margin: f64,
+    /// Opportunities whose available liquidity (min of per-leg quoted max stake) falls
+    /// below this are discarded as unfillable. A leg with no quoted max stake is treated
+    /// as unbounded and never fails this check on its own.
+    min_liquidity: f64,
+}
+
+impl ArbitrageService {
+    pub fn new(
+        pool: PgPool,
+        freshness_window_secs: i64,
+        total_stake: f64,
+        min_margin: f64,
+        min_liquidity: f64,
+    ) -> Self {
+        Self {
+            pool,
+            freshness_window_secs,
+            total_stake,
+            min_margin,
+            min_liquidity,
+        }
+    }
+
+    /// Poll `football_odds` for arbitrage, backing off from `min_delay` toward `max_delay`
+    /// when a cycle finds nothing, and resetting to `min_delay` the moment one does - so a
+    /// quiet set of fixtures doesn't hammer the DB but an active one gets rescanned promptly.
+    pub async fn run(&self, min_delay: Duration, max_delay: Duration) {
+        info!(
+            "Starting Arbitrage Service (freshness window: {}s, delay {:?}..{:?})",
+            self.freshness_window_secs, min_delay, max_delay
+        );
+        let mut delay = min_delay;
+
+        loop {
+            tokio::time::sleep(delay).await;
+
+            match self.scan_cycle().await {
+                Ok(found) if found > 0 => {
+                    debug!("Arbitrage scan found {} opportunities", found);
+                    delay = min_delay;
+                }
+                Ok(_) => delay = (delay * 2).min(max_delay),
+                Err(e) => {
+                    error!("Error in arbitrage scan cycle: {}", e);
+                    delay = (delay * 2).min(max_delay);
+                }
+            }
+        }
+    }
+
+    async fn scan_cycle(&self) -> Result<usize, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT fixture_id, bookie, decimals, odds_x12, odds_ah, odds_ou, lines, max_stakes, latest_t
+            FROM football_odds
+            WHERE odds_x12 IS NOT NULL OR odds_ah IS NOT NULL OR odds_ou IS NOT NULL
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let now = Utc::now().timestamp();
+        let mut by_fixture: HashMap<i64, Vec<BookQuote>> = HashMap::new();
+
+        for row in rows {
+            let fixture_id: i64 = row.get("fixture_id");
+            let bookie: String = row.get("bookie");
+            let decimals: i32 = row.get("decimals");
+            let odds_x12: Option<Value> = row.get("odds_x12");
+            let odds_ah: Option<Value> = row.get("odds_ah");
+            let odds_ou: Option<Value> = row.get("odds_ou");
+            let lines: Option<Value> = row.get("lines");
+            let max_stakes: Option<Value> = row.get("max_stakes");
+            let latest_t: Option<Value> = row.get("latest_t");
+
+            let x12 = if self.fresh(latest_t.as_ref(), "x12_ts", now) {
+                Self::latest_x12(odds_x12.as_ref(), decimals)
+            } else {
+                None
+            };
+            let max_stake_x12 = Self::latest_max_stake_x12(max_stakes.as_ref());
+
+            let ah = if self.fresh(latest_t.as_ref(), "ah_ts", now) {
+                Self::latest_line_quotes(odds_ah.as_ref(), lines.as_ref(), max_stakes.as_ref(), decimals, "ah")
+            } else {
+                Vec::new()
+            };
+            let ou = if self.fresh(latest_t.as_ref(), "ou_ts", now) {
+                Self::latest_line_quotes(odds_ou.as_ref(), lines.as_ref(), max_stakes.as_ref(), decimals, "ou")
+            } else {
+                Vec::new()
+            };
+
+            if x12.is_none() && ah.is_empty() && ou.is_empty() {
+                continue;
+            }
+
+            by_fixture.entry(fixture_id).or_default().push(BookQuote {
+                bookie,
+                x12,
+                max_stake_x12,
+                ah,
+                ou,
+            });
+        }
+
+        let mut opportunities_found = 0;
+        for (fixture_id, quotes) in by_fixture {
+            if quotes.len() < 2 {
+                continue;
+            }
+
+            let mut opportunities = Vec::new();
+            if let Some(opp) = Self::find_x12_arbitrage(fixture_id, &quotes, self.total_stake) {
+                opportunities.push(opp);
+            }
+            opportunities.extend(Self::find_two_way_arbitrage(fixture_id, &quotes, "ah", self.total_stake));
+            opportunities.extend(Self::find_two_way_arbitrage(fixture_id, &quotes, "ou", self.total_stake));
+
+            opportunities.retain(|opp| {
+                opp.profit_pct >= self.min_margin
+                    && Self::available_liquidity(&opp.legs).map_or(true, |liq| liq >= self.min_liquidity)
+            });
+
+            for opportunity in &opportunities {
+                self.persist_opportunity(opportunity).await?;
+                opportunities_found += 1;
+            }
+        }
+
+        Ok(opportunities_found)
+    }
+
+    fn fresh(&self, latest_t: Option<&Value>, key: &str, now: i64) -> bool {
+        let ts = latest_t.and_then(|v| v.get(key)).and_then(|v| v.as_i64());
+        match ts {
+            Some(ts) => now - ts <= self.freshness_window_secs,
+            None => false,
+        }
+    }
+
+    /// Pull the most recent `[home, draw, away]` decimal-odds triple out of the
+    /// `odds_x12` history array, converting from the stored `decimal * 10^decimals`
+    /// integer encoding.
+    fn latest_x12(odds_x12: Option<&Value>, decimals: i32) -> Option<[f64; X12_OUTCOMES]> {
+        let entry = odds_x12?.as_array()?.last()?;
+        let x12 = entry.get("x12")?.as_array()?;
+        if x12.len() != X12_OUTCOMES {
+            return None;
+        }
+
+        let scale = 10f64.powi(decimals);
+        let mut out = [0.0; X12_OUTCOMES];
+        for (i, v) in x12.iter().enumerate() {
+            let raw = v.as_f64()?;
+            out[i] = raw / scale;
+        }
+        Some(out)
+    }
+
+    fn latest_max_stake_x12(max_stakes: Option<&Value>) -> Option<[f64; X12_OUTCOMES]> {
+        let entry = max_stakes?.as_array()?.last()?;
+        let arr = entry.get("max_stake_x12")?.as_array()?;
+        if arr.len() != X12_OUTCOMES {
+            return None;
+        }
+        let mut out = [0.0; X12_OUTCOMES];
+        for (i, v) in arr.iter().enumerate() {
+            out[i] = v.as_f64().unwrap_or(0.0);
+        }
+        Some(out)
+    }
+
+    /// Decode the latest AH/OU lines entry into per-line quotes, aligning the
+    /// `odds_ah`/`odds_ou` and `max_stakes` history arrays against the `lines` column by
+    /// index, the same way `update_database_with_best_prices` wrote them.
+    fn latest_line_quotes(
+        odds: Option<&Value>,
+        lines: Option<&Value>,
+        max_stakes: Option<&Value>,
+        decimals: i32,
+        market: &str,
+    ) -> Vec<LineQuote> {
+        let Some(line_values) = lines
+            .and_then(|v| v.as_array())
+            .and_then(|a| a.last())
+            .and_then(|entry| entry.get(market))
+            .and_then(|v| v.as_array())
+        else {
+            return Vec::new();
+        };
+
+        let Some(odds_entry) = odds.and_then(|v| v.as_array()).and_then(|a| a.last()) else {
+            return Vec::new();
+        };
+
+        let (home_key, away_key) = if market == "ah" { ("ah_h", "ah_a") } else { ("ou_o", "ou_u") };
+        let Some(home_raw) = odds_entry.get(home_key).and_then(|v| v.as_array()) else {
+            return Vec::new();
+        };
+        let Some(away_raw) = odds_entry.get(away_key).and_then(|v| v.as_array()) else {
+            return Vec::new();
+        };
+
+        let stake_key = if market == "ah" { "max_stake_ah" } else { "max_stake_ou" };
+        let stake_entry = max_stakes.and_then(|v| v.as_array()).and_then(|a| a.last());
+        let home_stakes = stake_entry.and_then(|e| e.get(stake_key)).and_then(|s| s.get("h")).and_then(|v| v.as_array());
+        let away_stakes = stake_entry.and_then(|e| e.get(stake_key)).and_then(|s| s.get("a")).and_then(|v| v.as_array());
+
+        let scale = 10f64.powi(decimals);
+        let mut out = Vec::with_capacity(line_values.len());
+        for (i, line_val) in line_values.iter().enumerate() {
+            let Some(line) = line_val.as_f64() else { continue };
+            let home = home_raw.get(i).and_then(|v| v.as_f64()).map(|v| v / scale).unwrap_or(0.0);
+            let away = away_raw.get(i).and_then(|v| v.as_f64()).map(|v| v / scale).unwrap_or(0.0);
+            if home <= 1.0 || away <= 1.0 {
+                // No live quote on one side of this line yet.
+                continue;
+            }
+            out.push(LineQuote {
+                line,
+                home,
+                away,
+                max_stake_home: home_stakes.and_then(|a| a.get(i)).and_then(|v| v.as_f64()),
+                max_stake_away: away_stakes.and_then(|a| a.get(i)).and_then(|v| v.as_f64()),
+            });
+        }
+        out
+    }
+
+    /// Classic three-way book arbitrage: take the best (highest) decimal odds for each
+    /// outcome across books, compute the book coefficient `k = Σ 1/o_i`. If `k < 1`, a
+    /// risk-free return of `1/k − 1` is available by staking `(1/o_i)/k` of the total on
+    /// each leg.
+    fn find_x12_arbitrage(fixture_id: i64, quotes: &[BookQuote], total_stake: f64) -> Option<ArbitrageOpportunity> {
+        const OUTCOMES: [&str; X12_OUTCOMES] = ["home", "draw", "away"];
+
+        let mut best: [Option<(usize, f64, Option<f64>)>; X12_OUTCOMES] = [None; X12_OUTCOMES];
+        for (qi, quote) in quotes.iter().enumerate() {
+            let Some(x12) = quote.x12 else { continue };
+            for (i, &odds) in x12.iter().enumerate() {
+                if odds <= 1.0 {
+                    continue;
+                }
+                let max_stake = quote.max_stake_x12.map(|s| s[i]);
+                if max_stake.is_some_and(|s| s <= 0.0) {
+                    // No liquidity quoted on this outcome; not fillable.
+                    continue;
+                }
+                let is_better = match best[i] {
+                    Some((_, best_odds, _)) => odds > best_odds,
+                    None => true,
+                };
+                if is_better {
+                    best[i] = Some((qi, odds, max_stake));
+                }
+            }
+        }
+
+        let best: [(usize, f64, Option<f64>); X12_OUTCOMES] = [best[0]?, best[1]?, best[2]?];
+        let k: f64 = best.iter().map(|(_, odds, _)| 1.0 / odds).sum();
+        if k >= 1.0 {
+            return None;
+        }
+
+        let legs = std::array::from_fn(|i| {
+            let (qi, odds, max_stake) = best[i];
+            Self::build_leg(OUTCOMES[i], &quotes[qi].bookie, odds, k, max_stake, total_stake)
+        })
+        .into();
+
+        Some(ArbitrageOpportunity {
+            fixture_id,
+            market: "x12",
+            line: None,
+            profit_pct: 1.0 / k - 1.0,
+            legs,
+        })
+    }
+
+    /// Two-way arbitrage (AH or OU), evaluated independently per matched line: take the
+    /// best decimal odds for each side across books whose line values agree within
+    /// `LINE_TOLERANCE`, and emit an opportunity whenever the book coefficient dips below 1.
+    fn find_two_way_arbitrage(
+        fixture_id: i64,
+        quotes: &[BookQuote],
+        market: &'static str,
+        total_stake: f64,
+    ) -> Vec<ArbitrageOpportunity> {
+        let (home_label, away_label) = if market == "ah" { ("home", "away") } else { ("over", "under") };
+
+        // Group per-bookie line quotes by line value. Lines are always spaced further
+        // apart than `LINE_TOLERANCE`, so rounding to that precision is equivalent to the
+        // pairwise tolerance check `resolve_line_access` uses, without needing clustering.
+        let mut by_line: HashMap<i64, Vec<(&str, &LineQuote)>> = HashMap::new();
+        for quote in quotes {
+            let lines = if market == "ah" { &quote.ah } else { &quote.ou };
+            for lq in lines {
+                let key = (lq.line / LINE_TOLERANCE).round() as i64;
+                by_line.entry(key).or_default().push((quote.bookie.as_str(), lq));
+            }
+        }
+
+        let mut opportunities = Vec::new();
+        for line_quotes in by_line.values() {
+            if line_quotes.len() < 2 {
+                continue;
+            }
+            let line_value = line_quotes[0].1.line;
+
+            let mut best_home: Option<(&str, f64, Option<f64>)> = None;
+            let mut best_away: Option<(&str, f64, Option<f64>)> = None;
+            for (bookie, lq) in line_quotes {
+                if lq.max_stake_home.map_or(true, |s| s > 0.0) && best_home.as_ref().map_or(true, |(_, o, _)| lq.home > *o) {
+                    best_home = Some((bookie, lq.home, lq.max_stake_home));
+                }
+                if lq.max_stake_away.map_or(true, |s| s > 0.0) && best_away.as_ref().map_or(true, |(_, o, _)| lq.away > *o) {
+                    best_away = Some((bookie, lq.away, lq.max_stake_away));
+                }
+            }
+
+            let (Some((home_bookie, home_odds, home_max)), Some((away_bookie, away_odds, away_max))) = (best_home, best_away) else {
+                continue;
+            };
+
+            let k = 1.0 / home_odds + 1.0 / away_odds;
+            if k >= 1.0 {
+                continue;
+            }
+
+            let legs = vec![
+                Self::build_leg(home_label, home_bookie, home_odds, k, home_max, total_stake),
+                Self::build_leg(away_label, away_bookie, away_odds, k, away_max, total_stake),
+            ];
+
+            opportunities.push(ArbitrageOpportunity {
+                fixture_id,
+                market,
+                line: Some(line_value),
+                profit_pct: 1.0 / k - 1.0,
+                legs,
+            });
+        }
+
+        opportunities
+    }
+
+    /// The stake an opportunity can actually absorb: the smallest per-leg `max_stake`, or
+    /// `None` if every leg quoted unlimited liquidity.
+    fn available_liquidity(legs: &[ArbitrageLeg]) -> Option<f64> {
+        legs.iter()
+            .filter_map(|leg| leg.max_stake)
+            .fold(None, |acc, stake| Some(acc.map_or(stake, |best: f64| best.min(stake))))
+    }
+
+    fn build_leg(
+        outcome: &'static str,
+        bookie: &str,
+        decimal_odds: f64,
+        k: f64,
+        max_stake: Option<f64>,
+        total_stake: f64,
+    ) -> ArbitrageLeg {
+        let stake_fraction = (1.0 / decimal_odds) / k;
+        let ideal_stake = stake_fraction * total_stake;
+        let recommended_stake = match max_stake {
+            Some(cap) => ideal_stake.min(cap),
+            None => ideal_stake,
+        };
+
+        ArbitrageLeg {
+            outcome,
+            bookie: bookie.to_string(),
+            decimal_odds,
+            stake_fraction,
+            max_stake,
+            recommended_stake,
+        }
+    }
+
+    async fn persist_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        let expires_at = now + chrono::Duration::seconds(self.freshness_window_secs);
+        let market_key = match opportunity.line {
+            Some(line) => format!("{}:{}", opportunity.market, line),
+            None => opportunity.market.to_string(),
+        };
+
+        let legs = serde_json::json!(opportunity
+            .legs
+            .iter()
+            .map(|leg| {
+                serde_json::json!({
+                    "outcome": leg.outcome,
+                    "bookie": leg.bookie,
+                    "odds": leg.decimal_odds,
+                    "stake_fraction": leg.stake_fraction,
+                    "max_stake": leg.max_stake,
+                    "recommended_stake": leg.recommended_stake,
+                })
+            })
+            .collect::<Vec<_>>());
+
+        sqlx::query(
+            r#"
+            INSERT INTO arbitrage_opportunities
+                (fixture_id, market, profit_pct, legs, stake_total, detected_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (fixture_id, market) DO UPDATE SET
+                profit_pct = EXCLUDED.profit_pct,
+                legs = EXCLUDED.legs,
+                stake_total = EXCLUDED.stake_total,
+                detected_at = EXCLUDED.detected_at,
+                expires_at = EXCLUDED.expires_at
+            "#,
+        )
+        .bind(opportunity.fixture_id)
+        .bind(market_key)
+        .bind(opportunity.profit_pct)
+        .bind(legs)
+        .bind(self.total_stake)
+        .bind(now)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use crate::shared::types::PriceLevel;
+use crate::AppState;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// Number of 1X2 outcomes (home/draw/away).
+const X12_OUTCOMES: usize = 3;
+
+/// Lines within this distance are treated as the same market line when bucketing AH/OU
+/// `MarketMapping`s by `line_value`. Mirrors the tolerance `ArbitrageService` uses for the
+/// same purpose (see `arbitrage.rs`).
+const LINE_TOLERANCE: f64 = 0.001;
+
+/// A risk-free betting opportunity detected across the outcomes of a single live Monaco
+/// market - as opposed to `ArbitrageOpportunity`, which compares best prices *across*
+/// bookmakers from the persisted `football_odds` history.
+#[derive(Debug, Clone)]
+pub struct SureBetOpportunity {
+    pub fixture_id: i64,
+    /// "x12", "ah", or "ou".
+    pub market: &'static str,
+    /// Matched line value for ah/ou opportunities; `None` for x12.
+    pub line: Option<f64>,
+    /// Guaranteed return as a fraction of total stake, e.g. `0.02` for 2%.
+    pub margin: f64,
+    pub legs: Vec<SureBetLeg>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SureBetLeg {
+    pub outcome: &'static str,
+    pub decimal_odds: f64,
+    pub stake_fraction: f64,
+    /// Liquidity quoted at `decimal_odds` on the order book, i.e. the most this leg can
+    /// actually be filled for.
+    pub max_stake: f64,
+    /// `stake_fraction * total_stake`, capped at `max_stake` so the recommendation is
+    /// actually fillable against the book's quoted depth.
+    pub recommended_stake: f64,
+}
+
+/// Continuously scans the live `MonacoOrderBook` for cross-outcome arbitrage: a market
+/// whose mutually exclusive outcomes (the three sides of `x12`, or the two sides of an
+/// `ah`/`ou` line) can each be backed at a price such that the book sum `Σ 1/odds_i` dips
+/// below 1, guaranteeing a profit regardless of result. Runs as its own periodic
+/// background service, parallel to `ArbitrageService`, but reads the in-memory order book
+/// directly rather than polling `football_odds`, so it reacts to single-book depth moves
+/// the DB-backed scan can't see.
+pub struct ArbitrerService {
+    state: Arc<AppState>,
+    /// Opportunities below this margin are discarded as noise (e.g. rounding slack in the
+    /// quoted prices rather than a real edge).
+    min_margin: f64,
+    total_stake: f64,
+}
+
+impl ArbitrerService {
+    pub fn new(state: Arc<AppState>, min_margin: f64, total_stake: f64) -> Self {
+        Self {
+            state,
+            min_margin,
+            total_stake,
+        }
+    }
+
+    /// Poll the order book, backing off from `min_delay` toward `max_delay` when a cycle
+    /// finds nothing and resetting to `min_delay` the moment one does - so a quiet set of
+    /// markets doesn't get rescanned needlessly but an active one is caught promptly.
+    pub async fn run(&self, min_delay: Duration, max_delay: Duration) {
+        info!(
+            "Starting Arbitrer Service (min margin: {:.4}, delay {:?}..{:?})",
+            self.min_margin, min_delay, max_delay
+        );
+        let mut delay = min_delay;
+
+        loop {
+            tokio::time::sleep(delay).await;
+
+            let found = self.scan_cycle().await;
+            if found > 0 {
+                debug!("Arbitrer scan found {} opportunities", found);
+                delay = min_delay;
+            } else {
+                delay = (delay * 2).min(max_delay);
+            }
+        }
+    }
+
+    async fn scan_cycle(&self) -> usize {
+        // Group live market mappings by (fixture_id, market_type), bucketing ah/ou further
+        // by line_value so each bucket holds exactly the outcomes of one comparable market.
+        let mut groups: HashMap<(i64, &'static str, Option<i64>), (Option<f64>, HashMap<usize, String>)> = HashMap::new();
+        for entry in self.state.market_mapping.iter() {
+            let mapping = entry.value();
+            let Some(fixture_id) = mapping.fixture_id else { continue };
+            let market: &'static str = match mapping.market_type.as_str() {
+                "x12" => "x12",
+                "ah" => "ah",
+                "ou" => "ou",
+                _ => continue,
+            };
+            let Some(ref outcome_mappings) = mapping.outcome_mappings else { continue };
+
+            let line_key = mapping.line_value.map(|l| (l / LINE_TOLERANCE).round() as i64);
+            let (line_value, outcomes) = groups.entry((fixture_id, market, line_key)).or_default();
+            *line_value = line_value.or(mapping.line_value);
+            for (outcome_id, &idx) in outcome_mappings {
+                outcomes.entry(idx).or_insert_with(|| outcome_id.clone());
+            }
+        }
+
+        let order_book = self.state.order_book.lock().await;
+
+        let mut opportunities_found = 0;
+        for ((fixture_id, market, _line_key), (line_value, outcomes)) in groups {
+            let Some(book) = order_book.get_order_book(fixture_id, market) else { continue };
+            let Some(opportunity) = Self::find_opportunity(fixture_id, market, line_value, &outcomes, book, self.total_stake) else { continue };
+
+            if opportunity.margin < self.min_margin {
+                continue;
+            }
+
+            info!(
+                "💹 Sure bet: fixture={} market={}{} margin={:.3}%",
+                fixture_id,
+                market,
+                opportunity.line.map(|l| format!("@{}", l)).unwrap_or_default(),
+                opportunity.margin * 100.0,
+            );
+            opportunities_found += 1;
+        }
+
+        opportunities_found
+    }
+
+    /// Requires every outcome this market is supposed to have (3 for x12, 2 for ah/ou) to
+    /// currently quote a top-of-book price before it'll consider the market at all - a
+    /// market still missing a side's price isn't actionable, it's just incomplete.
+    fn find_opportunity(
+        fixture_id: i64,
+        market: &'static str,
+        line: Option<f64>,
+        outcomes: &HashMap<usize, String>,
+        book: &HashMap<String, Vec<PriceLevel>>,
+        total_stake: f64,
+    ) -> Option<SureBetOpportunity> {
+        let needed = if market == "x12" { X12_OUTCOMES } else { 2 };
+
+        let mut legs_raw = Vec::with_capacity(needed);
+        for idx in 0..needed {
+            let outcome_id = outcomes.get(&idx)?;
+            let best = book.get(outcome_id)?.first()?;
+            if best.price <= 1.0 || best.liquidity <= 0.0 {
+                return None;
+            }
+            legs_raw.push((idx, best.price, best.liquidity));
+        }
+
+        let k: f64 = legs_raw.iter().map(|(_, odds, _)| 1.0 / odds).sum();
+        if k >= 1.0 {
+            return None;
+        }
+
+        let legs = legs_raw
+            .into_iter()
+            .map(|(idx, odds, liquidity)| {
+                let stake_fraction = (1.0 / odds) / k;
+                let recommended_stake = (stake_fraction * total_stake).min(liquidity);
+                SureBetLeg {
+                    outcome: outcome_label(market, idx),
+                    decimal_odds: odds,
+                    stake_fraction,
+                    max_stake: liquidity,
+                    recommended_stake,
+                }
+            })
+            .collect();
+
+        Some(SureBetOpportunity {
+            fixture_id,
+            market,
+            line,
+            margin: 1.0 / k - 1.0,
+            legs,
+        })
+    }
+}
+
+/// Matches the index convention `monaco::handlers::build_odds_update` already decodes
+/// `outcome_mappings` against: 0/1/2 for x12 home/draw/away, even/odd for ah/ou home-or-over
+/// vs away-or-under.
+fn outcome_label(market: &str, idx: usize) -> &'static str {
+    match (market, idx) {
+        ("x12", 0) => "home",
+        ("x12", 1) => "draw",
+        ("x12", 2) => "away",
+        ("ah", i) if i % 2 == 0 => "home",
+        ("ah", _) => "away",
+        ("ou", i) if i % 2 == 0 => "over",
+        ("ou", _) => "under",
+        _ => "unknown",
+    }
+}
@@ -0,0 +1,158 @@
+// Thin Betfair Exchange API client: session login plus the two read endpoints
+// BetfairSource's polling loop needs (listMarketCatalogue for market metadata,
+// listMarketBook for live prices). Mirrors pinnacle::client::PinnacleApiClient's shape
+// (reqwest + env-configured credentials), since both are simple polling REST clients
+// feeding their own ingestion path.
+use crate::betfair::types::{MarketBook, MarketCatalogue};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use std::env;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+const IDENTITY_URL: &str = "https://identitysso.betfair.com/api/login";
+const BETTING_URL: &str = "https://api.betfair.com/exchange/betting/rest/v1.0";
+
+/// Betfair sessions are valid for hours but carry no expiry in the login response; re-login
+/// comfortably inside the shortest documented window rather than tracking the exact expiry.
+const SESSION_MAX_AGE: Duration = Duration::from_secs(4 * 60 * 60);
+
+#[derive(Debug, Deserialize)]
+struct LoginResponse {
+    status: String,
+    token: Option<String>,
+}
+
+/// Talks to the Betfair Exchange API: logs in with a username/password/app-key triple (the
+/// non-certificate login flow), then issues the two read calls the polling loop needs.
+pub struct BetfairApiClient {
+    client: Client,
+    app_key: String,
+    username: String,
+    password: String,
+    session_token: Option<String>,
+    session_started_at: Option<Instant>,
+}
+
+impl BetfairApiClient {
+    pub fn new() -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            app_key: env::var("BETFAIR_APP_KEY").unwrap_or_default(),
+            username: env::var("BETFAIR_USERNAME").unwrap_or_default(),
+            password: env::var("BETFAIR_PASSWORD").unwrap_or_default(),
+            session_token: None,
+            session_started_at: None,
+        }
+    }
+
+    fn session_is_stale(&self) -> bool {
+        match self.session_started_at {
+            Some(started) => started.elapsed() >= SESSION_MAX_AGE,
+            None => true,
+        }
+    }
+
+    /// Log in if there's no session yet or the current one is old enough to risk having
+    /// expired, returning the token to authenticate subsequent requests with.
+    async fn ensure_session(&mut self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        if let Some(token) = &self.session_token {
+            if !self.session_is_stale() {
+                return Ok(token.clone());
+            }
+        }
+
+        if self.app_key.is_empty() || self.username.is_empty() || self.password.is_empty() {
+            return Err("BETFAIR_APP_KEY/BETFAIR_USERNAME/BETFAIR_PASSWORD must be set".into());
+        }
+
+        let response = self
+            .client
+            .post(IDENTITY_URL)
+            .header("X-Application", &self.app_key)
+            .header("Accept", "application/json")
+            .form(&[("username", &self.username), ("password", &self.password)])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(format!("Betfair login failed: {}", error_text).into());
+        }
+
+        let login: LoginResponse = response.json().await?;
+        if login.status != "SUCCESS" {
+            return Err(format!("Betfair login rejected: {}", login.status).into());
+        }
+        let token = login.token.ok_or("Betfair login response missing token")?;
+
+        self.session_token = Some(token.clone());
+        self.session_started_at = Some(Instant::now());
+        Ok(token)
+    }
+
+    async fn post(&mut self, operation: &str, body: serde_json::Value) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let token = self.ensure_session().await?;
+        let url = format!("{}/{}/", BETTING_URL, operation);
+
+        let response = self
+            .client
+            .post(&url)
+            .header("X-Application", &self.app_key)
+            .header("X-Authentication", &token)
+            .json(&body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(format!("Betfair API error ({}): {} - {}", operation, status, text).into());
+        }
+
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Fetch catalogue metadata (name + runners) for the given market IDs, the input
+    /// `classify_market` needs to recognize a market's type. Betfair caps
+    /// `listMarketCatalogue` at 1000 IDs per call; callers are expected to stay well under that.
+    pub async fn list_market_catalogue(&mut self, market_ids: &[String]) -> Result<Vec<MarketCatalogue>, Box<dyn Error + Send + Sync>> {
+        if market_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body = json!({
+            "filter": { "marketIds": market_ids },
+            "marketProjection": ["RUNNER_DESCRIPTION"],
+            "maxResults": market_ids.len(),
+        });
+
+        let value = self.post("listMarketCatalogue", body).await?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Fetch live best-available-to-back/lay ladders for the given market IDs.
+    pub async fn list_market_book(&mut self, market_ids: &[String]) -> Result<Vec<MarketBook>, Box<dyn Error + Send + Sync>> {
+        if market_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body = json!({
+            "marketIds": market_ids,
+            "priceProjection": { "priceData": ["EX_BEST_OFFERS"] },
+        });
+
+        let value = self.post("listMarketBook", body).await?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+impl Default for BetfairApiClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,88 @@
+// Classifies a Betfair `MarketCatalogue` into the same `MarketMapping` shape Monaco
+// markets use, so downstream code (order book, `build_odds_update`, candles, depth)
+// doesn't need to know which exchange a market came from.
+use std::collections::HashMap;
+
+use crate::betfair::types::MarketCatalogue;
+use crate::monaco::types::MarketMapping;
+
+/// Classify a market catalogue entry into `x12`/`ah`/`ou`, building the
+/// `outcome_mappings` (Betfair `selectionId` -> outcome index) the same way Monaco's
+/// mapping does it. Returns `None` for market types we don't yet understand (e.g.
+/// correct score), exactly as Monaco's `MarketRules::resolve` does for unmapped types.
+pub fn classify_market(market: &MarketCatalogue) -> Option<MarketMapping> {
+    let mut runners = market.runners.clone();
+    runners.sort_by_key(|r| r.sort_priority);
+
+    let name = market.market_name.as_str();
+
+    if name.eq_ignore_ascii_case("Match Odds") && runners.len() == 3 {
+        let outcome_mappings: HashMap<String, usize> = runners
+            .iter()
+            .enumerate()
+            .map(|(idx, r)| (r.selection_id.to_string(), idx))
+            .collect();
+
+        return Some(MarketMapping {
+            event_id: String::new(),
+            market_id: market.market_id.clone(),
+            market_type_id: market.market_id.clone(),
+            market_type: "x12".to_string(),
+            name: market.market_name.clone(),
+            line_value: None,
+            line_index: None,
+            fixture_id: None,
+            outcome_mappings: Some(outcome_mappings),
+        });
+    }
+
+    if runners.len() == 2 {
+        if let Some(line_value) = parse_line_value(name, "Asian Handicap") {
+            return Some(handicap_or_total_mapping(market, &runners, "ah", line_value));
+        }
+        if let Some(line_value) = parse_line_value(name, "Over/Under") {
+            return Some(handicap_or_total_mapping(market, &runners, "ou", line_value));
+        }
+    }
+
+    None
+}
+
+fn handicap_or_total_mapping(
+    market: &MarketCatalogue,
+    runners: &[crate::betfair::types::CatalogueRunner],
+    market_type: &str,
+    line_value: f64,
+) -> MarketMapping {
+    let outcome_mappings: HashMap<String, usize> = runners
+        .iter()
+        .enumerate()
+        .map(|(idx, r)| (r.selection_id.to_string(), idx))
+        .collect();
+
+    MarketMapping {
+        event_id: String::new(),
+        market_id: market.market_id.clone(),
+        market_type_id: market.market_id.clone(),
+        market_type: market_type.to_string(),
+        name: market.market_name.clone(),
+        line_value: Some(line_value),
+        line_index: None,
+        fixture_id: None,
+        outcome_mappings: Some(outcome_mappings),
+    }
+}
+
+/// Pull the numeric line out of a market name like "Asian Handicap -0.5" or
+/// "Over/Under 2.5 Goals", given the expected prefix.
+fn parse_line_value(market_name: &str, prefix: &str) -> Option<f64> {
+    if !market_name.starts_with(prefix) {
+        return None;
+    }
+    market_name
+        .trim_start_matches(prefix)
+        .trim()
+        .split_whitespace()
+        .next()
+        .and_then(|token| token.parse::<f64>().ok())
+}
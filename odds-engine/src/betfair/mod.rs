@@ -0,0 +1,5 @@
+pub mod types;
+pub mod market_mapping;
+pub mod source;
+pub mod client;
+pub mod service;
@@ -0,0 +1,91 @@
+// Polling loop pairing `BetfairApiClient` with `BetfairSource`: classifies each tracked
+// market once via `listMarketCatalogue`, then polls `listMarketBook` on an interval and
+// feeds the results into the shared `OddsSource` pipeline - the same role
+// `monaco::stream::MonacoWebSocketClient::start` plays for Monaco.
+//
+// Market discovery (matching a Betfair market to one of our fixtures) has no equivalent
+// to Pinnacle's `football_leagues.pinnacle_league_id` mapping to drive it automatically,
+// so the markets to track are supplied explicitly via `BETFAIR_MARKETS` rather than
+// invented schema.
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{error, warn};
+
+use crate::betfair::client::BetfairApiClient;
+use crate::betfair::market_mapping::classify_market;
+use crate::betfair::source::BetfairSource;
+use crate::AppState;
+
+/// One market this engine instance has been told to track.
+#[derive(Debug, Clone)]
+pub struct TrackedMarket {
+    pub market_id: String,
+    pub event_id: String,
+    pub fixture_id: i64,
+}
+
+/// Parse `BETFAIR_MARKETS`, a comma-separated list of `marketId:eventId:fixtureId`
+/// triples (e.g. `1.23456789:5001:987`). Malformed entries are skipped.
+pub fn parse_tracked_markets(spec: &str) -> Vec<TrackedMarket> {
+    spec.split(',')
+        .filter_map(|entry| {
+            let entry = entry.trim();
+            if entry.is_empty() {
+                return None;
+            }
+            let mut parts = entry.splitn(3, ':');
+            let market_id = parts.next()?.to_string();
+            let event_id = parts.next()?.to_string();
+            let fixture_id = parts.next()?.parse().ok()?;
+            Some(TrackedMarket { market_id, event_id, fixture_id })
+        })
+        .collect()
+}
+
+/// Classify and register each tracked market with `source`, then poll `listMarketBook`
+/// for all of them on `poll_interval` until the process exits.
+pub async fn run(state: Arc<AppState>, source: Arc<BetfairSource>, markets: Vec<TrackedMarket>, poll_interval: Duration) {
+    if markets.is_empty() {
+        warn!("Betfair polling loop started with no tracked markets (BETFAIR_MARKETS is empty); nothing to poll");
+        return;
+    }
+
+    let mut client = BetfairApiClient::new();
+    let market_ids: Vec<String> = markets.iter().map(|m| m.market_id.clone()).collect();
+
+    match client.list_market_catalogue(&market_ids).await {
+        Ok(catalogue) => {
+            for entry in &catalogue {
+                let Some(tracked) = markets.iter().find(|m| m.market_id == entry.market_id) else {
+                    continue;
+                };
+                let Some(mut mapping) = classify_market(entry) else {
+                    warn!("Unrecognized Betfair market type for {} ({})", entry.market_id, entry.market_name);
+                    continue;
+                };
+                mapping.event_id = tracked.event_id.clone();
+                mapping.fixture_id = Some(tracked.fixture_id);
+                source.register_market(&state, mapping);
+            }
+        }
+        Err(e) => {
+            error!("Failed to fetch Betfair market catalogue: {}", e);
+        }
+    }
+
+    let mut interval = tokio::time::interval(poll_interval);
+    loop {
+        interval.tick().await;
+        match client.list_market_book(&market_ids).await {
+            Ok(books) => {
+                for book in &books {
+                    source.ingest_market_book(book);
+                }
+            }
+            Err(e) => {
+                error!("Failed to fetch Betfair market book: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// Adapts the Betfair Exchange into an `OddsSource` by translating its native
+// `MarketBook` shape into a Monaco-shaped `MarketPriceUpdate` envelope before
+// broadcasting it - so `monaco::handlers::handle_price_update` and everything
+// downstream of it (order book, candles, depth feed, `build_odds_update`) runs
+// completely unchanged for Betfair prices, exactly as it does for Monaco's own.
+//
+// Unlike `MonacoSource`, whose `stream()` owns spawning its own client, this adapter is
+// fed from outside: `betfair::service::run` owns the `BetfairApiClient` session, calls
+// `register_market`/`ingest_market_book` directly, and is spawned alongside
+// `start_ingestion_engine` in `main.rs` rather than from `stream()` itself - registration
+// needs this type's inherent methods, not just the `OddsSource` trait object.
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+
+use crate::betfair::types::MarketBook;
+use crate::monaco::types::MarketMapping;
+use crate::source::{NormalizedOutcome, NormalizedUpdate, OddsSource};
+use crate::AppState;
+
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Betfair exchange adapter. Holds the market metadata `classify_market` produced
+/// for each market it has seen, keyed by Betfair's `marketId`, so `ingest_market_book`
+/// can turn a bare price ladder into a fully-addressed Monaco-shaped update.
+pub struct BetfairSource {
+    market_mapping: Arc<DashMap<String, MarketMapping>>,
+    tx: broadcast::Sender<Value>,
+}
+
+impl BetfairSource {
+    pub fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            market_mapping: Arc::new(DashMap::new()),
+            tx,
+        }
+    }
+
+    /// Register (or replace) the classification for a market, the way Monaco's
+    /// `market_init::fetch_and_process_markets` populates `state.market_mapping`.
+    /// Call this once per market after a `listMarketCatalogue` call, before
+    /// `ingest_market_book` updates for it will be dispatched. Writes into both this
+    /// adapter's own `marketId`-keyed cache (needed because a `MarketBook` update only
+    /// carries `marketId`, not `eventId`) and the shared `state.market_mapping`, keyed
+    /// `"{event_id}-{market_id}"` like Monaco's, so `handle_price_update` finds it too.
+    pub fn register_market(&self, state: &AppState, mapping: MarketMapping) {
+        let key = format!("{}-{}", mapping.event_id, mapping.market_id);
+        state.market_mapping.insert(key, mapping.clone());
+        self.market_mapping.insert(mapping.market_id.clone(), mapping);
+    }
+
+    /// Feed one `listMarketBook` response in. Looks up the market's mapping,
+    /// builds a Monaco-shaped `MarketPriceUpdate` envelope from the best
+    /// available-to-back price and size per runner, and broadcasts it.
+    pub fn ingest_market_book(&self, book: &MarketBook) {
+        let Some(mapping) = self.market_mapping.get(&book.market_id) else {
+            return;
+        };
+
+        let prices: Vec<Value> = book
+            .runners
+            .iter()
+            .filter_map(|runner| {
+                let best = runner.ex.available_to_back.first()?;
+                Some(json!({
+                    "outcomeId": runner.selection_id.to_string(),
+                    "price": best.price,
+                    "liquidity": best.size,
+                }))
+            })
+            .collect();
+
+        if prices.is_empty() {
+            return;
+        }
+
+        let envelope = json!({
+            "type": "MarketPriceUpdate",
+            "eventId": mapping.event_id,
+            "marketId": mapping.market_id,
+            "prices": prices,
+        });
+
+        // A send error just means nothing is subscribed right now; nothing to do.
+        let _ = self.tx.send(envelope);
+    }
+}
+
+impl Default for BetfairSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OddsSource for BetfairSource {
+    fn bookie_id(&self) -> i64 {
+        2
+    }
+
+    fn decimals(&self) -> i32 {
+        2
+    }
+
+    fn bookmaker(&self) -> &str {
+        "Betfair"
+    }
+
+    async fn stream(&self) -> broadcast::Receiver<Value> {
+        self.tx.subscribe()
+    }
+
+    fn parse(&self, raw: &Value) -> Option<NormalizedUpdate> {
+        if raw["type"].as_str() != Some("MarketPriceUpdate") {
+            return None;
+        }
+
+        let market_id = raw["marketId"].as_str()?.to_string();
+        let event_id = raw["eventId"].as_str().unwrap_or_default().to_string();
+        let outcomes: Vec<NormalizedOutcome> = raw["prices"]
+            .as_array()?
+            .iter()
+            .filter_map(|p| {
+                let outcome_id = p["outcomeId"].as_str()?.to_string();
+                let price = p["price"].as_f64()?;
+                let liquidity = p["liquidity"].as_f64().unwrap_or(0.0);
+                Some((outcome_id, price, liquidity))
+            })
+            .collect();
+
+        Some(NormalizedUpdate {
+            market_id,
+            event_id,
+            outcomes,
+            valid_at: None,
+        })
+    }
+}
@@ -0,0 +1,64 @@
+// Minimal slice of the Betfair Exchange API types needed to turn a `listMarketBook`
+// response into prices the engine can use. Field names mirror Betfair's own JSON
+// (camelCase via `serde(rename)`), the same way `monaco::types` mirrors Monaco's.
+use serde::Deserialize;
+
+/// A single price/size rung from an exchange ladder.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceSize {
+    pub price: f64,
+    pub size: f64,
+}
+
+/// Best-available-to-back/lay ladder for one runner, as returned under
+/// `runners[].ex` when `listMarketBook` is called with a `PriceProjection`.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ExchangePrices {
+    #[serde(rename = "availableToBack", default)]
+    pub available_to_back: Vec<PriceSize>,
+    #[serde(rename = "availableToLay", default)]
+    pub available_to_lay: Vec<PriceSize>,
+}
+
+/// One selection's current prices within a market.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Runner {
+    #[serde(rename = "selectionId")]
+    pub selection_id: i64,
+    #[serde(default)]
+    pub ex: ExchangePrices,
+}
+
+/// A `listMarketBook` entry for one market.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarketBook {
+    #[serde(rename = "marketId")]
+    pub market_id: String,
+    pub runners: Vec<Runner>,
+}
+
+/// One selection's metadata within a `listMarketCatalogue` entry, used to classify the
+/// market and build outcome mappings - not to be confused with `Runner` above, which
+/// carries this same selection's live prices instead.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatalogueRunner {
+    #[serde(rename = "selectionId")]
+    pub selection_id: i64,
+    #[serde(rename = "sortPriority")]
+    pub sort_priority: i32,
+    #[serde(rename = "runnerName")]
+    pub runner_name: String,
+}
+
+/// A `listMarketCatalogue` entry: static market metadata (name, runners) used once to
+/// build the `fixture_id`/`market_type`/`outcome_mappings` a `MarketBook` is later
+/// resolved against, the same role Monaco's `market_init::fetch_and_process_markets`
+/// plays for Monaco markets.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarketCatalogue {
+    #[serde(rename = "marketId")]
+    pub market_id: String,
+    #[serde(rename = "marketName")]
+    pub market_name: String,
+    pub runners: Vec<CatalogueRunner>,
+}
@@ -0,0 +1,313 @@
+use crate::fair_odds::{compute_fair_probs, FairProbMethod};
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::info;
+
+/// A single no-vig-able 1X2 price plus max stake, already converted to decimal odds.
+#[derive(Debug, Clone, Default)]
+pub struct NormalizedX12 {
+    pub odds: [f64; 3], // home, draw, away
+    pub max_stake: Option<f64>,
+}
+
+/// A single Asian-handicap line, already converted to decimal odds.
+#[derive(Debug, Clone)]
+pub struct NormalizedSpread {
+    pub hdp: f64,
+    pub alt_line_id: i64,
+    pub home: f64,
+    pub away: f64,
+}
+
+/// A single totals (over/under) line, already converted to decimal odds.
+#[derive(Debug, Clone)]
+pub struct NormalizedTotal {
+    pub points: f64,
+    pub alt_line_id: i64,
+    pub over: f64,
+    pub under: f64,
+}
+
+/// A bookmaker's raw market shape, normalized into the common fields the
+/// `football_odds` upsert needs. Built by each `BookmakerIngestor::normalize` impl.
+#[derive(Debug, Clone, Default)]
+pub struct NormalizedMarket {
+    pub x12: Option<NormalizedX12>,
+    pub spreads: Vec<NormalizedSpread>,
+    pub totals: Vec<NormalizedTotal>,
+    pub max_stake_spread: Option<f64>,
+    pub max_stake_total: Option<f64>,
+    /// Source-specific line/market identifier, recorded alongside the per-line
+    /// `line_ids` in the `ids` history entry (e.g. Pinnacle's period `line_id`).
+    pub line_id: Option<i64>,
+}
+
+/// Common ingestion surface for a bookmaker source: translate its raw market type into
+/// `NormalizedMarket` and report how it identifies itself in `football_odds`. The shared
+/// merge/upsert logic lives in `FootballOddsWriter`, which every bookmaker reuses so all
+/// books feed `football_odds` the same way (a precondition for cross-book comparisons
+/// like arbitrage and fair-odds to have more than one book to compare).
+pub trait BookmakerIngestor {
+    type RawMarket;
+
+    fn bookie_name(&self) -> &'static str;
+    /// Decimal places the integer-encoded odds in `football_odds` are scaled by
+    /// (e.g. `3` means a 1.952 price is stored as `1952`).
+    fn decimals(&self) -> i32;
+    fn normalize(&self, raw: &Self::RawMarket) -> NormalizedMarket;
+}
+
+/// Shared write path for `football_odds`: merges a `NormalizedMarket` into the
+/// timestamped history arrays (`odds_x12`, `odds_ah`, `odds_ou`, `lines`, `ids`,
+/// `max_stakes`), computes fair probabilities for the 1X2 market, and upserts the row.
+/// Every `BookmakerIngestor` delegates here after normalizing its source-specific markets.
+pub struct FootballOddsWriter {
+    pool: PgPool,
+    fair_prob_method: FairProbMethod,
+}
+
+impl FootballOddsWriter {
+    pub fn new(pool: PgPool, fair_prob_method: FairProbMethod) -> Self {
+        Self { pool, fair_prob_method }
+    }
+
+    pub async fn write_entry(
+        &self,
+        fixture_id: i64,
+        event_id: i64,
+        bookie_name: &str,
+        decimals: i32,
+        market: &NormalizedMarket,
+        home_team: &str,
+        away_team: &str,
+        existing_data: Option<&Value>,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let timestamp = chrono::Utc::now().timestamp();
+        let scale = 10f64.powi(decimals);
+        let encode = |odds: f64| -> i32 { (odds * scale).round() as i32 };
+
+        let mut x12_odds = Vec::new();
+        let mut fair_probs = Vec::new();
+        if let Some(x12) = &market.x12 {
+            x12_odds.push(serde_json::json!({
+                "t": timestamp,
+                "x12": [encode(x12.odds[0]), encode(x12.odds[1]), encode(x12.odds[2])]
+            }));
+
+            if let Some(fair) = compute_fair_probs(x12.odds, self.fair_prob_method) {
+                fair_probs.push(serde_json::json!({ "t": timestamp, "fair": fair }));
+            }
+        }
+
+        let mut ah_odds = Vec::new();
+        let mut ou_odds = Vec::new();
+        let mut lines = Vec::new();
+        let mut ids = Vec::new();
+
+        let mut combined_line_entry = serde_json::Map::new();
+        combined_line_entry.insert("t".to_string(), serde_json::json!(timestamp));
+        let mut combined_id_entry = serde_json::Map::new();
+        combined_id_entry.insert("t".to_string(), serde_json::json!(timestamp));
+        if let Some(line_id) = market.line_id {
+            combined_id_entry.insert("line_id".to_string(), serde_json::json!(line_id));
+        }
+        let mut line_ids_map = serde_json::Map::new();
+
+        if !market.spreads.is_empty() {
+            let mut spreads = market.spreads.clone();
+            spreads.sort_by(|a, b| a.hdp.partial_cmp(&b.hdp).unwrap());
+
+            let ah_home: Vec<i32> = spreads.iter().map(|s| encode(s.home)).collect();
+            let ah_away: Vec<i32> = spreads.iter().map(|s| encode(s.away)).collect();
+            let ah_line_values: Vec<f64> = spreads.iter().map(|s| s.hdp).collect();
+            let ah_alt_line_ids: Vec<i64> = spreads.iter().map(|s| s.alt_line_id).collect();
+
+            ah_odds.push(serde_json::json!({ "t": timestamp, "ah_h": ah_home, "ah_a": ah_away }));
+            combined_line_entry.insert("ah".to_string(), serde_json::json!(ah_line_values));
+            line_ids_map.insert("ah".to_string(), serde_json::json!(ah_alt_line_ids));
+        }
+
+        if !market.totals.is_empty() {
+            let mut totals = market.totals.clone();
+            totals.sort_by(|a, b| a.points.partial_cmp(&b.points).unwrap());
+
+            let ou_over: Vec<i32> = totals.iter().map(|t| encode(t.over)).collect();
+            let ou_under: Vec<i32> = totals.iter().map(|t| encode(t.under)).collect();
+            let ou_line_values: Vec<f64> = totals.iter().map(|t| t.points).collect();
+            let ou_alt_line_ids: Vec<i64> = totals.iter().map(|t| t.alt_line_id).collect();
+
+            ou_odds.push(serde_json::json!({ "t": timestamp, "ou_o": ou_over, "ou_u": ou_under }));
+            combined_line_entry.insert("ou".to_string(), serde_json::json!(ou_line_values));
+            line_ids_map.insert("ou".to_string(), serde_json::json!(ou_alt_line_ids));
+        }
+
+        if combined_line_entry.contains_key("ah") || combined_line_entry.contains_key("ou") {
+            lines.push(Value::Object(combined_line_entry));
+        }
+        if !line_ids_map.is_empty() {
+            combined_id_entry.insert("line_ids".to_string(), Value::Object(line_ids_map));
+            ids.push(Value::Object(combined_id_entry));
+        }
+
+        let mut max_stakes = Vec::new();
+        if market.x12.as_ref().and_then(|x| x.max_stake).is_some()
+            || market.max_stake_spread.is_some()
+            || market.max_stake_total.is_some()
+        {
+            let mut stake_entry = serde_json::Map::new();
+            stake_entry.insert("t".to_string(), serde_json::json!(timestamp));
+            stake_entry.insert(
+                "max_stake_x12".to_string(),
+                match market.x12.as_ref().and_then(|x| x.max_stake) {
+                    Some(max) => serde_json::json!([max]),
+                    None => serde_json::json!([]),
+                },
+            );
+            stake_entry.insert(
+                "max_stake_ah".to_string(),
+                match market.max_stake_spread {
+                    Some(max) => serde_json::json!({"h": [max], "a": [max]}),
+                    None => serde_json::json!({}),
+                },
+            );
+            stake_entry.insert(
+                "max_stake_ou".to_string(),
+                match market.max_stake_total {
+                    Some(max) => serde_json::json!({"o": [max], "u": [max]}),
+                    None => serde_json::json!({}),
+                },
+            );
+            max_stakes.push(Value::Object(stake_entry));
+        }
+
+        let mut final_x12 = x12_odds;
+        let mut final_ah = ah_odds;
+        let mut final_ou = ou_odds;
+        let mut final_lines = lines;
+        let mut final_max_stakes = max_stakes;
+        let mut final_fair_probs = fair_probs;
+
+        let mut updates = Vec::new();
+
+        if let Some(existing) = existing_data {
+            let ex_x12 = existing.get("oddsX12").and_then(|v| v.as_array()).map(|v| v.len()).unwrap_or(0);
+            final_x12 = Self::merge_history(existing.get("oddsX12"), final_x12);
+            if final_x12.len() > ex_x12 { updates.push("X12"); }
+
+            let ex_fair = existing.get("fairProbs").and_then(|v| v.as_array()).map(|v| v.len()).unwrap_or(0);
+            final_fair_probs = Self::merge_history(existing.get("fairProbs"), final_fair_probs);
+            if final_fair_probs.len() > ex_fair { updates.push("FairProbs"); }
+
+            let ex_ah = existing.get("oddsAh").and_then(|v| v.as_array()).map(|v| v.len()).unwrap_or(0);
+            final_ah = Self::merge_history(existing.get("oddsAh"), final_ah);
+            if final_ah.len() > ex_ah { updates.push("AH"); }
+
+            let ex_ou = existing.get("oddsOu").and_then(|v| v.as_array()).map(|v| v.len()).unwrap_or(0);
+            final_ou = Self::merge_history(existing.get("oddsOu"), final_ou);
+            if final_ou.len() > ex_ou { updates.push("OU"); }
+
+            let ex_lines = existing.get("lines").and_then(|v| v.as_array()).map(|v| v.len()).unwrap_or(0);
+            final_lines = Self::merge_history(existing.get("lines"), final_lines);
+            if final_lines.len() > ex_lines { updates.push("Lines"); }
+
+            let ex_stakes = existing.get("maxStakes").and_then(|v| v.as_array()).map(|v| v.len()).unwrap_or(0);
+            final_max_stakes = Self::merge_history(existing.get("maxStakes"), final_max_stakes);
+            if final_max_stakes.len() > ex_stakes { updates.push("Stakes"); }
+        } else {
+            if !final_x12.is_empty() { updates.push("X12"); }
+            if !final_fair_probs.is_empty() { updates.push("FairProbs"); }
+            if !final_ah.is_empty() { updates.push("AH"); }
+            if !final_ou.is_empty() { updates.push("OU"); }
+            if !final_lines.is_empty() { updates.push("Lines"); }
+            if !final_max_stakes.is_empty() { updates.push("Stakes"); }
+        }
+
+        let mut latest_t = if let Some(existing) = existing_data {
+            existing.get("latestT").cloned().unwrap_or(serde_json::json!({}))
+        } else {
+            serde_json::json!({})
+        };
+
+        if let Some(obj) = latest_t.as_object_mut() {
+            if !final_x12.is_empty() { obj.insert("x12_ts".to_string(), serde_json::json!(timestamp)); }
+            if !final_fair_probs.is_empty() { obj.insert("fair_probs_ts".to_string(), serde_json::json!(timestamp)); }
+            if !final_ah.is_empty() { obj.insert("ah_ts".to_string(), serde_json::json!(timestamp)); }
+            if !final_ou.is_empty() { obj.insert("ou_ts".to_string(), serde_json::json!(timestamp)); }
+            if !final_lines.is_empty() { obj.insert("lines_ts".to_string(), serde_json::json!(timestamp)); }
+            if !ids.is_empty() { obj.insert("ids_ts".to_string(), serde_json::json!(timestamp)); }
+        }
+
+        if updates.is_empty() {
+            return Ok(false);
+        }
+
+        let upsert_query = r#"
+        INSERT INTO football_odds (
+            fixture_id, bookie_id, bookie, decimals,
+            odds_x12, odds_ah, odds_ou, lines, ids, max_stakes, latest_t, fair_probs, updated_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        ON CONFLICT (fixture_id, bookie) DO UPDATE SET
+            bookie_id = EXCLUDED.bookie_id,
+            odds_x12 = EXCLUDED.odds_x12,
+            odds_ah = EXCLUDED.odds_ah,
+            odds_ou = EXCLUDED.odds_ou,
+            lines = EXCLUDED.lines,
+            ids = EXCLUDED.ids,
+            max_stakes = EXCLUDED.max_stakes,
+            latest_t = EXCLUDED.latest_t,
+            fair_probs = EXCLUDED.fair_probs,
+            updated_at = EXCLUDED.updated_at
+        "#;
+
+        sqlx::query(upsert_query)
+            .bind(fixture_id)
+            .bind(event_id)
+            .bind(bookie_name)
+            .bind(decimals)
+            .bind(if !final_x12.is_empty() { Some(serde_json::json!(final_x12)) } else { None })
+            .bind(if !final_ah.is_empty() { Some(serde_json::json!(final_ah)) } else { None })
+            .bind(if !final_ou.is_empty() { Some(serde_json::json!(final_ou)) } else { None })
+            .bind(if !final_lines.is_empty() { Some(serde_json::json!(final_lines)) } else { None })
+            .bind(if !ids.is_empty() { Some(serde_json::json!(ids)) } else { None })
+            .bind(if !final_max_stakes.is_empty() { Some(serde_json::json!(final_max_stakes)) } else { None })
+            .bind(latest_t)
+            .bind(if !final_fair_probs.is_empty() { Some(serde_json::json!(final_fair_probs)) } else { None })
+            .bind(chrono::Utc::now())
+            .execute(&self.pool)
+            .await?;
+
+        info!("✅ Updated odds for {} v {} (fixture: {}, bookie: {}). Changes: {:?}.", home_team, away_team, fixture_id, bookie_name, updates);
+        Ok(true)
+    }
+
+    fn merge_history(existing: Option<&Value>, new_items: Vec<Value>) -> Vec<Value> {
+        if new_items.is_empty() {
+            return existing.cloned().unwrap_or(serde_json::json!([])).as_array().cloned().unwrap_or_default();
+        }
+
+        let mut result = existing.cloned().unwrap_or(serde_json::json!([])).as_array().cloned().unwrap_or_default();
+
+        if !result.is_empty() {
+            let last = &result[result.len() - 1];
+            let new_item = &new_items[0];
+
+            if Self::is_different(last, new_item) {
+                result.push(new_item.clone());
+            }
+        } else {
+            result.push(new_items[0].clone());
+        }
+
+        result
+    }
+
+    fn is_different(v1: &Value, v2: &Value) -> bool {
+        let mut c1 = v1.clone();
+        let mut c2 = v2.clone();
+
+        if let Some(obj) = c1.as_object_mut() { obj.remove("t"); }
+        if let Some(obj) = c2.as_object_mut() { obj.remove("t"); }
+
+        c1 != c2
+    }
+}
@@ -0,0 +1,244 @@
+// OHLC candle aggregation over the raw Monaco order book, built straight from the
+// top-of-book tick stream in `monaco::handlers::handle_price_update` — before the
+// per-outcome prices are ever folded into an `OddsUpdate` and sent to odds-processor.
+//
+// This is deliberately a separate series from odds-processor's own `odds_candles`
+// table (see `odds-processor/src/candles.rs`): that one is keyed by bookmaker and
+// built from the merged `OddsUpdate` stream downstream of this process, so reusing
+// its table name here would mean two independent aggregators racing to upsert the
+// same rows for what looks like, but isn't, the same tick. This module's candles are
+// keyed by `(fixture_id, market_type, outcome_id)` only, since at this point in the
+// pipeline every tick is already known to be Monaco's.
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// Identifies a single candle series: one outcome of one market, on one fixture.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CandleKey {
+    pub fixture_id: i64,
+    pub market_type: String,
+    pub outcome_id: String,
+}
+
+/// A single OHLC bucket, expressed in decimal odds. `volume` is the summed
+/// liquidity observed across every tick folded into the bucket, `ticks` the number of
+/// ticks folded in (used to detect a bucket that hasn't changed since it was last
+/// persisted, so the continuous in-progress rewrite can skip a redundant write).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    pub bucket_start: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub ticks: u32,
+}
+
+impl Candle {
+    fn open_at(bucket_start: i64, price: f64, liquidity: f64) -> Self {
+        Self {
+            bucket_start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: liquidity,
+            ticks: 1,
+        }
+    }
+
+    /// A zero-volume bucket carrying a previous close forward, used to backfill
+    /// gaps where no tick landed.
+    fn flat(bucket_start: i64, price: f64) -> Self {
+        Self {
+            bucket_start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 0.0,
+            ticks: 0,
+        }
+    }
+
+    fn apply(&mut self, price: f64, liquidity: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += liquidity;
+        self.ticks += 1;
+    }
+}
+
+/// Accumulates live top-of-book ticks into in-progress candles across several fixed
+/// granularities at once (e.g. 1m/5m/1h), handing back every candle that closed as a
+/// result of a tick (normally at most one per interval, but a gap in the tick stream can
+/// close several at once via backfill).
+pub struct CandleAggregator {
+    intervals_ms: Vec<i64>,
+    open_candles: HashMap<(CandleKey, i64), Candle>,
+}
+
+impl CandleAggregator {
+    pub fn new(intervals_secs: &[i64]) -> Self {
+        Self {
+            intervals_ms: intervals_secs.iter().map(|s| s.max(&1) * 1000).collect(),
+            open_candles: HashMap::new(),
+        }
+    }
+
+    /// Record a tick (decimal odds price and liquidity at a millisecond timestamp).
+    /// Returns the candles that just closed across every configured interval, tagged
+    /// with their `interval_secs`. If the previous tick for a key landed more than one
+    /// bucket ago, the skipped buckets are backfilled with flat, zero-volume candles
+    /// carrying the previous close forward, so a chart built from this series has no
+    /// holes.
+    pub fn record(
+        &mut self,
+        key: CandleKey,
+        price: f64,
+        liquidity: f64,
+        timestamp_ms: i64,
+    ) -> Vec<(i64, Candle)> {
+        let mut closed = Vec::new();
+
+        for &interval_ms in &self.intervals_ms {
+            let interval_secs = interval_ms / 1000;
+            let bucket_start = (timestamp_ms / interval_ms) * interval_ms;
+            let map_key = (key.clone(), interval_ms);
+
+            match self.open_candles.get_mut(&map_key) {
+                Some(candle) if candle.bucket_start == bucket_start => {
+                    candle.apply(price, liquidity);
+                }
+                Some(candle) => {
+                    closed.push((interval_secs, candle.clone()));
+
+                    let mut backfill_start = candle.bucket_start + interval_ms;
+                    let last_close = candle.close;
+                    while backfill_start < bucket_start {
+                        closed.push((interval_secs, Candle::flat(backfill_start, last_close)));
+                        backfill_start += interval_ms;
+                    }
+
+                    self.open_candles
+                        .insert(map_key, Candle::open_at(bucket_start, price, liquidity));
+                }
+                None => {
+                    self.open_candles
+                        .insert(map_key, Candle::open_at(bucket_start, price, liquidity));
+                }
+            }
+        }
+
+        closed
+    }
+
+    /// Force-close and remove every in-progress candle (across every interval) for a
+    /// market (all of its outcomes), for when `handle_market_status_update` zeroes the
+    /// order book out early instead of waiting for a tick that will never come to roll
+    /// it over.
+    pub fn flush_market(&mut self, fixture_id: i64, market_type: &str) -> Vec<(CandleKey, i64, Candle)> {
+        let map_keys: Vec<(CandleKey, i64)> = self
+            .open_candles
+            .keys()
+            .filter(|(k, _)| k.fixture_id == fixture_id && k.market_type == market_type)
+            .cloned()
+            .collect();
+
+        map_keys
+            .into_iter()
+            .filter_map(|map_key| {
+                self.open_candles
+                    .remove(&map_key)
+                    .map(|c| (map_key.0, map_key.1 / 1000, c))
+            })
+            .collect()
+    }
+
+    /// A snapshot of every currently in-progress candle, across every key and interval.
+    /// Used by the periodic flush task to continuously rewrite the current bucket
+    /// instead of only persisting once it closes.
+    pub fn open_snapshot(&self) -> Vec<(CandleKey, i64, Candle)> {
+        self.open_candles
+            .iter()
+            .map(|((key, interval_ms), candle)| (key.clone(), interval_ms / 1000, candle.clone()))
+            .collect()
+    }
+}
+
+/// Persist one candle, upserting on (fixture_id, market_type, outcome_id, interval_secs,
+/// bucket_start). `complete` marks whether the candle's interval has fully elapsed;
+/// `false` candles keep getting overwritten by later ticks/flushes of the same bucket,
+/// `true` ones are final.
+pub async fn persist_candle(
+    pool: &PgPool,
+    interval_secs: i64,
+    key: &CandleKey,
+    candle: &Candle,
+    complete: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO engine_odds_candles
+            (fixture_id, market_type, outcome_id, interval_secs, bucket_start, open, high, low, close, volume, ticks, complete)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        ON CONFLICT (fixture_id, market_type, outcome_id, interval_secs, bucket_start)
+        DO UPDATE SET high = GREATEST(engine_odds_candles.high, EXCLUDED.high),
+                      low = LEAST(engine_odds_candles.low, EXCLUDED.low),
+                      close = EXCLUDED.close,
+                      volume = EXCLUDED.volume,
+                      ticks = EXCLUDED.ticks,
+                      complete = EXCLUDED.complete
+        "#,
+    )
+    .bind(key.fixture_id)
+    .bind(&key.market_type)
+    .bind(&key.outcome_id)
+    .bind(interval_secs)
+    .bind(candle.bucket_start)
+    .bind(candle.open)
+    .bind(candle.high)
+    .bind(candle.low)
+    .bind(candle.close)
+    .bind(candle.volume)
+    .bind(candle.ticks as i32)
+    .bind(complete)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Periodically rewrites every still-open candle so downstream consumers see the
+/// current bucket update live rather than only once it closes, skipping a key/interval
+/// whose tick count hasn't changed since the last flush so a quiet market isn't
+/// rewritten with an identical row every cycle.
+pub async fn run_open_candle_flush(
+    pool: PgPool,
+    aggregator: std::sync::Arc<tokio::sync::Mutex<CandleAggregator>>,
+    flush_interval: std::time::Duration,
+) {
+    let mut last_flushed_ticks: HashMap<(CandleKey, i64), u32> = HashMap::new();
+    let mut interval = tokio::time::interval(flush_interval);
+
+    loop {
+        interval.tick().await;
+
+        let open = aggregator.lock().await.open_snapshot();
+        for (key, interval_secs, candle) in open {
+            let map_key = (key.clone(), interval_secs);
+            if last_flushed_ticks.get(&map_key) == Some(&candle.ticks) {
+                continue;
+            }
+
+            if let Err(e) = persist_candle(&pool, interval_secs, &key, &candle, false).await {
+                tracing::error!("Failed to flush in-progress candle: {}", e);
+                continue;
+            }
+            last_flushed_ticks.insert(map_key, candle.ticks);
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use std::env;
+use std::sync::Arc;
+use arc_swap::ArcSwap;
+use tracing::{info, warn};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Config {
     pub database_url: String,
     pub monaco_base_url: String,
@@ -10,8 +13,96 @@ pub struct Config {
     pub server_port: u16,
     pub monaco_odds_enabled: bool,
     pub pinnacle_odds_enabled: bool,
+    pub betfair_odds_enabled: bool,
+    /// Comma-separated `marketId:eventId:fixtureId` triples to poll; see
+    /// `betfair::service::parse_tracked_markets`.
+    pub betfair_markets: String,
+    pub betfair_poll_interval_secs: u64,
     pub processor_enabled: bool,
     pub processor_port: u16,
+    pub processor_queue_capacity: usize,
+    pub processor_use_msgpack: bool,
+    /// OHLC granularities the live Monaco candle aggregator tracks simultaneously, in
+    /// seconds (e.g. `[60, 300, 3600]` for 1m/5m/1h).
+    pub candle_intervals_secs: Vec<i64>,
+    /// How often the still-open bucket of each interval is rewritten to `engine_odds_candles`
+    /// so consumers see it update live instead of only once it closes.
+    pub candle_flush_interval_secs: u64,
+    pub depth_enabled: bool,
+    pub depth_port: u16,
+    pub depth_queue_capacity: usize,
+    pub arbitrage_enabled: bool,
+    pub arbitrage_freshness_secs: i64,
+    pub arbitrage_min_delay_secs: u64,
+    pub arbitrage_max_delay_secs: u64,
+    pub arbitrage_total_stake: f64,
+    /// Opportunities below this guaranteed-return fraction are discarded as noise.
+    pub arbitrage_min_margin: f64,
+    /// Opportunities whose available liquidity (min of per-leg quoted max stake) falls
+    /// below this are discarded as unfillable.
+    pub arbitrage_min_liquidity: f64,
+    pub fair_prob_method: String,
+    pub ratings_enabled: bool,
+    pub ratings_refresh_interval_secs: u64,
+    pub ratings_half_life_days: f64,
+    /// Whether to periodically rebuild `football_odds_candles` from stored snapshot
+    /// history (`history_candles::build_candles`), independent of the live Monaco
+    /// candle aggregator.
+    pub history_candles_enabled: bool,
+    pub history_candles_refresh_interval_secs: u64,
+    pub line_movement_enabled: bool,
+    pub line_movement_scan_interval_secs: u64,
+    pub line_movement_prob_threshold: f64,
+    pub line_movement_window_secs: i64,
+    pub middles_enabled: bool,
+    pub middles_scan_interval_secs: u64,
+    pub middles_min_window_size: f64,
+    /// Trigram similarity a fuzzy team-name match must clear before `find_fixture_by_event`
+    /// accepts it (and learns it as a new alias).
+    pub team_alias_similarity_threshold: f64,
+    /// How far on either side of a Monaco event's expected start time to look for a matching
+    /// fixture.
+    pub team_alias_window_hours: i64,
+    pub arbitrer_enabled: bool,
+    /// Opportunities below this guaranteed-return fraction are discarded as noise.
+    pub arbitrer_min_margin: f64,
+    pub arbitrer_min_delay_secs: u64,
+    pub arbitrer_max_delay_secs: u64,
+    /// Path to the TOML file declaring Monaco market-type/line-value mapping rules, loaded
+    /// once at startup.
+    pub market_rules_path: String,
+    /// Whether to run a one-shot historical backfill on startup, independent of the live
+    /// Monaco ingestion loop.
+    pub backfill_enabled: bool,
+    /// Start of the backfill window, RFC3339 (e.g. `2026-01-01T00:00:00Z`).
+    pub backfill_start: String,
+    /// End of the backfill window, RFC3339.
+    pub backfill_end: String,
+    /// Window size, in days, paged through the Monaco API per backfill batch.
+    pub backfill_batch_days: i64,
+    /// Markets API page size used while paging through each backfill window.
+    pub backfill_page_size: u32,
+    /// How many backfill windows are fetched/processed concurrently.
+    pub backfill_concurrency: usize,
+    /// Snapshots older than this are pruned from `odds_x12`/`odds_ah`/`odds_ou`/
+    /// `depth_*` on every write, independent of count.
+    pub odds_history_max_age_secs: i64,
+    /// Each history is also capped at this many snapshots regardless of age, so a
+    /// fixture ticking constantly can't grow its row unbounded within the age window.
+    pub odds_history_max_entries: usize,
+    /// Whether consecutive snapshots with no price change are collapsed down to just
+    /// the first/last of the unchanged run.
+    pub odds_history_dedup_unchanged: bool,
+    /// Per-market-type vig applied when encoding a decimal price into the stored integer
+    /// (e.g. `0.99` shaves 1% off the true price).
+    pub odds_transform_margin_x12: f64,
+    pub odds_transform_margin_ah: f64,
+    pub odds_transform_margin_ou: f64,
+    /// Scale applied after the margin, before rounding (the stored integer is roughly
+    /// `decimal_price * scale`).
+    pub odds_transform_scale: f64,
+    /// `"floor"`, `"round"`, or `"ceil"` - how the scaled, margined price becomes an integer.
+    pub odds_transform_rounding: String,
 }
 
 impl Config {
@@ -32,11 +123,219 @@ impl Config {
             server_port,
             monaco_odds_enabled: env::var("MONACO_ODDS").map(|v| v == "true").unwrap_or(true),
             pinnacle_odds_enabled: env::var("PINNACLE_ODDS").map(|v| v == "true").unwrap_or(true),
+            betfair_odds_enabled: env::var("BETFAIR_ODDS").map(|v| v == "true").unwrap_or(false),
+            betfair_markets: env::var("BETFAIR_MARKETS").unwrap_or_default(),
+            betfair_poll_interval_secs: env::var("BETFAIR_POLL_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
             processor_enabled: env::var("PROCESSOR_ENABLED").map(|v| v == "true").unwrap_or(true),
             processor_port: env::var("PROCESSOR_PORT")
                 .unwrap_or_else(|_| "9000".to_string())
                 .parse()
                 .unwrap_or(9000),
+            processor_queue_capacity: env::var("PROCESSOR_QUEUE_CAPACITY")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .unwrap_or(10000),
+            processor_use_msgpack: env::var("PROCESSOR_USE_MSGPACK").map(|v| v == "true").unwrap_or(false),
+            candle_intervals_secs: env::var("CANDLE_INTERVALS_SECS")
+                .unwrap_or_else(|_| "60,300,3600".to_string())
+                .split(',')
+                .filter_map(|s| s.trim().parse::<i64>().ok())
+                .filter(|&s| s > 0)
+                .collect(),
+            candle_flush_interval_secs: env::var("CANDLE_FLUSH_INTERVAL_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            depth_enabled: env::var("DEPTH_ENABLED").map(|v| v == "true").unwrap_or(false),
+            depth_port: env::var("DEPTH_PORT")
+                .unwrap_or_else(|_| "9001".to_string())
+                .parse()
+                .unwrap_or(9001),
+            depth_queue_capacity: env::var("DEPTH_QUEUE_CAPACITY")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .unwrap_or(10000),
+            arbitrage_enabled: env::var("ARBITRAGE_ENABLED").map(|v| v == "true").unwrap_or(false),
+            arbitrage_freshness_secs: env::var("ARBITRAGE_FRESHNESS_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            arbitrage_min_delay_secs: env::var("ARBITRAGE_MIN_DELAY_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            arbitrage_max_delay_secs: env::var("ARBITRAGE_MAX_DELAY_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            arbitrage_total_stake: env::var("ARBITRAGE_TOTAL_STAKE")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100.0),
+            arbitrage_min_margin: env::var("ARBITRAGE_MIN_MARGIN")
+                .unwrap_or_else(|_| "0.01".to_string())
+                .parse()
+                .unwrap_or(0.01),
+            arbitrage_min_liquidity: env::var("ARBITRAGE_MIN_LIQUIDITY")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0),
+            fair_prob_method: env::var("FAIR_PROB_METHOD").unwrap_or_else(|_| "multiplicative".to_string()),
+            ratings_enabled: env::var("RATINGS_ENABLED").map(|v| v == "true").unwrap_or(false),
+            ratings_refresh_interval_secs: env::var("RATINGS_REFRESH_INTERVAL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            ratings_half_life_days: env::var("RATINGS_HALF_LIFE_DAYS")
+                .unwrap_or_else(|_| "180".to_string())
+                .parse()
+                .unwrap_or(180.0),
+            history_candles_enabled: env::var("HISTORY_CANDLES_ENABLED").map(|v| v == "true").unwrap_or(false),
+            history_candles_refresh_interval_secs: env::var("HISTORY_CANDLES_REFRESH_INTERVAL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            line_movement_enabled: env::var("LINE_MOVEMENT_ENABLED").map(|v| v == "true").unwrap_or(false),
+            line_movement_scan_interval_secs: env::var("LINE_MOVEMENT_SCAN_INTERVAL_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            line_movement_prob_threshold: env::var("LINE_MOVEMENT_PROB_THRESHOLD")
+                .unwrap_or_else(|_| "0.05".to_string())
+                .parse()
+                .unwrap_or(0.05),
+            line_movement_window_secs: env::var("LINE_MOVEMENT_WINDOW_SECS")
+                .unwrap_or_else(|_| "900".to_string())
+                .parse()
+                .unwrap_or(900),
+            middles_enabled: env::var("MIDDLES_ENABLED").map(|v| v == "true").unwrap_or(false),
+            middles_scan_interval_secs: env::var("MIDDLES_SCAN_INTERVAL_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            middles_min_window_size: env::var("MIDDLES_MIN_WINDOW_SIZE")
+                .unwrap_or_else(|_| "0.5".to_string())
+                .parse()
+                .unwrap_or(0.5),
+            team_alias_similarity_threshold: env::var("TEAM_ALIAS_SIMILARITY_THRESHOLD")
+                .unwrap_or_else(|_| "0.6".to_string())
+                .parse()
+                .unwrap_or(0.6),
+            team_alias_window_hours: env::var("TEAM_ALIAS_WINDOW_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse()
+                .unwrap_or(24),
+            arbitrer_enabled: env::var("ARBITRER_ENABLED").map(|v| v == "true").unwrap_or(false),
+            arbitrer_min_margin: env::var("ARBITRER_MIN_MARGIN")
+                .unwrap_or_else(|_| "0.01".to_string())
+                .parse()
+                .unwrap_or(0.01),
+            arbitrer_min_delay_secs: env::var("ARBITRER_MIN_DELAY_SECS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2),
+            arbitrer_max_delay_secs: env::var("ARBITRER_MAX_DELAY_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            market_rules_path: env::var("MARKET_RULES_PATH")
+                .unwrap_or_else(|_| "market_rules.toml".to_string()),
+            backfill_enabled: env::var("BACKFILL_ENABLED").map(|v| v == "true").unwrap_or(false),
+            backfill_start: env::var("BACKFILL_START").unwrap_or_default(),
+            backfill_end: env::var("BACKFILL_END").unwrap_or_default(),
+            backfill_batch_days: env::var("BACKFILL_BATCH_DAYS")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()
+                .unwrap_or(1),
+            backfill_page_size: env::var("BACKFILL_PAGE_SIZE")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .unwrap_or(500),
+            backfill_concurrency: env::var("BACKFILL_CONCURRENCY")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2),
+            odds_history_max_age_secs: env::var("ODDS_HISTORY_MAX_AGE_SECS")
+                .unwrap_or_else(|_| (30 * 24 * 60 * 60).to_string())
+                .parse()
+                .unwrap_or(30 * 24 * 60 * 60),
+            odds_history_max_entries: env::var("ODDS_HISTORY_MAX_ENTRIES")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+            odds_history_dedup_unchanged: env::var("ODDS_HISTORY_DEDUP_UNCHANGED")
+                .map(|v| v == "true")
+                .unwrap_or(true),
+            odds_transform_margin_x12: env::var("ODDS_TRANSFORM_MARGIN_X12")
+                .unwrap_or_else(|_| "0.99".to_string())
+                .parse()
+                .unwrap_or(0.99),
+            odds_transform_margin_ah: env::var("ODDS_TRANSFORM_MARGIN_AH")
+                .unwrap_or_else(|_| "0.99".to_string())
+                .parse()
+                .unwrap_or(0.99),
+            odds_transform_margin_ou: env::var("ODDS_TRANSFORM_MARGIN_OU")
+                .unwrap_or_else(|_| "0.99".to_string())
+                .parse()
+                .unwrap_or(0.99),
+            odds_transform_scale: env::var("ODDS_TRANSFORM_SCALE")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .unwrap_or(1000.0),
+            odds_transform_rounding: env::var("ODDS_TRANSFORM_ROUNDING")
+                .unwrap_or_else(|_| "floor".to_string()),
         }
     }
 }
+
+/// Config shared across the app behind a lock-free swap, so a reload can publish
+/// a new snapshot without readers ever blocking on it.
+pub type SharedConfig = Arc<ArcSwap<Config>>;
+
+/// Watch for `SIGHUP` and hot-reload configuration from the environment on receipt.
+///
+/// Fields that can't be changed without a restart (`database_url`, the already-bound
+/// `server_port`) are kept at their original value and a warning is logged instead of
+/// applying them. Everything else is swapped in, and `on_change` is called with the
+/// (old, new) pair so callers can react to things like the Monaco/Pinnacle enabled flags
+/// flipping by starting or stopping their ingestion tasks.
+pub fn spawn_reload_watcher<F>(shared: SharedConfig, mut on_change: F)
+where
+    F: FnMut(&Config, &Config) + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut sighup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("Failed to install SIGHUP handler, config hot-reload disabled: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            if sighup.recv().await.is_none() {
+                return;
+            }
+
+            info!("📋 SIGHUP received, reloading configuration from environment...");
+            let old = shared.load_full();
+            let mut new = Config::from_env();
+
+            if new.database_url != old.database_url {
+                warn!("DATABASE_URL changed but cannot be applied without a restart; keeping existing connection");
+                new.database_url = old.database_url.clone();
+            }
+            if new.server_port != old.server_port {
+                warn!("PORT changed but the listener is already bound; keeping {}", old.server_port);
+                new.server_port = old.server_port;
+            }
+
+            on_change(&old, &new);
+            shared.store(Arc::new(new));
+            info!("✅ Configuration reloaded");
+        }
+    });
+}
@@ -0,0 +1,241 @@
+// Full depth-of-book side channel: unlike the flattened top-of-book `OddsUpdate`s sent
+// over `processor_client`, this streams the entire price ladder per outcome so a
+// consumer can reconstruct the book rather than only ever seeing `price_levels[0]`.
+// On first sight of a market it sends a full `BookCheckpoint`; every later tick sends
+// only the levels that actually changed as a `LevelUpdate`, the way an exchange
+// order-book feed sends a snapshot once and diffs after. No listener consumes this
+// feed yet (nothing downstream models full depth today) - this is the sending half of
+// the protocol, wired up so a depth-aware consumer can be added without touching the
+// price-update handler again.
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::{Mutex, Notify};
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use crate::shared::types::PriceLevel;
+
+/// One price level, as sent on the wire.
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceLevelDto {
+    pub price: f64,
+    pub liquidity: f64,
+}
+
+/// A market's full ladder at the time this market was first seen, keyed by outcome_id.
+#[derive(Debug, Clone, Serialize)]
+pub struct BookCheckpoint {
+    pub fixture_id: i64,
+    pub market_type: String,
+    pub sequence: i64,
+    pub levels: HashMap<String, Vec<PriceLevelDto>>,
+}
+
+/// Only the levels that changed since the last checkpoint/update for this market,
+/// keyed by outcome_id. A level with `liquidity == 0.0` means that price was removed.
+#[derive(Debug, Clone, Serialize)]
+pub struct LevelUpdate {
+    pub fixture_id: i64,
+    pub market_type: String,
+    pub sequence: i64,
+    pub changed: HashMap<String, Vec<PriceLevelDto>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind")]
+pub enum BookMessage {
+    Checkpoint(BookCheckpoint),
+    Update(LevelUpdate),
+}
+
+/// Tracks, per `(fixture_id, market_type)`, whether a checkpoint has been sent yet and
+/// the highest sequence number applied so far - mirroring how the order book itself
+/// drops stale/out-of-order `MarketPriceUpdate`s, but for the depth feed's own
+/// checkpoint/diff state rather than the ladder contents.
+#[derive(Default)]
+pub struct DepthTracker {
+    last_sequence: HashMap<(i64, String), i64>,
+    checkpointed: HashSet<(i64, String)>,
+}
+
+impl DepthTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Derive this update's sequence number from Monaco's `validAt` (millisecond
+    /// precision is enough to order ticks for one market), falling back to the
+    /// previous sequence plus one if `validAt` is missing or unparseable.
+    pub fn derive_sequence(&self, fixture_id: i64, market_type: &str, message: &serde_json::Value) -> i64 {
+        if let Some(valid_at_str) = message["prices"][0]["validAt"].as_str() {
+            if let Ok(valid_at_dt) = chrono::DateTime::parse_from_rfc3339(valid_at_str) {
+                return valid_at_dt.timestamp_millis();
+            }
+        }
+        let key = (fixture_id, market_type.to_string());
+        self.last_sequence.get(&key).copied().unwrap_or(0) + 1
+    }
+
+    /// Returns `true` and records `sequence` as the new high-water mark if it's newer
+    /// than the last sequence applied for this market; returns `false` (leaving state
+    /// untouched) for a stale or duplicate sequence, which the caller should drop.
+    pub fn accept(&mut self, fixture_id: i64, market_type: &str, sequence: i64) -> bool {
+        let key = (fixture_id, market_type.to_string());
+        match self.last_sequence.get(&key) {
+            Some(&last) if sequence <= last => false,
+            _ => {
+                self.last_sequence.insert(key, sequence);
+                true
+            }
+        }
+    }
+
+    /// Returns `true` exactly once per market: the first call after a market is seen,
+    /// signalling the caller should send a full checkpoint instead of a diff.
+    pub fn needs_checkpoint(&mut self, fixture_id: i64, market_type: &str) -> bool {
+        self.checkpointed.insert((fixture_id, market_type.to_string()))
+    }
+}
+
+/// Diff two price ladders for one outcome, returning every level that's new, changed,
+/// or removed (removed levels are reported with `liquidity: 0.0`).
+pub fn diff_levels(old: &[PriceLevel], new: &[PriceLevel]) -> Vec<PriceLevelDto> {
+    let mut changed: Vec<PriceLevelDto> = new
+        .iter()
+        .filter(|level| {
+            !old.iter()
+                .any(|o| o.price == level.price && o.liquidity == level.liquidity)
+        })
+        .map(|level| PriceLevelDto {
+            price: level.price,
+            liquidity: level.liquidity,
+        })
+        .collect();
+
+    changed.extend(old.iter().filter(|level| !new.iter().any(|n| n.price == level.price)).map(|level| PriceLevelDto {
+        price: level.price,
+        liquidity: 0.0,
+    }));
+
+    changed
+}
+
+/// Client for sending depth-of-book messages. Follows the same bounded-queue,
+/// reconnect-with-backoff shape as `ProcessorClient`, kept as its own type rather than
+/// generalizing `ProcessorClient` over the message type, since the two protocols (and
+/// their consumers) are independent.
+pub struct DepthClient {
+    addr: String,
+    capacity: usize,
+    queue: Mutex<VecDeque<BookMessage>>,
+    notify: Notify,
+    dropped: AtomicU64,
+}
+
+impl DepthClient {
+    pub fn new(addr: &str, capacity: usize) -> Self {
+        Self {
+            addr: addr.to_string(),
+            capacity,
+            queue: Mutex::new(VecDeque::with_capacity(capacity.min(1024))),
+            notify: Notify::new(),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub async fn send(&self, msg: BookMessage) {
+        let mut queue = self.queue.lock().await;
+        if queue.len() >= self.capacity {
+            queue.pop_front();
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            warn!("⚠️ Depth queue full (capacity={}), dropped oldest message (total dropped: {})", self.capacity, dropped);
+        }
+        queue.push_back(msg);
+        drop(queue);
+        self.notify.notify_one();
+    }
+
+    async fn dequeue(&self) -> BookMessage {
+        loop {
+            {
+                let mut queue = self.queue.lock().await;
+                if let Some(msg) = queue.pop_front() {
+                    return msg;
+                }
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    async fn requeue_front(&self, msg: BookMessage) {
+        self.queue.lock().await.push_front(msg);
+    }
+
+    async fn connect_and_drain(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        info!("🔌 Connecting to depth sink at {}...", self.addr);
+        let mut stream = TcpStream::connect(&self.addr).await?;
+        info!("✅ Connected to depth sink at {}", self.addr);
+
+        loop {
+            let msg = self.dequeue().await;
+            let json = serde_json::to_string(&msg)?;
+            let line = format!("{}\n", json);
+
+            if let Err(e) = stream.write_all(line.as_bytes()).await {
+                warn!("⚠️ Failed to send depth message: {}. Dropping connection.", e);
+                self.requeue_front(msg).await;
+                return Err(e.into());
+            }
+        }
+    }
+
+    async fn run(self: Arc<Self>) {
+        let mut retry_count: u32 = 0;
+        let max_backoff_secs = 60;
+
+        loop {
+            let backoff_secs = if retry_count == 0 {
+                0
+            } else {
+                std::cmp::min(2u64.pow(retry_count.saturating_sub(1)), max_backoff_secs)
+            };
+
+            if backoff_secs > 0 {
+                info!("⏳ Waiting {}s before reconnecting to depth sink (attempt {})...", backoff_secs, retry_count + 1);
+                sleep(Duration::from_secs(backoff_secs)).await;
+            }
+
+            match self.connect_and_drain().await {
+                Ok(_) => {
+                    retry_count = 0;
+                }
+                Err(e) => {
+                    retry_count = retry_count.saturating_add(1);
+                    warn!("❌ Depth sink connection error (attempt {}): {}", retry_count, e);
+                }
+            }
+        }
+    }
+}
+
+/// Create a shared depth client and spawn its background writer task.
+pub fn create_depth_client(enabled: bool, port: u16, queue_capacity: usize) -> Option<Arc<DepthClient>> {
+    if enabled {
+        let addr = format!("127.0.0.1:{}", port);
+        info!("🔗 Depth client will connect to {} (queue capacity: {})", addr, queue_capacity);
+        let client = Arc::new(DepthClient::new(&addr, queue_capacity));
+        tokio::spawn(client.clone().run());
+        Some(client)
+    } else {
+        info!("📡 Depth client disabled");
+        None
+    }
+}
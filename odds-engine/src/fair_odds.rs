@@ -0,0 +1,90 @@
+/// Margin-removal method used to turn a book's raw overround-loaded 1X2 odds into
+/// no-vig fair probabilities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FairProbMethod {
+    /// `p_i = (1/o_i) / Σ(1/o_j)` — scales implied probabilities down so they sum to 1.
+    /// Assumes the margin is spread evenly across outcomes, which isn't quite true but is
+    /// simple and always converges.
+    Multiplicative,
+    /// Models a fraction `z` of insider money skewing the book; solved by bisection so
+    /// that the resulting probabilities sum to 1.
+    Shin,
+}
+
+impl FairProbMethod {
+    pub fn from_env_str(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "shin" => FairProbMethod::Shin,
+            _ => FairProbMethod::Multiplicative,
+        }
+    }
+}
+
+const SHIN_BISECTION_ITERATIONS: u32 = 100;
+const SHIN_CONVERGENCE_EPS: f64 = 1e-9;
+
+/// Compute no-vig fair probabilities for a 1X2 market from decimal odds `[home, draw, away]`.
+///
+/// Returns `None` if any leg is missing (decimal odds `<= 1.0`, i.e. market not fully
+/// quoted). Falls back to the multiplicative method if Shin's method fails to converge.
+pub fn compute_fair_probs(decimal_odds: [f64; 3], method: FairProbMethod) -> Option<[f64; 3]> {
+    if decimal_odds.iter().any(|&o| o <= 1.0) {
+        return None;
+    }
+
+    match method {
+        FairProbMethod::Multiplicative => Some(multiplicative(decimal_odds)),
+        FairProbMethod::Shin => shin(decimal_odds).or_else(|| Some(multiplicative(decimal_odds))),
+    }
+}
+
+fn multiplicative(decimal_odds: [f64; 3]) -> [f64; 3] {
+    let implied: [f64; 3] = decimal_odds.map(|o| 1.0 / o);
+    let booksum: f64 = implied.iter().sum();
+    implied.map(|p| p / booksum)
+}
+
+/// Shin's (1992) method: `p_i = (sqrt(z^2 + 4(1-z) * (1/o_i)^2 / B) - z) / (2(1-z))`,
+/// where `B = Σ 1/o_i`. `z` is the unique root in `[0, 1)` of `Σ p_i(z) - 1 = 0`; the sum
+/// is monotonically decreasing in `z`, so bisection applies directly.
+fn shin(decimal_odds: [f64; 3]) -> Option<[f64; 3]> {
+    let implied: [f64; 3] = decimal_odds.map(|o| 1.0 / o);
+    let booksum: f64 = implied.iter().sum();
+    if booksum <= 1.0 {
+        // No margin to remove; multiplicative normalization is exact here.
+        return Some(multiplicative(decimal_odds));
+    }
+
+    let probs_for = |z: f64| -> [f64; 3] {
+        implied.map(|p_i| {
+            let inner = z * z + 4.0 * (1.0 - z) * p_i * p_i / booksum;
+            (inner.sqrt() - z) / (2.0 * (1.0 - z))
+        })
+    };
+    let residual = |z: f64| probs_for(z).iter().sum::<f64>() - 1.0;
+
+    let mut lo = 0.0_f64;
+    let mut hi = 0.2_f64;
+    // Widen the bracket until it contains a sign change, or give up.
+    while residual(lo).signum() == residual(hi).signum() {
+        hi += 0.2;
+        if hi >= 1.0 {
+            return None;
+        }
+    }
+
+    for _ in 0..SHIN_BISECTION_ITERATIONS {
+        let mid = (lo + hi) / 2.0;
+        let r_mid = residual(mid);
+        if r_mid.abs() < SHIN_CONVERGENCE_EPS {
+            return Some(probs_for(mid));
+        }
+        if residual(lo).signum() == r_mid.signum() {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Some(probs_for((lo + hi) / 2.0))
+}
@@ -0,0 +1,346 @@
+// OHLC candle aggregation over the timestamped snapshot history already persisted per
+// bookie in `football_odds` (`odds_x12`/`odds_ah`/`odds_ou`), rebuilt on demand from
+// that stored history rather than accumulated from a live tick stream. Complementary to
+// `candles` (built from the raw Monaco top-of-book stream as it happens) and
+// odds-processor's own `candles` module (built from the merged `OddsUpdate` stream): this
+// one only needs whatever has already landed in `football_odds`, so it can reconstruct a
+// series for a bookie or a time range the live aggregators never ran against.
+use serde_json::Value;
+use sqlx::{PgPool, Row};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+use tracing::{error, info};
+
+/// The market types `run` rebuilds candles for on each cycle.
+const MARKET_TYPES: &[&str] = &["x12", "ah", "ou"];
+
+/// Periodically rebuilds `football_odds_candles`, at every resolution in
+/// `resolutions_secs`, for every fixture with stored `football_odds` history. Runs as its
+/// own periodic background service, the same way `line_movement::LineMovementService`
+/// scans `football_odds` on an interval.
+pub async fn run(pool: PgPool, refresh_interval: Duration, resolutions_secs: Vec<i64>) {
+    info!("Starting History Candles Service (resolutions: {:?})", resolutions_secs);
+    let mut interval = tokio::time::interval(refresh_interval);
+
+    loop {
+        interval.tick().await;
+
+        let fixture_ids = match load_fixture_ids_with_odds(&pool).await {
+            Ok(ids) => ids,
+            Err(e) => {
+                error!("Failed to load fixture ids for history candle rebuild: {}", e);
+                continue;
+            }
+        };
+
+        let mut written = 0;
+        for fixture_id in fixture_ids {
+            for &market_type in MARKET_TYPES {
+                for &resolution_secs in &resolutions_secs {
+                    match build_candles(&pool, fixture_id, market_type, resolution_secs).await {
+                        Ok(count) => written += count,
+                        Err(e) => error!(
+                            "Failed to rebuild {} candles for fixture_id={} at {}s: {}",
+                            market_type, fixture_id, resolution_secs, e
+                        ),
+                    }
+                }
+            }
+        }
+
+        if written > 0 {
+            info!("History candle rebuild wrote {} candles", written);
+        }
+    }
+}
+
+async fn load_fixture_ids_with_odds(pool: &PgPool) -> Result<Vec<i64>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT DISTINCT fixture_id FROM football_odds WHERE odds_x12 IS NOT NULL OR odds_ah IS NOT NULL OR odds_ou IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.iter().map(|r| r.get("fixture_id")).collect())
+}
+
+/// The finest resolution raw snapshots are bucketed at; every other supported
+/// resolution is derived from this one by rolling buckets up, not by re-bucketing the
+/// raw snapshots again.
+const BASE_RESOLUTION_SECS: i64 = 60;
+
+/// A single OHLC value, in the same `decimal * 10^decimals`-scaled integer encoding the
+/// raw snapshots use.
+#[derive(Debug, Clone, Copy)]
+struct Ohlc {
+    open: i32,
+    high: i32,
+    low: i32,
+    close: i32,
+}
+
+const NO_QUOTE: Ohlc = Ohlc { open: 0, high: 0, low: 0, close: 0 };
+
+/// One bucket's candles for every outcome/line of a market at once, shaped the same way
+/// as a raw snapshot entry (e.g. `{"x12": [...]}` or `{"ah_h": [...], "ah_a": [...]}`),
+/// just with open/high/low/close each holding that shape instead of a single value.
+struct Bucket {
+    bucket_start: i64,
+    open: Value,
+    high: Value,
+    low: Value,
+    close: Value,
+}
+
+/// Rebuilds OHLC candles for one fixture/market across every bookie with stored history,
+/// bucketed at `resolution_secs` (one of `BASE_RESOLUTION_SECS` or a whole multiple of
+/// it, e.g. 60/300/3600), and upserts them into `football_odds_candles`. Returns the
+/// number of candles written.
+pub async fn build_candles(
+    pool: &PgPool,
+    fixture_id: i64,
+    market_type: &str,
+    resolution_secs: i64,
+) -> Result<usize, sqlx::Error> {
+    let field_name = format!("odds_{}", market_type);
+    let rows = sqlx::query(&format!(
+        r#"SELECT bookie, {} AS odds FROM football_odds WHERE fixture_id = $1 AND {} IS NOT NULL"#,
+        field_name, field_name
+    ))
+    .bind(fixture_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut written = 0;
+    for row in rows {
+        let bookie: String = row.get("bookie");
+        let odds: Value = row.get("odds");
+        let Some(snapshots) = odds.as_array() else { continue };
+        if snapshots.is_empty() {
+            continue;
+        }
+
+        for bucket in bucket_snapshots(snapshots, market_type, resolution_secs) {
+            if let Err(e) = persist_bucket(pool, fixture_id, &bookie, market_type, resolution_secs, &bucket).await {
+                error!("Failed to persist odds candle for fixture_id={}: {}", fixture_id, e);
+                return Err(e);
+            }
+            written += 1;
+        }
+    }
+
+    Ok(written)
+}
+
+/// The snapshot fields that hold outcome/line prices for a market, e.g. `odds_ah`
+/// entries carry both `ah_h` and `ah_a` arrays.
+fn value_fields(market_type: &str) -> &'static [&'static str] {
+    match market_type {
+        "x12" => &["x12"],
+        "ah" => &["ah_h", "ah_a"],
+        "ou" => &["ou_o", "ou_u"],
+        _ => &[],
+    }
+}
+
+fn field_len(snapshots: &[Value], field: &str) -> usize {
+    snapshots
+        .iter()
+        .filter_map(|s| s.get(field).and_then(|v| v.as_array()).map(|a| a.len()))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Every `(t, price)` tick recorded for one outcome/line across a bookie's snapshot
+/// history, skipping entries with no live quote yet (encoded as `0`, same convention
+/// `update_database_with_best_prices` and the arbitrage scanner both use).
+fn extract_index_ticks(snapshots: &[Value], field: &str, index: usize) -> Vec<(i64, i32)> {
+    let mut ticks = Vec::new();
+    for snapshot in snapshots {
+        let Some(t) = snapshot.get("t").and_then(|v| v.as_i64()) else { continue };
+        let Some(val) = snapshot
+            .get(field)
+            .and_then(|v| v.as_array())
+            .and_then(|a| a.get(index))
+            .and_then(|v| v.as_i64())
+        else {
+            continue;
+        };
+        if val <= 0 {
+            continue;
+        }
+        ticks.push((t, val as i32));
+    }
+    ticks
+}
+
+fn bucket_series(ticks: &[(i64, i32)], resolution_secs: i64) -> BTreeMap<i64, Ohlc> {
+    let mut buckets = BTreeMap::new();
+    for &(t, price) in ticks {
+        let bucket_start = (t / resolution_secs) * resolution_secs;
+        buckets
+            .entry(bucket_start)
+            .and_modify(|c: &mut Ohlc| {
+                c.high = c.high.max(price);
+                c.low = c.low.min(price);
+                c.close = price;
+            })
+            .or_insert(Ohlc { open: price, high: price, low: price, close: price });
+    }
+    buckets
+}
+
+/// Fills every bucket between the first and last observed bucket, carrying the
+/// previous bucket's close forward as open/high/low/close wherever no tick landed, so
+/// a chart built from the series has no holes.
+fn forward_fill(mut raw: BTreeMap<i64, Ohlc>, step: i64) -> BTreeMap<i64, Ohlc> {
+    let (Some(&first), Some(&last)) = (raw.keys().next(), raw.keys().next_back()) else {
+        return raw;
+    };
+
+    let mut filled = BTreeMap::new();
+    let mut prev_close = 0;
+    let mut bucket_start = first;
+    while bucket_start <= last {
+        if let Some(candle) = raw.remove(&bucket_start) {
+            prev_close = candle.close;
+            filled.insert(bucket_start, candle);
+        } else {
+            filled.insert(bucket_start, Ohlc { open: prev_close, high: prev_close, low: prev_close, close: prev_close });
+        }
+        bucket_start += step;
+    }
+    filled
+}
+
+/// Derives a coarser resolution from an already-bucketed, gap-free finer series:
+/// open = first sub-candle's open, high/low = max/min across sub-candles, close = last
+/// sub-candle's close. `base` must be in ascending bucket order, which `BTreeMap`
+/// already guarantees.
+fn roll_up(base: &BTreeMap<i64, Ohlc>, target_resolution_secs: i64) -> BTreeMap<i64, Ohlc> {
+    let mut out: BTreeMap<i64, Ohlc> = BTreeMap::new();
+    for (&bucket_start, candle) in base {
+        let target_start = (bucket_start / target_resolution_secs) * target_resolution_secs;
+        out.entry(target_start)
+            .and_modify(|c| {
+                c.high = c.high.max(candle.high);
+                c.low = c.low.min(candle.low);
+                c.close = candle.close;
+            })
+            .or_insert(*candle);
+    }
+    out
+}
+
+/// Builds one outcome/line's gap-free candle series at `resolution_secs`, bucketing raw
+/// ticks at `BASE_RESOLUTION_SECS` and rolling up if a coarser resolution was requested.
+fn build_index_series(ticks: Vec<(i64, i32)>, resolution_secs: i64) -> BTreeMap<i64, Ohlc> {
+    if ticks.is_empty() {
+        return BTreeMap::new();
+    }
+
+    let base = forward_fill(bucket_series(&ticks, BASE_RESOLUTION_SECS), BASE_RESOLUTION_SECS);
+    if resolution_secs == BASE_RESOLUTION_SECS {
+        base
+    } else {
+        roll_up(&base, resolution_secs)
+    }
+}
+
+/// Builds every outcome/line's candle series for one bookie's snapshot history and
+/// recombines them into one `Bucket` per bucket_start, so a single row can hold the
+/// whole market the same way a raw snapshot entry does.
+fn bucket_snapshots(snapshots: &[Value], market_type: &str, resolution_secs: i64) -> Vec<Bucket> {
+    let fields = value_fields(market_type);
+    if fields.is_empty() {
+        return Vec::new();
+    }
+
+    let per_field: Vec<(&'static str, Vec<BTreeMap<i64, Ohlc>>)> = fields
+        .iter()
+        .map(|&field| {
+            let len = field_len(snapshots, field);
+            let series = (0..len)
+                .map(|index| build_index_series(extract_index_ticks(snapshots, field, index), resolution_secs))
+                .collect();
+            (field, series)
+        })
+        .collect();
+
+    let mut bucket_starts: BTreeSet<i64> = BTreeSet::new();
+    for (_, series) in &per_field {
+        for s in series {
+            bucket_starts.extend(s.keys().copied());
+        }
+    }
+
+    bucket_starts
+        .into_iter()
+        .map(|bucket_start| {
+            let mut open = serde_json::Map::new();
+            let mut high = serde_json::Map::new();
+            let mut low = serde_json::Map::new();
+            let mut close = serde_json::Map::new();
+
+            for (field, series) in &per_field {
+                let mut o = Vec::with_capacity(series.len());
+                let mut h = Vec::with_capacity(series.len());
+                let mut l = Vec::with_capacity(series.len());
+                let mut c = Vec::with_capacity(series.len());
+                for s in series {
+                    let candle = s.get(&bucket_start).copied().unwrap_or(NO_QUOTE);
+                    o.push(candle.open);
+                    h.push(candle.high);
+                    l.push(candle.low);
+                    c.push(candle.close);
+                }
+                open.insert(field.to_string(), serde_json::json!(o));
+                high.insert(field.to_string(), serde_json::json!(h));
+                low.insert(field.to_string(), serde_json::json!(l));
+                close.insert(field.to_string(), serde_json::json!(c));
+            }
+
+            Bucket {
+                bucket_start,
+                open: Value::Object(open),
+                high: Value::Object(high),
+                low: Value::Object(low),
+                close: Value::Object(close),
+            }
+        })
+        .collect()
+}
+
+async fn persist_bucket(
+    pool: &PgPool,
+    fixture_id: i64,
+    bookie: &str,
+    market_type: &str,
+    resolution_secs: i64,
+    bucket: &Bucket,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO football_odds_candles
+            (fixture_id, bookie, market_type, resolution, bucket_start, open, high, low, close)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT (fixture_id, bookie, market_type, resolution, bucket_start) DO UPDATE SET
+            open = EXCLUDED.open,
+            high = EXCLUDED.high,
+            low = EXCLUDED.low,
+            close = EXCLUDED.close
+        "#,
+    )
+    .bind(fixture_id)
+    .bind(bookie)
+    .bind(market_type)
+    .bind(resolution_secs)
+    .bind(bucket.bucket_start)
+    .bind(&bucket.open)
+    .bind(&bucket.high)
+    .bind(&bucket.low)
+    .bind(&bucket.close)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,300 @@
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::{PgPool, Row};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, error, info};
+
+/// A detected sharp move in a stored odds time series: either a probability swing on a
+/// price market (x12/ah/ou) or the primary AH/OU line value migrating.
+#[derive(Debug, Clone)]
+pub struct SteamMoveEvent {
+    pub fixture_id: i64,
+    pub bookie: String,
+    pub market: &'static str, // "x12" | "ah" | "ou"
+    pub kind: &'static str,   // "probability" | "line"
+    pub outcome: String,
+    /// For a probability move: the implied-probability delta. For a line move: the
+    /// absolute change in the line value.
+    pub magnitude: f64,
+    pub from_value: f64,
+    pub to_value: f64,
+    pub from_t: i64,
+    pub to_t: i64,
+}
+
+/// Scans the timestamped history arrays `merge_history` builds up (`odds_x12`,
+/// `odds_ah`, `odds_ou`, `lines`) for sharp, "steam" moves — either a big implied-
+/// probability jump between consecutive snapshots within a time window, or the primary
+/// handicap/total line migrating. Runs as its own periodic background service.
+pub struct LineMovementService {
+    pool: PgPool,
+    tx: broadcast::Sender<SteamMoveEvent>,
+    /// Minimum implied-probability delta (0..1) between consecutive snapshots to flag.
+    prob_threshold: f64,
+    /// Only snapshot pairs within this many seconds of each other are compared, so a
+    /// genuinely sharp move isn't conflated with odds drifting apart over days.
+    window_secs: i64,
+}
+
+impl LineMovementService {
+    pub fn new(pool: PgPool, prob_threshold: f64, window_secs: i64) -> (Self, broadcast::Receiver<SteamMoveEvent>) {
+        let (tx, rx) = broadcast::channel(256);
+        (
+            Self { pool, tx, prob_threshold, window_secs },
+            rx,
+        )
+    }
+
+    pub async fn run(&self, scan_interval: Duration) {
+        info!(
+            "Starting Line Movement Service (threshold: {}, window: {}s)",
+            self.prob_threshold, self.window_secs
+        );
+        let mut interval = tokio::time::interval(scan_interval);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.scan_cycle().await {
+                error!("Error in line movement scan cycle: {}", e);
+            }
+        }
+    }
+
+    async fn scan_cycle(&self) -> Result<(), sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT fixture_id, bookie, decimals, odds_x12, odds_ah, odds_ou, lines
+            FROM football_odds
+            WHERE odds_x12 IS NOT NULL OR odds_ah IS NOT NULL OR odds_ou IS NOT NULL
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut events_found = 0;
+        for row in rows {
+            let fixture_id: i64 = row.get("fixture_id");
+            let bookie: String = row.get("bookie");
+            let decimals: i32 = row.get("decimals");
+            let odds_x12: Option<Value> = row.get("odds_x12");
+            let odds_ah: Option<Value> = row.get("odds_ah");
+            let odds_ou: Option<Value> = row.get("odds_ou");
+            let lines: Option<Value> = row.get("lines");
+            let scale = 10f64.powi(decimals);
+
+            let mut events = Vec::new();
+            events.extend(self.scan_x12(fixture_id, &bookie, scale, odds_x12.as_ref()));
+            events.extend(self.scan_ah_ou(fixture_id, &bookie, "ah", "ah_h", "ah_a", scale, odds_ah.as_ref()));
+            events.extend(self.scan_ah_ou(fixture_id, &bookie, "ou", "ou_o", "ou_u", scale, odds_ou.as_ref()));
+            events.extend(self.scan_line_migration(fixture_id, &bookie, "ah", lines.as_ref()));
+            events.extend(self.scan_line_migration(fixture_id, &bookie, "ou", lines.as_ref()));
+
+            for event in events {
+                self.persist_event(&event).await?;
+                let _ = self.tx.send(event);
+                events_found += 1;
+            }
+        }
+
+        if events_found > 0 {
+            debug!("Line movement scan found {} steam moves", events_found);
+        }
+
+        Ok(())
+    }
+
+    /// x12 snapshots are `{t, x12: [home, draw, away]}` integer-encoded odds; compare
+    /// implied probability (`1/odds` on the decoded decimal odds, i.e. `scale/raw`) for each
+    /// outcome across consecutive snapshots.
+    fn scan_x12(&self, fixture_id: i64, bookie: &str, scale: f64, odds_x12: Option<&Value>) -> Vec<SteamMoveEvent> {
+        const OUTCOMES: [&str; 3] = ["home", "draw", "away"];
+        let Some(snapshots) = odds_x12.and_then(|v| v.as_array()) else {
+            return Vec::new();
+        };
+
+        let mut events = Vec::new();
+        for pair in snapshots.windows(2) {
+            let (Some(prev_t), Some(next_t)) = (pair[0].get("t").and_then(|v| v.as_i64()), pair[1].get("t").and_then(|v| v.as_i64())) else {
+                continue;
+            };
+            if next_t - prev_t > self.window_secs {
+                continue;
+            }
+            let (Some(prev_x12), Some(next_x12)) = (
+                pair[0].get("x12").and_then(|v| v.as_array()),
+                pair[1].get("x12").and_then(|v| v.as_array()),
+            ) else {
+                continue;
+            };
+            if prev_x12.len() != 3 || next_x12.len() != 3 {
+                continue;
+            }
+
+            for (i, outcome) in OUTCOMES.iter().enumerate() {
+                let (Some(prev_odds), Some(next_odds)) = (prev_x12[i].as_f64(), next_x12[i].as_f64()) else {
+                    continue;
+                };
+                if prev_odds <= 0.0 || next_odds <= 0.0 {
+                    continue;
+                }
+                let prev_prob = scale / prev_odds;
+                let next_prob = scale / next_odds;
+                let delta = next_prob - prev_prob;
+
+                if delta.abs() >= self.prob_threshold {
+                    events.push(SteamMoveEvent {
+                        fixture_id,
+                        bookie: bookie.to_string(),
+                        market: "x12",
+                        kind: "probability",
+                        outcome: outcome.to_string(),
+                        magnitude: delta,
+                        from_value: prev_prob,
+                        to_value: next_prob,
+                        from_t: prev_t,
+                        to_t: next_t,
+                    });
+                }
+            }
+        }
+
+        events
+    }
+
+    /// AH/OU snapshots are `{t, <side_a>: [...], <side_b>: [...]}`, one entry per line. We
+    /// compare the first (primary) line's implied probability across consecutive snapshots.
+    fn scan_ah_ou(
+        &self,
+        fixture_id: i64,
+        bookie: &str,
+        market: &'static str,
+        side_a_key: &str,
+        side_b_key: &str,
+        scale: f64,
+        odds: Option<&Value>,
+    ) -> Vec<SteamMoveEvent> {
+        let Some(snapshots) = odds.and_then(|v| v.as_array()) else {
+            return Vec::new();
+        };
+
+        let mut events = Vec::new();
+        for pair in snapshots.windows(2) {
+            let (Some(prev_t), Some(next_t)) = (pair[0].get("t").and_then(|v| v.as_i64()), pair[1].get("t").and_then(|v| v.as_i64())) else {
+                continue;
+            };
+            if next_t - prev_t > self.window_secs {
+                continue;
+            }
+
+            for (side_key, outcome) in [(side_a_key, "a"), (side_b_key, "b")] {
+                let (Some(prev_arr), Some(next_arr)) = (
+                    pair[0].get(side_key).and_then(|v| v.as_array()),
+                    pair[1].get(side_key).and_then(|v| v.as_array()),
+                ) else {
+                    continue;
+                };
+                let (Some(prev_odds), Some(next_odds)) = (
+                    prev_arr.first().and_then(|v| v.as_f64()),
+                    next_arr.first().and_then(|v| v.as_f64()),
+                ) else {
+                    continue;
+                };
+                if prev_odds <= 0.0 || next_odds <= 0.0 {
+                    continue;
+                }
+
+                let prev_prob = scale / prev_odds;
+                let next_prob = scale / next_odds;
+                let delta = next_prob - prev_prob;
+
+                if delta.abs() >= self.prob_threshold {
+                    events.push(SteamMoveEvent {
+                        fixture_id,
+                        bookie: bookie.to_string(),
+                        market,
+                        kind: "probability",
+                        outcome: outcome.to_string(),
+                        magnitude: delta,
+                        from_value: prev_prob,
+                        to_value: next_prob,
+                        from_t: prev_t,
+                        to_t: next_t,
+                    });
+                }
+            }
+        }
+
+        events
+    }
+
+    /// `lines` snapshots are `{t, ah: [...], ou: [...]}` sorted ascending; the primary
+    /// line is the first entry. Flag when it migrates between consecutive snapshots
+    /// (e.g. a handicap moving from -0.25 to -0.5).
+    fn scan_line_migration(&self, fixture_id: i64, bookie: &str, market: &'static str, lines: Option<&Value>) -> Vec<SteamMoveEvent> {
+        let Some(snapshots) = lines.and_then(|v| v.as_array()) else {
+            return Vec::new();
+        };
+
+        let mut events = Vec::new();
+        for pair in snapshots.windows(2) {
+            let (Some(prev_t), Some(next_t)) = (pair[0].get("t").and_then(|v| v.as_i64()), pair[1].get("t").and_then(|v| v.as_i64())) else {
+                continue;
+            };
+            if next_t - prev_t > self.window_secs {
+                continue;
+            }
+
+            let (Some(prev_primary), Some(next_primary)) = (
+                pair[0].get(market).and_then(|v| v.as_array()).and_then(|a| a.first()).and_then(|v| v.as_f64()),
+                pair[1].get(market).and_then(|v| v.as_array()).and_then(|a| a.first()).and_then(|v| v.as_f64()),
+            ) else {
+                continue;
+            };
+
+            if (next_primary - prev_primary).abs() > f64::EPSILON {
+                events.push(SteamMoveEvent {
+                    fixture_id,
+                    bookie: bookie.to_string(),
+                    market,
+                    kind: "line",
+                    outcome: "primary".to_string(),
+                    magnitude: (next_primary - prev_primary).abs(),
+                    from_value: prev_primary,
+                    to_value: next_primary,
+                    from_t: prev_t,
+                    to_t: next_t,
+                });
+            }
+        }
+
+        events
+    }
+
+    async fn persist_event(&self, event: &SteamMoveEvent) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO steam_moves
+                (fixture_id, bookie, market, kind, outcome, magnitude, from_value, to_value, from_t, to_t, detected_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (fixture_id, bookie, market, outcome, from_t, to_t) DO NOTHING
+            "#,
+        )
+        .bind(event.fixture_id)
+        .bind(&event.bookie)
+        .bind(event.market)
+        .bind(event.kind)
+        .bind(&event.outcome)
+        .bind(event.magnitude)
+        .bind(event.from_value)
+        .bind(event.to_value)
+        .bind(event.from_t)
+        .bind(event.to_t)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,16 +1,31 @@
+mod arbitrage;
+mod arbitrer;
+mod betfair;
+mod bookmaker;
+mod candles;
 mod config;
+mod depth;
+mod fair_odds;
+mod history_candles;
+mod line_movement;
+mod metrics;
+mod middles;
+mod ratings;
 mod shared;
 mod monaco;
 mod pinnacle;
 mod processor_client;
+mod source;
 
 use axum::{
-    extract::{ws::{Message, WebSocket, WebSocketUpgrade}, State},
+    extract::{ws::{Message, WebSocket, WebSocketUpgrade}, Path, Query, State},
+    http::StatusCode,
     response::IntoResponse,
     routing::get,
-    Router,
+    Json, Router,
 };
-use config::Config;
+use arc_swap::ArcSwap;
+use config::{Config, SharedConfig};
 use dashmap::DashMap;
 use monaco::{client::MonacoApiClient, stream::MonacoWebSocketClient, types::MarketMapping};
 use monaco::order_book::MonacoOrderBook;
@@ -19,13 +34,14 @@ use serde_json::Value;
 use sqlx::postgres::PgPoolOptions;
 use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
 use tracing::info;
 
 // --- Types ---
 
 // Shared state accessible by all parts of the app
 pub struct AppState {
-    pub config: Config,
+    pub config: SharedConfig,
     pub tx: broadcast::Sender<Value>,
     pub db: sqlx::PgPool,
     // Market mapping: "eventId-marketId" -> MarketMapping
@@ -36,6 +52,16 @@ pub struct AppState {
     pub order_book: Arc<Mutex<MonacoOrderBook>>,
     // Processor client for sending updates
     pub processor_client: Option<Arc<ProcessorClient>>,
+    // OHLC candle aggregator, fed from the same top-of-book ticks as the order book
+    pub candle_aggregator: Arc<Mutex<candles::CandleAggregator>>,
+    // Depth-of-book checkpoint/diff state, keyed per (fixture_id, market_type)
+    pub depth_tracker: Arc<Mutex<depth::DepthTracker>>,
+    // Depth-of-book sink for full order-book checkpoints and incremental diffs
+    pub depth_client: Option<Arc<depth::DepthClient>>,
+    // Counters and latency histograms exported over `/metrics`
+    pub metrics: Arc<metrics::Metrics>,
+    // Monaco market-type/line-value mapping rules, loaded once at startup
+    pub market_rules: Arc<monaco::market_rules::MarketRules>,
 }
 
 // --- Main ---
@@ -51,6 +77,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let config = Config::from_env();
     info!("📋 Configuration loaded");
+    let shared_config: SharedConfig = Arc::new(ArcSwap::from_pointee(config.clone()));
 
     // Connect to Postgres with proper pool configuration
     info!("🔌 Connecting to Postgres...");
@@ -65,122 +92,196 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     info!("✅ Connected to Postgres");
 
+    info!("📐 Loading market rules from {}...", config.market_rules_path);
+    let market_rules = Arc::new(
+        monaco::market_rules::MarketRules::load(&config.market_rules_path)
+            .expect("failed to load market rules"),
+    );
+
     // Initialize processor client
+    let processor_wire_format = if config.processor_use_msgpack {
+        processor_client::WireFormat::MessagePack
+    } else {
+        processor_client::WireFormat::NewlineJson
+    };
     let processor_client = processor_client::create_processor_client(
         config.processor_enabled,
         config.processor_port,
+        config.processor_queue_capacity,
+        processor_wire_format,
+    );
+
+    // Initialize depth-of-book client
+    let depth_client = depth::create_depth_client(
+        config.depth_enabled,
+        config.depth_port,
+        config.depth_queue_capacity,
     );
 
     // Initialize State
     let (tx, _rx) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
     let state = Arc::new(AppState {
-        config: config.clone(),
+        config: shared_config.clone(),
         tx: tx.clone(),
         db: pool.clone(),
         market_mapping: DashMap::new(),
         event_to_fixture: DashMap::new(),
         order_book: Arc::new(Mutex::new(MonacoOrderBook::new())),
         processor_client,
+        candle_aggregator: Arc::new(Mutex::new(candles::CandleAggregator::new(&config.candle_intervals_secs))),
+        depth_tracker: Arc::new(Mutex::new(depth::DepthTracker::new())),
+        depth_client,
+        metrics: Arc::new(metrics::Metrics::new()),
+        market_rules,
     });
 
-    // Initialize Monaco Client & Ingestion
-    if state.config.monaco_odds_enabled {
-        info!("🎰 Initializing Monaco API client...");
-        let monaco_api = Arc::new(Mutex::new(MonacoApiClient::new(
-            config.monaco_base_url.clone(),
-            config.monaco_app_id.clone(),
-            config.monaco_api_key.clone(),
-        )));
-        
-        let monaco_ws = MonacoWebSocketClient::new(
-            config.monaco_stream_url.clone(),
-            monaco_api.clone()
-        );
-
-        // Initialize markets and mappings with retry
-        info!("🔄 Fetching and processing markets...");
-        let mut retry_count = 0;
-        let max_retries = 3;
-        
-        loop {
-            match crate::monaco::market_init::fetch_and_process_markets(
-                &monaco_api,
-                &state.db,
-                &state.market_mapping,
-                &state.event_to_fixture,
-                &state.order_book,
-            )
-            .await
-            {
-                Ok(_) => {
-                    info!("✅ Markets initialized successfully");
-                    break;
-                }
-                Err(e) => {
-                    retry_count += 1;
-                    if retry_count >= max_retries {
-                        tracing::error!("❌ Failed to initialize markets after {} attempts: {}", max_retries, e);
-                        tracing::error!("❌ Monaco service will continue but may not have initial market data");
-                        break;
-                    }
-                    let wait_secs = 2u64.pow(retry_count);
-                    tracing::warn!("⚠️ Market initialization failed (attempt {}/{}): {}. Retrying in {}s...", 
-                        retry_count, max_retries, e, wait_secs);
-                    tokio::time::sleep(Duration::from_secs(wait_secs)).await;
-                }
-            }
-        }
+    let flush_interval = Duration::from_secs(config.candle_flush_interval_secs);
+    let flush_pool = pool.clone();
+    let flush_aggregator = state.candle_aggregator.clone();
+    tokio::spawn(async move {
+        candles::run_open_candle_flush(flush_pool, flush_aggregator, flush_interval).await;
+    });
 
-        // Spawn Ingestion Task
-        info!("📡 Starting Monaco ingestion engine...");
-        let ingestion_state = state.clone();
-        tokio::spawn(async move {
-            monaco::handlers::start_ingestion_engine(ingestion_state, monaco_ws).await;
-        });
+    // Monaco and Pinnacle are started/stopped together as their config flags toggle, so we
+    // keep their task handles behind a lock the reload watcher below can reach into.
+    let monaco_tasks: Arc<Mutex<Option<MonacoTasks>>> = Arc::new(Mutex::new(None));
+    let pinnacle_task: Arc<Mutex<Option<JoinHandle<()>>>> = Arc::new(Mutex::new(None));
+    let betfair_tasks: Arc<Mutex<Option<BetfairTasks>>> = Arc::new(Mutex::new(None));
 
-        // Spawn Periodic Market Refresh Task (every 60 minutes)
-        info!("🔄 Starting periodic market refresh (every 60 minutes)...");
-        let refresh_state = state.clone();
-        let refresh_api = monaco_api.clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(MARKET_REFRESH_INTERVAL_SECS));
-            loop {
-                interval.tick().await;
-                info!("🔄 Refreshing markets for new events...");
-                if let Err(e) = crate::monaco::market_init::fetch_and_process_markets(
-                    &refresh_api,
-                    &refresh_state.db,
-                    &refresh_state.market_mapping,
-                    &refresh_state.event_to_fixture,
-                    &refresh_state.order_book,
-                )
-                .await
-                {
-                    tracing::error!("Error during periodic market refresh: {}", e);
-                }
-            }
-        });
+    if config.monaco_odds_enabled {
+        *monaco_tasks.lock().await = Some(start_monaco_services(state.clone()).await);
     } else {
         info!("📡 Monaco services disabled (MONACO_ODDS != true)");
     }
 
-    // Start Pinnacle Service
-    if state.config.pinnacle_odds_enabled {
-        info!("🏔️ Starting Pinnacle Service...");
-        let pinnacle_pool = pool.clone();
-        let pinnacle_processor_client = state.processor_client.clone();
-        tokio::spawn(async move {
-            let mut pinnacle_service = crate::pinnacle::PinnacleService::new(pinnacle_pool, pinnacle_processor_client);
-            pinnacle_service.run().await;
-        });
+    if config.pinnacle_odds_enabled {
+        *pinnacle_task.lock().await = Some(start_pinnacle_service(pool.clone(), state.processor_client.clone(), &config));
     } else {
         info!("🏔️ Pinnacle Service disabled (PINNACLE_ODDS != true)");
     }
 
+    if config.betfair_odds_enabled {
+        *betfair_tasks.lock().await = Some(start_betfair_service(state.clone(), &config));
+    } else {
+        info!("🏇 Betfair Service disabled (BETFAIR_ODDS != true)");
+    }
+
+    if config.arbitrage_enabled {
+        start_arbitrage_service(pool.clone(), &config);
+    } else {
+        info!("💰 Arbitrage Service disabled (ARBITRAGE_ENABLED != true)");
+    }
+
+    if config.arbitrer_enabled {
+        start_arbitrer_service(state.clone(), &config);
+    } else {
+        info!("🎲 Arbitrer Service disabled (ARBITRER_ENABLED != true)");
+    }
+
+    if config.backfill_enabled {
+        start_backfill_service(state.clone(), &config);
+    } else {
+        info!("📼 Backfill disabled (BACKFILL_ENABLED != true)");
+    }
+
+    if config.ratings_enabled {
+        start_ratings_service(pool.clone(), &config);
+    } else {
+        info!("📊 Team Ratings Service disabled (RATINGS_ENABLED != true)");
+    }
+
+    if config.history_candles_enabled {
+        start_history_candles_service(pool.clone(), &config);
+    } else {
+        info!("🕯️ History Candles Service disabled (HISTORY_CANDLES_ENABLED != true)");
+    }
+
+    if config.line_movement_enabled {
+        start_line_movement_service(pool.clone(), &config);
+    } else {
+        info!("📈 Line Movement Service disabled (LINE_MOVEMENT_ENABLED != true)");
+    }
+
+    if config.middles_enabled {
+        start_middles_service(pool.clone(), &config);
+    } else {
+        info!("🎯 Middles Service disabled (MIDDLES_ENABLED != true)");
+    }
+
+    // Hot-reload: toggling MONACO_ODDS/PINNACLE_ODDS/BETFAIR_ODDS via SIGHUP starts or stops
+    // their tasks live; DATABASE_URL and PORT changes are rejected by the watcher itself.
+    {
+        let reload_state = state.clone();
+        let reload_pool = pool.clone();
+        let reload_monaco_tasks = monaco_tasks.clone();
+        let reload_pinnacle_task = pinnacle_task.clone();
+        let reload_betfair_tasks = betfair_tasks.clone();
+        config::spawn_reload_watcher(shared_config.clone(), move |old, new| {
+            if old.monaco_odds_enabled != new.monaco_odds_enabled {
+                let state = reload_state.clone();
+                let tasks = reload_monaco_tasks.clone();
+                let enable = new.monaco_odds_enabled;
+                tokio::spawn(async move {
+                    let mut guard = tasks.lock().await;
+                    if enable {
+                        if guard.is_none() {
+                            info!("📡 MONACO_ODDS enabled via reload, starting Monaco services...");
+                            *guard = Some(start_monaco_services(state).await);
+                        }
+                    } else if let Some(handles) = guard.take() {
+                        handles.ingestion.abort();
+                        handles.refresh.abort();
+                        info!("📡 MONACO_ODDS disabled via reload, Monaco services stopped");
+                    }
+                });
+            }
+            if old.pinnacle_odds_enabled != new.pinnacle_odds_enabled {
+                let pool = reload_pool.clone();
+                let processor_client = reload_state.processor_client.clone();
+                let task = reload_pinnacle_task.clone();
+                let enable = new.pinnacle_odds_enabled;
+                let new_config = new.clone();
+                tokio::spawn(async move {
+                    let mut guard = task.lock().await;
+                    if enable {
+                        if guard.is_none() {
+                            info!("🏔️ PINNACLE_ODDS enabled via reload, starting Pinnacle service...");
+                            *guard = Some(start_pinnacle_service(pool, processor_client, &new_config));
+                        }
+                    } else if let Some(handle) = guard.take() {
+                        handle.abort();
+                        info!("🏔️ PINNACLE_ODDS disabled via reload, Pinnacle service stopped");
+                    }
+                });
+            }
+            if old.betfair_odds_enabled != new.betfair_odds_enabled {
+                let state = reload_state.clone();
+                let tasks = reload_betfair_tasks.clone();
+                let enable = new.betfair_odds_enabled;
+                let new_config = new.clone();
+                tokio::spawn(async move {
+                    let mut guard = tasks.lock().await;
+                    if enable {
+                        if guard.is_none() {
+                            info!("🏇 BETFAIR_ODDS enabled via reload, starting Betfair service...");
+                            *guard = Some(start_betfair_service(state, &new_config));
+                        }
+                    } else if let Some(handles) = guard.take() {
+                        handles.ingestion.abort();
+                        handles.polling.abort();
+                        info!("🏇 BETFAIR_ODDS disabled via reload, Betfair service stopped");
+                    }
+                });
+            }
+        });
+    }
+
     // Start API Server
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/ws", get(ws_handler))
+        .route("/metrics", get(metrics_handler))
+        .route("/fixtures/{fixture_id}/structure", get(fixture_structure_handler))
         .with_state(state);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], config.server_port));
@@ -204,6 +305,254 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+// --- Service supervision (start/stop on config reload) ---
+
+struct MonacoTasks {
+    ingestion: JoinHandle<()>,
+    refresh: JoinHandle<()>,
+}
+
+/// Fetch/process markets (with retry) and spawn the ingestion + periodic refresh tasks.
+/// Used both at startup and when MONACO_ODDS is flipped back on via a config reload, so a
+/// fresh `MonacoApiClient` (and thus fresh credentials from the current config) is always
+/// built rather than reusing a stale one.
+async fn start_monaco_services(state: Arc<AppState>) -> MonacoTasks {
+    let config = state.config.load_full();
+    info!("🎰 Initializing Monaco API client...");
+    let monaco_api = Arc::new(Mutex::new(MonacoApiClient::new(
+        config.monaco_base_url.clone(),
+        config.monaco_app_id.clone(),
+        config.monaco_api_key.clone(),
+    )));
+
+    let monaco_ws = MonacoWebSocketClient::new(config.monaco_stream_url.clone(), monaco_api.clone());
+
+    // Initialize markets and mappings with retry
+    info!("🔄 Fetching and processing markets...");
+    let mut retry_count = 0;
+    let max_retries = 3;
+
+    loop {
+        match crate::monaco::market_init::fetch_and_process_markets(
+            &monaco_api,
+            &state.db,
+            &state.market_mapping,
+            &state.event_to_fixture,
+            &state.order_book,
+            &state.market_rules,
+            &state.metrics,
+            config.team_alias_similarity_threshold,
+            config.team_alias_window_hours,
+        )
+        .await
+        {
+            Ok(_) => {
+                info!("✅ Markets initialized successfully");
+                break;
+            }
+            Err(e) => {
+                retry_count += 1;
+                if retry_count >= max_retries {
+                    tracing::error!("❌ Failed to initialize markets after {} attempts: {}", max_retries, e);
+                    tracing::error!("❌ Monaco service will continue but may not have initial market data");
+                    break;
+                }
+                let wait_secs = 2u64.pow(retry_count);
+                tracing::warn!("⚠️ Market initialization failed (attempt {}/{}): {}. Retrying in {}s...",
+                    retry_count, max_retries, e, wait_secs);
+                tokio::time::sleep(Duration::from_secs(wait_secs)).await;
+            }
+        }
+    }
+
+    // Spawn Ingestion Task
+    info!("📡 Starting Monaco ingestion engine...");
+    let ingestion_state = state.clone();
+    let monaco_source: Arc<dyn source::OddsSource> = Arc::new(source::MonacoSource::new(Arc::new(monaco_ws)));
+    let ingestion = tokio::spawn(async move {
+        monaco::handlers::start_ingestion_engine(ingestion_state, monaco_source).await;
+    });
+
+    // Spawn Periodic Market Refresh Task (every 60 minutes)
+    info!("🔄 Starting periodic market refresh (every 60 minutes)...");
+    let refresh_state = state.clone();
+    let refresh_api = monaco_api.clone();
+    let refresh = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(MARKET_REFRESH_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            info!("🔄 Refreshing markets for new events...");
+            let refresh_config = refresh_state.config.load_full();
+            if let Err(e) = crate::monaco::market_init::fetch_and_process_markets(
+                &refresh_api,
+                &refresh_state.db,
+                &refresh_state.market_mapping,
+                &refresh_state.event_to_fixture,
+                &refresh_state.order_book,
+                &refresh_state.market_rules,
+                &refresh_state.metrics,
+                refresh_config.team_alias_similarity_threshold,
+                refresh_config.team_alias_window_hours,
+            )
+            .await
+            {
+                tracing::error!("Error during periodic market refresh: {}", e);
+            }
+        }
+    });
+
+    MonacoTasks { ingestion, refresh }
+}
+
+struct BetfairTasks {
+    ingestion: JoinHandle<()>,
+    polling: JoinHandle<()>,
+}
+
+/// Build a `BetfairSource`, spawn its ingestion-engine task (routes translated updates
+/// through the same order_book/build_odds_update pipeline Monaco uses) and its polling
+/// loop task (Betfair API session + listMarketCatalogue/listMarketBook), returning both
+/// handles so they can be aborted together if BETFAIR_ODDS is disabled via a config reload.
+fn start_betfair_service(state: Arc<AppState>, config: &Config) -> BetfairTasks {
+    info!("🏇 Starting Betfair Service...");
+    let betfair_source = Arc::new(betfair::source::BetfairSource::new());
+
+    let ingestion_state = state.clone();
+    let ingestion_source: Arc<dyn source::OddsSource> = betfair_source.clone();
+    let ingestion = tokio::spawn(async move {
+        monaco::handlers::start_ingestion_engine(ingestion_state, ingestion_source).await;
+    });
+
+    let markets = betfair::service::parse_tracked_markets(&config.betfair_markets);
+    let poll_interval = Duration::from_secs(config.betfair_poll_interval_secs);
+    let polling_state = state.clone();
+    let polling = tokio::spawn(async move {
+        betfair::service::run(polling_state, betfair_source, markets, poll_interval).await;
+    });
+
+    BetfairTasks { ingestion, polling }
+}
+
+/// Spawn the Pinnacle polling service, returning its task handle so it can be aborted
+/// if PINNACLE_ODDS is disabled via a config reload.
+fn start_pinnacle_service(pool: sqlx::PgPool, processor_client: Option<Arc<ProcessorClient>>, config: &Config) -> JoinHandle<()> {
+    info!("🏔️ Starting Pinnacle Service...");
+    let fair_prob_method = fair_odds::FairProbMethod::from_env_str(&config.fair_prob_method);
+    tokio::spawn(async move {
+        let mut pinnacle_service = crate::pinnacle::PinnacleService::new(pool, processor_client, fair_prob_method);
+        pinnacle_service.run().await;
+    })
+}
+
+fn start_arbitrage_service(pool: sqlx::PgPool, config: &Config) -> JoinHandle<()> {
+    info!("💰 Starting Arbitrage Service...");
+    let service = crate::arbitrage::ArbitrageService::new(
+        pool,
+        config.arbitrage_freshness_secs,
+        config.arbitrage_total_stake,
+        config.arbitrage_min_margin,
+        config.arbitrage_min_liquidity,
+    );
+    let min_delay = Duration::from_secs(config.arbitrage_min_delay_secs);
+    let max_delay = Duration::from_secs(config.arbitrage_max_delay_secs);
+    tokio::spawn(async move {
+        service.run(min_delay, max_delay).await;
+    })
+}
+
+fn start_arbitrer_service(state: Arc<AppState>, config: &Config) -> JoinHandle<()> {
+    info!("🎲 Starting Arbitrer Service...");
+    let service = crate::arbitrer::ArbitrerService::new(
+        state,
+        config.arbitrer_min_margin,
+        config.arbitrage_total_stake,
+    );
+    let min_delay = Duration::from_secs(config.arbitrer_min_delay_secs);
+    let max_delay = Duration::from_secs(config.arbitrer_max_delay_secs);
+    tokio::spawn(async move {
+        service.run(min_delay, max_delay).await;
+    })
+}
+
+/// Spawn the one-shot historical backfill, independent of the live Monaco ingestion loop
+/// started by `start_monaco_services`. Runs once and exits; it is not re-triggered by a
+/// config reload.
+fn start_backfill_service(state: Arc<AppState>, config: &Config) -> JoinHandle<()> {
+    info!("📼 Starting Monaco backfill service...");
+    let start = match chrono::DateTime::parse_from_rfc3339(&config.backfill_start) {
+        Ok(dt) => dt.with_timezone(&chrono::Utc),
+        Err(e) => {
+            tracing::error!("❌ Invalid BACKFILL_START '{}': {}. Skipping backfill.", config.backfill_start, e);
+            return tokio::spawn(async {});
+        }
+    };
+    let end = match chrono::DateTime::parse_from_rfc3339(&config.backfill_end) {
+        Ok(dt) => dt.with_timezone(&chrono::Utc),
+        Err(e) => {
+            tracing::error!("❌ Invalid BACKFILL_END '{}': {}. Skipping backfill.", config.backfill_end, e);
+            return tokio::spawn(async {});
+        }
+    };
+    let batch_days = config.backfill_batch_days;
+    let batch_size = config.backfill_page_size;
+    let concurrency = config.backfill_concurrency;
+    let team_alias_similarity_threshold = config.team_alias_similarity_threshold;
+    let team_alias_window_hours = config.team_alias_window_hours;
+    tokio::spawn(async move {
+        monaco::backfill::run_backfill(
+            state,
+            start,
+            end,
+            batch_days,
+            batch_size,
+            concurrency,
+            team_alias_similarity_threshold,
+            team_alias_window_hours,
+        )
+        .await;
+    })
+}
+
+fn start_ratings_service(pool: sqlx::PgPool, config: &Config) -> JoinHandle<()> {
+    info!("📊 Starting Team Ratings Service...");
+    let refresh_interval = Duration::from_secs(config.ratings_refresh_interval_secs);
+    let half_life_days = config.ratings_half_life_days;
+    tokio::spawn(async move {
+        ratings::run(pool, refresh_interval, half_life_days).await;
+    })
+}
+
+fn start_history_candles_service(pool: sqlx::PgPool, config: &Config) -> JoinHandle<()> {
+    info!("🕯️ Starting History Candles Service...");
+    let refresh_interval = Duration::from_secs(config.history_candles_refresh_interval_secs);
+    let resolutions_secs = config.candle_intervals_secs.clone();
+    tokio::spawn(async move {
+        history_candles::run(pool, refresh_interval, resolutions_secs).await;
+    })
+}
+
+fn start_line_movement_service(pool: sqlx::PgPool, config: &Config) -> JoinHandle<()> {
+    info!("📈 Starting Line Movement Service...");
+    let (service, _steam_move_rx) = line_movement::LineMovementService::new(
+        pool,
+        config.line_movement_prob_threshold,
+        config.line_movement_window_secs,
+    );
+    let scan_interval = Duration::from_secs(config.line_movement_scan_interval_secs);
+    tokio::spawn(async move {
+        service.run(scan_interval).await;
+    })
+}
+
+fn start_middles_service(pool: sqlx::PgPool, config: &Config) -> JoinHandle<()> {
+    info!("🎯 Starting Middles Service...");
+    let service = middles::MiddlesService::new(pool, config.middles_min_window_size);
+    let scan_interval = Duration::from_secs(config.middles_scan_interval_secs);
+    tokio::spawn(async move {
+        service.run(scan_interval).await;
+    })
+}
+
 // --- Constants ---
 const BROADCAST_CHANNEL_CAPACITY: usize = 1000;
 const DB_MAX_CONNECTIONS: u32 = 20;
@@ -219,6 +568,15 @@ async fn health_check() -> &'static str {
     "OK"
 }
 
+/// Prometheus text-exposition format scrape target for the counters/histograms in
+/// `AppState::metrics`.
+async fn metrics_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render_prometheus(),
+    )
+}
+
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
@@ -227,6 +585,30 @@ async fn ws_handler(
     ws.on_upgrade(|socket| handle_socket(socket, state))
 }
 
+#[derive(serde::Deserialize)]
+struct FixtureStructureQuery {
+    bookie: String,
+    t: i64,
+}
+
+/// Point-in-time view of a fixture's lines/ids/max_stakes structure, for backtesting
+/// against a historical snapshot instead of the live one (see
+/// `monaco::persistence::get_fixture_structure_at`).
+async fn fixture_structure_handler(
+    State(state): State<Arc<AppState>>,
+    Path(fixture_id): Path<i64>,
+    Query(params): Query<FixtureStructureQuery>,
+) -> impl IntoResponse {
+    match monaco::persistence::get_fixture_structure_at(&state.db, fixture_id, &params.bookie, params.t).await {
+        Ok(Some(structure)) => Json(structure).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            tracing::error!("Failed to fetch fixture structure for fixture_id={}: {}", fixture_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
 async fn handle_socket(mut socket: WebSocket, state: Arc<AppState>) {
     info!("✅ WebSocket client connected");
     let mut rx = state.tx.subscribe();
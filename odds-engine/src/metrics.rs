@@ -0,0 +1,269 @@
+// Lightweight metrics registry modeled on mango-feeds-connector's `MetricU64`/`MetricType`
+// pattern: a handful of named, label-keyed atomics rather than a full client library,
+// since the label cardinality here (msg_type x bookie_id) is small and fixed. Replaces the
+// periodic "processed N messages" `info!` line with counters and latency histograms an
+// operator can actually alert on, exported as Prometheus text over `/metrics`.
+use dashmap::DashMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A single named counter, cheap to clone (shares the same atomic).
+#[derive(Debug, Clone, Default)]
+pub struct MetricU64(Arc<AtomicU64>);
+
+impl MetricU64 {
+    pub fn increment(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+}
+
+/// Whether a `MetricU64` should be rendered as a Prometheus `counter` (monotonic) or
+/// `gauge` (can go down). Every counter in this registry is monotonic today, but this
+/// keeps `render_prometheus` honest about what it's emitting rather than hardcoding it.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+}
+
+impl MetricType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+        }
+    }
+}
+
+/// Fixed-bucket latency/duration histogram, in milliseconds. Buckets are cumulative
+/// (`le` semantics), matching the Prometheus histogram wire format directly.
+#[derive(Debug)]
+struct Histogram {
+    bounds: &'static [f64],
+    state: Mutex<HistogramState>,
+}
+
+#[derive(Debug, Default)]
+struct HistogramState {
+    bucket_counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+const LATENCY_BUCKETS_MS: &[f64] = &[
+    5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0, f64::INFINITY,
+];
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            bounds: LATENCY_BUCKETS_MS,
+            state: Mutex::new(HistogramState {
+                bucket_counts: vec![0; LATENCY_BUCKETS_MS.len()],
+                sum: 0.0,
+                count: 0,
+            }),
+        }
+    }
+
+    fn observe(&self, value_ms: f64) {
+        let mut state = self.state.lock().unwrap();
+        for (i, &bound) in self.bounds.iter().enumerate() {
+            if value_ms <= bound {
+                state.bucket_counts[i] += 1;
+            }
+        }
+        state.sum += value_ms;
+        state.count += 1;
+    }
+}
+
+/// Counters and histograms for one ingestion source, keyed by `msg_type` where relevant.
+/// One `Metrics` instance is shared across all sources via `AppState`; `bookie_id` is
+/// carried as a label on every series it exposes rather than via separate registries.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    received: DashMap<(String, i64), MetricU64>,
+    processed: DashMap<(String, i64), MetricU64>,
+    dropped: DashMap<(String, i64), MetricU64>,
+    unmapped_market_skips: DashMap<(String, i64), MetricU64>,
+    order_book_update_ms: DashMap<i64, Arc<Histogram>>,
+    ingest_lag_ms: DashMap<i64, Arc<Histogram>>,
+    // Market-processing pipeline (`fetch_and_process_markets`): these replace what used to
+    // be `info!`-only counters, so a sudden drop in fixture-match rate (a common sign the
+    // upstream Monaco schema changed and market-type resolution started dropping everything)
+    // shows up as an alertable series instead of something only visible by reading logs.
+    markets_fetched: MetricU64,
+    markets_processed: DashMap<String, MetricU64>,
+    markets_skipped_unmapped_type: DashMap<String, MetricU64>,
+    events_without_data: MetricU64,
+    events_no_fixture_match: MetricU64,
+    fixtures_matched: MetricU64,
+    market_fetch_cycle_ms: Arc<Histogram>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_received(&self, msg_type: &str, bookie_id: i64) {
+        self.received
+            .entry((msg_type.to_string(), bookie_id))
+            .or_default()
+            .increment();
+    }
+
+    pub fn record_processed(&self, msg_type: &str, bookie_id: i64) {
+        self.processed
+            .entry((msg_type.to_string(), bookie_id))
+            .or_default()
+            .increment();
+    }
+
+    pub fn record_dropped(&self, msg_type: &str, bookie_id: i64) {
+        self.dropped
+            .entry((msg_type.to_string(), bookie_id))
+            .or_default()
+            .increment();
+    }
+
+    pub fn record_unmapped_market(&self, msg_type: &str, bookie_id: i64) {
+        self.unmapped_market_skips
+            .entry((msg_type.to_string(), bookie_id))
+            .or_default()
+            .increment();
+    }
+
+    pub fn record_order_book_update_ms(&self, bookie_id: i64, duration_ms: f64) {
+        self.order_book_update_ms
+            .entry(bookie_id)
+            .or_insert_with(|| Arc::new(Histogram::new()))
+            .observe(duration_ms);
+    }
+
+    /// Record the `validAt -> now` delta (publish-to-ingest latency) for `bookie_id`.
+    pub fn record_ingest_lag_ms(&self, bookie_id: i64, lag_ms: f64) {
+        self.ingest_lag_ms
+            .entry(bookie_id)
+            .or_insert_with(|| Arc::new(Histogram::new()))
+            .observe(lag_ms);
+    }
+
+    pub fn record_markets_fetched(&self, n: u64) {
+        self.markets_fetched.add(n);
+    }
+
+    pub fn record_market_processed(&self, market_type: &str) {
+        self.markets_processed.entry(market_type.to_string()).or_default().increment();
+    }
+
+    pub fn record_market_skipped_unmapped_type(&self, market_type_id: &str) {
+        self.markets_skipped_unmapped_type
+            .entry(market_type_id.to_string())
+            .or_default()
+            .increment();
+    }
+
+    pub fn record_event_without_data(&self) {
+        self.events_without_data.increment();
+    }
+
+    pub fn record_event_no_fixture_match(&self) {
+        self.events_no_fixture_match.increment();
+    }
+
+    pub fn record_fixture_matched(&self) {
+        self.fixtures_matched.increment();
+    }
+
+    /// Record the wall-clock duration of one whole `fetch_and_process_markets` cycle.
+    pub fn record_market_fetch_cycle_ms(&self, duration_ms: f64) {
+        self.market_fetch_cycle_ms.observe(duration_ms);
+    }
+
+    /// Render the full registry as Prometheus text-exposition format.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        render_counter(&mut out, "odds_engine_messages_received_total", "Messages received from the source stream, by msg_type and bookie_id", &self.received);
+        render_counter(&mut out, "odds_engine_messages_processed_total", "Messages successfully dispatched to a handler, by msg_type and bookie_id", &self.processed);
+        render_counter(&mut out, "odds_engine_messages_dropped_total", "Messages that matched no known handler, by msg_type and bookie_id", &self.dropped);
+        render_counter(&mut out, "odds_engine_unmapped_market_skips_total", "Price updates skipped for lacking a market mapping, by msg_type and bookie_id", &self.unmapped_market_skips);
+        render_histogram(&mut out, "odds_engine_order_book_update_duration_ms", "OrderBook.update() duration in milliseconds, by bookie_id", &self.order_book_update_ms);
+        render_histogram(&mut out, "odds_engine_ingest_lag_ms", "validAt -> now delta in milliseconds, by bookie_id", &self.ingest_lag_ms);
+        render_scalar_counter(&mut out, "odds_engine_market_init_markets_fetched_total", "Markets returned by the Monaco markets API across all fetch_and_process_markets cycles", &self.markets_fetched);
+        render_labeled_counter(&mut out, "odds_engine_market_init_markets_processed_total", "Markets resolved to a known market_type, by market_type", "market_type", &self.markets_processed);
+        render_labeled_counter(&mut out, "odds_engine_market_init_markets_skipped_total", "Markets skipped for resolving to no known market_type, by market_type_id", "market_type_id", &self.markets_skipped_unmapped_type);
+        render_scalar_counter(&mut out, "odds_engine_market_init_events_without_data_total", "Events referenced by a market but missing from the fetch's events payload", &self.events_without_data);
+        render_scalar_counter(&mut out, "odds_engine_market_init_events_no_fixture_match_total", "Events for which no football_fixtures row could be matched", &self.events_no_fixture_match);
+        render_scalar_counter(&mut out, "odds_engine_market_init_fixtures_matched_total", "Fixtures successfully matched and initialized", &self.fixtures_matched);
+        render_single_histogram(&mut out, "odds_engine_market_fetch_cycle_duration_ms", "fetch_and_process_markets wall-clock duration in milliseconds", &self.market_fetch_cycle_ms);
+        out
+    }
+}
+
+fn render_scalar_counter(out: &mut String, name: &str, help: &str, metric: &MetricU64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, MetricType::Counter.as_str());
+    let _ = writeln!(out, "{} {}", name, metric.0.load(Ordering::Relaxed));
+}
+
+fn render_labeled_counter(out: &mut String, name: &str, help: &str, label: &str, series: &DashMap<String, MetricU64>) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, MetricType::Counter.as_str());
+    for entry in series.iter() {
+        let _ = writeln!(out, "{}{{{}=\"{}\"}} {}", name, label, entry.key(), entry.value().0.load(Ordering::Relaxed));
+    }
+}
+
+fn render_single_histogram(out: &mut String, name: &str, help: &str, histogram: &Histogram) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    let state = histogram.state.lock().unwrap();
+    for (bound, count) in histogram.bounds.iter().zip(state.bucket_counts.iter()) {
+        let le = if bound.is_infinite() { "+Inf".to_string() } else { bound.to_string() };
+        let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, le, count);
+    }
+    let _ = writeln!(out, "{}_sum {}", name, state.sum);
+    let _ = writeln!(out, "{}_count {}", name, state.count);
+}
+
+fn render_counter(out: &mut String, name: &str, help: &str, series: &DashMap<(String, i64), MetricU64>) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, MetricType::Counter.as_str());
+    for entry in series.iter() {
+        let (msg_type, bookie_id) = entry.key();
+        let _ = writeln!(
+            out,
+            "{}{{msg_type=\"{}\",bookie_id=\"{}\"}} {}",
+            name, msg_type, bookie_id, entry.value().0.load(Ordering::Relaxed)
+        );
+    }
+}
+
+fn render_histogram(out: &mut String, name: &str, help: &str, series: &DashMap<i64, Arc<Histogram>>) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    for entry in series.iter() {
+        let bookie_id = *entry.key();
+        let state = entry.value().state.lock().unwrap();
+        for (bound, count) in entry.value().bounds.iter().zip(state.bucket_counts.iter()) {
+            let le = if bound.is_infinite() { "+Inf".to_string() } else { bound.to_string() };
+            let _ = writeln!(out, "{}_bucket{{bookie_id=\"{}\",le=\"{}\"}} {}", name, bookie_id, le, count);
+        }
+        let _ = writeln!(out, "{}_sum{{bookie_id=\"{}\"}} {}", name, bookie_id, state.sum);
+        let _ = writeln!(out, "{}_count{{bookie_id=\"{}\"}} {}", name, bookie_id, state.count);
+    }
+}
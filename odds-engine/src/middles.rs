@@ -0,0 +1,303 @@
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+/// A middle: backing the "low" side of a line at one book and the "high" side at
+/// another, such that a result strictly between the two lines wins both bets.
+#[derive(Debug, Clone)]
+pub struct MiddleOpportunity {
+    pub fixture_id: i64,
+    pub market: &'static str, // "ah" | "ou"
+    pub low_bookie: String,
+    pub low_line: f64,
+    pub low_odds: f64,
+    pub high_bookie: String,
+    pub high_line: f64,
+    pub high_odds: f64,
+    /// `high_line - low_line`; how wide the winning window is.
+    pub window_size: f64,
+    /// `1 - (1/low_odds + 1/high_odds)`; positive means the two legs alone are already
+    /// cheaper than break-even (an outright arb), negative is the usual case where the
+    /// middle's value comes entirely from landing in the window.
+    pub edge: f64,
+    /// Estimated probability the result lands strictly inside the window, from a Poisson
+    /// total-goals model seeded off the fixture's fair 1X2 probabilities. `None` when no
+    /// fair-probability quote is available to seed the model.
+    pub window_probability: Option<f64>,
+}
+
+struct LineQuote {
+    bookie: String,
+    /// Sorted line values with their over/home decimal odds.
+    low_side: Vec<(f64, f64)>,
+    /// Sorted line values with their under/away decimal odds.
+    high_side: Vec<(f64, f64)>,
+}
+
+/// Scans `odds_ah`/`odds_ou` across books for "middle" opportunities: a low total/handicap
+/// backed Over (or home) at one book and a higher total/handicap backed Under (or away) at
+/// another, where a result landing strictly between the two lines wins both bets. Runs as
+/// its own periodic background service, parallel to `ArbitrageService`.
+pub struct MiddlesService {
+    pool: PgPool,
+    /// Discard windows no wider than this (in goals/points); a window of 0 is just two
+    /// books quoting the same line and isn't a middle.
+    min_window_size: f64,
+}
+
+impl MiddlesService {
+    pub fn new(pool: PgPool, min_window_size: f64) -> Self {
+        Self { pool, min_window_size }
+    }
+
+    pub async fn run(&self, scan_interval: Duration) {
+        info!("Starting Middles Service (min window: {})", self.min_window_size);
+        let mut interval = tokio::time::interval(scan_interval);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.scan_cycle().await {
+                error!("Error in middles scan cycle: {}", e);
+            }
+        }
+    }
+
+    async fn scan_cycle(&self) -> Result<(), sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT fixture_id, bookie, decimals, odds_ah, odds_ou, lines, fair_probs
+            FROM football_odds
+            WHERE odds_ah IS NOT NULL OR odds_ou IS NOT NULL
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut ah_by_fixture: HashMap<i64, Vec<LineQuote>> = HashMap::new();
+        let mut ou_by_fixture: HashMap<i64, Vec<LineQuote>> = HashMap::new();
+        let mut fair_by_fixture: HashMap<i64, [f64; 3]> = HashMap::new();
+
+        for row in rows {
+            let fixture_id: i64 = row.get("fixture_id");
+            let bookie: String = row.get("bookie");
+            let odds_ah: Option<Value> = row.get("odds_ah");
+            let odds_ou: Option<Value> = row.get("odds_ou");
+            let lines: Option<Value> = row.get("lines");
+            let fair_probs: Option<Value> = row.get("fair_probs");
+            let decimals: i32 = row.get("decimals");
+            let scale = 10f64.powi(decimals);
+
+            if let Some(fair) = Self::latest_fair(fair_probs.as_ref()) {
+                fair_by_fixture.entry(fixture_id).or_insert(fair);
+            }
+
+            if let Some(quote) = Self::latest_quote(&bookie, "ah", "ah_h", "ah_a", scale, odds_ah.as_ref(), lines.as_ref()) {
+                ah_by_fixture.entry(fixture_id).or_default().push(quote);
+            }
+            if let Some(quote) = Self::latest_quote(&bookie, "ou", "ou_o", "ou_u", scale, odds_ou.as_ref(), lines.as_ref()) {
+                ou_by_fixture.entry(fixture_id).or_default().push(quote);
+            }
+        }
+
+        let mut found = 0;
+        for (fixture_id, quotes) in ah_by_fixture {
+            let fair = fair_by_fixture.get(&fixture_id).copied();
+            for opp in self.find_middles(fixture_id, "ah", &quotes, fair) {
+                self.persist_opportunity(&opp).await?;
+                found += 1;
+            }
+        }
+        for (fixture_id, quotes) in ou_by_fixture {
+            let fair = fair_by_fixture.get(&fixture_id).copied();
+            for opp in self.find_middles(fixture_id, "ou", &quotes, fair) {
+                self.persist_opportunity(&opp).await?;
+                found += 1;
+            }
+        }
+
+        if found > 0 {
+            debug!("Middles scan found {} opportunities", found);
+        }
+
+        Ok(())
+    }
+
+    fn latest_fair(fair_probs: Option<&Value>) -> Option<[f64; 3]> {
+        let fair = fair_probs?.as_array()?.last()?.get("fair")?.as_array()?;
+        if fair.len() != 3 {
+            return None;
+        }
+        Some([fair[0].as_f64()?, fair[1].as_f64()?, fair[2].as_f64()?])
+    }
+
+    /// Build a book's latest quote from its most recent `odds_ah`/`odds_ou` snapshot and
+    /// the matching `lines` snapshot (same array index convention used when they were
+    /// written: one line-value array per snapshot, parallel to the odds arrays). The stored
+    /// integer-encoded odds are divided by `scale` (`10^decimals`) to get decimal odds.
+    fn latest_quote(
+        bookie: &str,
+        market: &str,
+        side_a_key: &str,
+        side_b_key: &str,
+        scale: f64,
+        odds: Option<&Value>,
+        lines: Option<&Value>,
+    ) -> Option<LineQuote> {
+        let odds_entry = odds?.as_array()?.last()?;
+        let line_values: Vec<f64> = lines?
+            .as_array()?
+            .last()?
+            .get(market)?
+            .as_array()?
+            .iter()
+            .filter_map(|v| v.as_f64())
+            .collect();
+
+        let side_a: Vec<f64> = odds_entry.get(side_a_key)?.as_array()?.iter().filter_map(|v| v.as_f64()).map(|o| o / scale).collect();
+        let side_b: Vec<f64> = odds_entry.get(side_b_key)?.as_array()?.iter().filter_map(|v| v.as_f64()).map(|o| o / scale).collect();
+
+        if line_values.is_empty() || side_a.len() != line_values.len() || side_b.len() != line_values.len() {
+            return None;
+        }
+
+        Some(LineQuote {
+            bookie: bookie.to_string(),
+            low_side: line_values.iter().copied().zip(side_a.iter().copied()).collect(),
+            high_side: line_values.iter().copied().zip(side_b.iter().copied()).collect(),
+        })
+    }
+
+    /// For every pair of distinct books, try backing every low-side line (Over / home) at
+    /// one against every high-side line (Under / away) at the other, keeping only pairs
+    /// whose window (`high_line - low_line`) is wider than `min_window_size` and whose
+    /// odds are both above 1.0.
+    fn find_middles(
+        &self,
+        fixture_id: i64,
+        market: &'static str,
+        quotes: &[LineQuote],
+        fair: Option<[f64; 3]>,
+    ) -> Vec<MiddleOpportunity> {
+        let mut opportunities = Vec::new();
+
+        for low_quote in quotes {
+            for high_quote in quotes {
+                if low_quote.bookie == high_quote.bookie {
+                    continue;
+                }
+
+                for &(low_line, low_odds) in &low_quote.low_side {
+                    for &(high_line, high_odds) in &high_quote.high_side {
+                        let window_size = high_line - low_line;
+                        if window_size <= self.min_window_size || low_odds <= 1.0 || high_odds <= 1.0 {
+                            continue;
+                        }
+
+                        let edge = 1.0 - (1.0 / low_odds + 1.0 / high_odds);
+                        let window_probability = fair.map(|f| {
+                            estimate_window_probability(market, low_line, high_line, f)
+                        });
+
+                        opportunities.push(MiddleOpportunity {
+                            fixture_id,
+                            market,
+                            low_bookie: low_quote.bookie.clone(),
+                            low_line,
+                            low_odds,
+                            high_bookie: high_quote.bookie.clone(),
+                            high_line,
+                            high_odds,
+                            window_size,
+                            edge,
+                            window_probability,
+                        });
+                    }
+                }
+            }
+        }
+
+        opportunities
+    }
+
+    async fn persist_opportunity(&self, opp: &MiddleOpportunity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO middle_opportunities
+                (fixture_id, market, low_bookie, low_line, low_odds, high_bookie, high_line, high_odds,
+                 window_size, edge, window_probability, detected_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (fixture_id, market, low_bookie, low_line, high_bookie, high_line) DO UPDATE SET
+                low_odds = EXCLUDED.low_odds,
+                high_odds = EXCLUDED.high_odds,
+                window_size = EXCLUDED.window_size,
+                edge = EXCLUDED.edge,
+                window_probability = EXCLUDED.window_probability,
+                detected_at = EXCLUDED.detected_at
+            "#,
+        )
+        .bind(opp.fixture_id)
+        .bind(opp.market)
+        .bind(&opp.low_bookie)
+        .bind(opp.low_line)
+        .bind(opp.low_odds)
+        .bind(&opp.high_bookie)
+        .bind(opp.high_line)
+        .bind(opp.high_odds)
+        .bind(opp.window_size)
+        .bind(opp.edge)
+        .bind(opp.window_probability)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Estimate the probability the result lands strictly inside `(low_line, high_line)`,
+/// using a single-parameter Poisson model over the relevant quantity (total goals for
+/// `ou`, goal difference for `ah`). The Poisson mean is seeded from the fixture's fair
+/// 1X2 probabilities via a simple, deliberately approximate heuristic: a more balanced
+/// match (draw probability near its typical ~25%) maps to the historical average total
+/// of 2.5 goals, and skews away from that as the draw probability moves away from 25%.
+/// This is not a calibrated goal-expectancy model — it's a cheap directional estimate
+/// good enough for ranking middles, not for precise pricing.
+fn estimate_window_probability(market: &str, low_line: f64, high_line: f64, fair: [f64; 3]) -> f64 {
+    let p_draw = fair[1];
+    let lambda = (2.5 - (p_draw - 0.25) * 4.0).max(0.5);
+
+    match market {
+        "ou" => poisson_window_probability(lambda, low_line, high_line),
+        _ => {
+            // Asian handicap: approximate the goal-difference distribution as Poisson
+            // around 0 with the same total-goals variance halved between the two teams.
+            poisson_window_probability(lambda / 2.0, low_line, high_line)
+        }
+    }
+}
+
+/// `P(low_line < k < high_line)` for `k` a non-negative integer count, under
+/// `Poisson(lambda)`. Lines are typically `.0`/`.25`/`.5`/`.75` handicap/total values, so
+/// this sums the pmf over every integer strictly inside the open interval.
+fn poisson_window_probability(lambda: f64, low_line: f64, high_line: f64) -> f64 {
+    let lo = (low_line.floor() as i64 + 1).max(0);
+    let hi = high_line.ceil() as i64 - 1;
+    if hi < lo {
+        return 0.0;
+    }
+
+    (lo..=hi).map(|k| poisson_pmf(lambda, k as u64)).sum()
+}
+
+fn poisson_pmf(lambda: f64, k: u64) -> f64 {
+    let log_pmf = -lambda + (k as f64) * lambda.ln() - ln_factorial(k);
+    log_pmf.exp()
+}
+
+fn ln_factorial(n: u64) -> f64 {
+    (1..=n).map(|i| (i as f64).ln()).sum()
+}
@@ -0,0 +1,116 @@
+// Historical replay, independent of the live `fetch_and_process_markets` loop: an operator
+// kicks this off after downtime to reconstruct `market_mapping`/`event_to_fixture`/the order
+// book/`football_odds` for a past window without waiting on the periodic live refresh. Pages
+// through `[backfill_start, backfill_end)` in `batch_days`-sized windows across a bounded pool
+// of concurrent workers, and feeds each window through `market_init::process_markets_data` -
+// the exact same idempotent mapping + persistence path live ingestion uses - so a backfill
+// that overlaps the live feed is harmless rather than double-counting.
+use crate::monaco::client::MonacoApiClient;
+use crate::monaco::market_init;
+use crate::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinSet;
+use tracing::{error, info};
+
+/// Outcome of one `run_backfill` invocation, logged on completion.
+pub struct BackfillSummary {
+    pub windows_total: usize,
+    pub windows_failed: u64,
+}
+
+/// Replay `[start, end)` in `batch_days`-sized windows, `concurrency` of them in flight at
+/// once so a large backfill can't exhaust the Postgres connection pool the live loop also
+/// depends on.
+pub async fn run_backfill(
+    state: Arc<AppState>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    batch_days: i64,
+    batch_size: u32,
+    concurrency: usize,
+    team_alias_similarity_threshold: f64,
+    team_alias_window_hours: i64,
+) -> BackfillSummary {
+    let config = state.config.load_full();
+    let api_client = Arc::new(Mutex::new(MonacoApiClient::new(
+        config.monaco_base_url.clone(),
+        config.monaco_app_id.clone(),
+        config.monaco_api_key.clone(),
+    )));
+
+    let mut windows = Vec::new();
+    let mut window_start = start;
+    let step = ChronoDuration::days(batch_days.max(1));
+    while window_start < end {
+        let window_end = (window_start + step).min(end);
+        windows.push((window_start, window_end));
+        window_start = window_end;
+    }
+    let windows_total = windows.len();
+
+    info!("📼 Starting Monaco backfill: {} window(s) of {} day(s) from {} to {}, concurrency={}",
+        windows_total, batch_days, start.to_rfc3339(), end.to_rfc3339(), concurrency);
+
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let windows_failed = Arc::new(AtomicU64::new(0));
+    let mut tasks = JoinSet::new();
+
+    for (window_start, window_end) in windows {
+        let permit = semaphore.clone().acquire_owned().await.expect("backfill semaphore closed");
+        let state = state.clone();
+        let api_client = api_client.clone();
+        let windows_failed = windows_failed.clone();
+
+        tasks.spawn(async move {
+            let _permit = permit;
+            let from = window_start.to_rfc3339();
+            let to = window_end.to_rfc3339();
+
+            let markets_data = {
+                let mut client = api_client.lock().await;
+                client.fetch_all_markets_in_range(&from, &to, batch_size).await
+            };
+
+            let markets_data = match markets_data {
+                Ok(data) => data,
+                Err(e) => {
+                    error!("❌ Backfill window {}..{} failed to fetch: {}", from, to, e);
+                    windows_failed.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+            };
+
+            if let Err(e) = market_init::process_markets_data(
+                markets_data,
+                &state.db,
+                &state.market_mapping,
+                &state.event_to_fixture,
+                &state.order_book,
+                &state.market_rules,
+                &state.metrics,
+                team_alias_similarity_threshold,
+                team_alias_window_hours,
+            )
+            .await
+            {
+                error!("❌ Backfill window {}..{} failed to process: {}", from, to, e);
+                windows_failed.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+
+            info!("✅ Backfill window {}..{} processed", from, to);
+        });
+    }
+
+    while tasks.join_next().await.is_some() {}
+
+    let summary = BackfillSummary {
+        windows_total,
+        windows_failed: windows_failed.load(Ordering::Relaxed),
+    };
+    info!("📼 Monaco backfill complete: {}/{} windows failed", summary.windows_failed, summary.windows_total);
+    summary
+}
@@ -233,15 +233,32 @@ impl MonacoApiClient {
         &mut self,
         page: u32,
         event_ids: Option<Vec<String>>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        self.fetch_markets_page(page, 2000, event_ids, "Initializing,Open,Locked,Closed", None)
+            .await
+    }
+
+    /// Shared implementation behind `fetch_markets` (live) and the historical backfill path:
+    /// `statuses`/`date_range` differ between the two so the live loop only ever sees
+    /// currently-tradeable markets while a backfill can additionally pull `Settled` ones
+    /// bounded to a specific window.
+    pub async fn fetch_markets_page(
+        &mut self,
+        page: u32,
+        size: u32,
+        event_ids: Option<Vec<String>>,
+        statuses: &str,
+        date_range: Option<(&str, &str)>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         self.ensure_authenticated().await?;
         self.check_api_rate_limit().await?;
 
+        let size_str = size.to_string();
         let mut params = vec![
             ("marketTypeIds", "FOOTBALL_OVER_UNDER_TOTAL_GOALS,FOOTBALL_FULL_TIME_RESULT_HANDICAP,FOOTBALL_FULL_TIME_RESULT"),
             ("inPlayStatuses", "PrePlay,NotApplicable"),
-            ("statuses", "Initializing,Open,Locked,Closed"),
-            ("size", "2000"),
+            ("statuses", statuses),
+            ("size", &size_str),
         ];
 
         let page_str = page.to_string();
@@ -253,6 +270,11 @@ impl MonacoApiClient {
             params.push(("eventIds", &event_ids_str));
         }
 
+        if let Some((from, to)) = date_range {
+            params.push(("eventStartFrom", from));
+            params.push(("eventStartTo", to));
+        }
+
         info!("🔍 Fetching markets with params: {:?}", params);
 
         let response = self
@@ -331,4 +353,55 @@ impl MonacoApiClient {
 
         Ok(result)
     }
+
+    /// Backfill counterpart to `fetch_all_markets`: pages through `[from, to)` (RFC3339) in
+    /// `batch_size`-sized pages, additionally requesting `Settled` markets since a historical
+    /// window is expected to be mostly-settled rather than still-tradeable.
+    pub async fn fetch_all_markets_in_range(
+        &mut self,
+        from: &str,
+        to: &str,
+        batch_size: u32,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let mut page = 0;
+        let mut all_markets = Vec::new();
+        let mut all_events = Vec::new();
+        let mut all_event_groups = Vec::new();
+
+        loop {
+            let data = self
+                .fetch_markets_page(page, batch_size, None, "Initializing,Open,Locked,Closed,Settled", Some((from, to)))
+                .await?;
+
+            if let Some(markets_array) = data.get("markets").and_then(|m| m.as_array()) {
+                all_markets.extend(markets_array.clone());
+                if markets_array.len() < batch_size as usize {
+                    if let Some(events_array) = data.get("events").and_then(|e| e.as_array()) {
+                        all_events.extend(events_array.clone());
+                    }
+                    if let Some(groups_array) = data.get("eventGroups").and_then(|g| g.as_array()) {
+                        all_event_groups.extend(groups_array.clone());
+                    }
+                    break;
+                }
+            } else {
+                break;
+            }
+
+            if let Some(events_array) = data.get("events").and_then(|e| e.as_array()) {
+                all_events.extend(events_array.clone());
+            }
+            if let Some(groups_array) = data.get("eventGroups").and_then(|g| g.as_array()) {
+                all_event_groups.extend(groups_array.clone());
+            }
+
+            page += 1;
+        }
+
+        Ok(serde_json::json!({
+            "markets": all_markets,
+            "events": all_events,
+            "eventGroups": all_event_groups
+        }))
+    }
 }
@@ -0,0 +1,658 @@
+use crate::order_book::OrderBook;
+use crate::shared::types::PriceLevel;
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use tracing::info;
+
+/// Default number of price levels retained when persisting full order-book depth
+/// alongside the best-price fields.
+pub const DEFAULT_DEPTH_LEVELS: usize = 10;
+
+/// Notional sizes (in the order book's own liquidity units) a depth-weighted average
+/// fill price is precomputed for, so a consumer can answer "what price would N units
+/// fill at" without walking the ladder itself.
+const DEPTH_VWAP_SIZES: [f64; 3] = [100.0, 500.0, 1000.0];
+
+/// How `PriceTransform::apply` turns the margined, scaled price into the stored integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    Floor,
+    Round,
+    Ceil,
+}
+
+impl RoundingMode {
+    /// Parses an operator-facing name (`"floor"`/`"round"`/`"ceil"`), defaulting to
+    /// `Floor` on anything else so a typo'd config value degrades to the historical
+    /// behavior instead of panicking.
+    pub fn from_config_str(s: &str) -> Self {
+        match s {
+            "round" => RoundingMode::Round,
+            "ceil" => RoundingMode::Ceil,
+            _ => RoundingMode::Floor,
+        }
+    }
+
+    fn apply(self, x: f64) -> i32 {
+        match self {
+            RoundingMode::Floor => x.floor() as i32,
+            RoundingMode::Round => x.round() as i32,
+            RoundingMode::Ceil => x.ceil() as i32,
+        }
+    }
+}
+
+/// The margin/scale/rounding applied when a decimal price is encoded into the integer
+/// `odds_*`/`depth_*` fields. Defaults match the previously-hardcoded encoding (1% margin,
+/// 1000x scale, floor), so existing stored data keeps meaning unchanged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceTransform {
+    pub margin_factor: f64,
+    pub scale: f64,
+    pub rounding: RoundingMode,
+}
+
+impl Default for PriceTransform {
+    fn default() -> Self {
+        Self { margin_factor: 0.99, scale: 1000.0, rounding: RoundingMode::Floor }
+    }
+}
+
+impl PriceTransform {
+    fn apply(&self, price: f64) -> i32 {
+        self.rounding.apply(((price - 1.0) * self.margin_factor + 1.0) * self.scale)
+    }
+
+    /// Recovers the true decimal price a stored integer was encoded from, undoing the
+    /// margin and scale, so implied probabilities computed from it aren't skewed by the
+    /// stored margin.
+    pub fn invert(&self, transformed: i32) -> f64 {
+        (transformed as f64 / self.scale - 1.0) / self.margin_factor + 1.0
+    }
+}
+
+/// Builds the `PriceTransform` configured for `market_type` (`"x12"`/`"ah"`/`"ou"`), reading
+/// the per-market margin plus the shared scale/rounding off `config`. Falls back to the
+/// default encoding's margin for any other `market_type`.
+pub fn transform_for_market(market_type: &str, config: &crate::config::Config) -> PriceTransform {
+    let margin_factor = match market_type {
+        "x12" => config.odds_transform_margin_x12,
+        "ah" => config.odds_transform_margin_ah,
+        "ou" => config.odds_transform_margin_ou,
+        _ => PriceTransform::default().margin_factor,
+    };
+    PriceTransform {
+        margin_factor,
+        scale: config.odds_transform_scale,
+        rounding: RoundingMode::from_config_str(&config.odds_transform_rounding),
+    }
+}
+
+/// Encodes a decimal price into the stored integer form using the default margin/scale/
+/// rounding. `decimals` is accepted for call-site compatibility with the per-update odds
+/// extraction path (which has no `Config` in hand) but doesn't affect the encoding.
+pub fn transform_price(price: f64, _decimals: i32) -> i32 {
+    PriceTransform::default().apply(price)
+}
+
+/// How aggressively `odds_x12`/`odds_ah`/`odds_ou` (and their `depth_*` counterparts)
+/// are trimmed on every write, mirroring the age/count pruning
+/// `monaco::persistence::prune_retention` already applies to the `lines`/`ids`/
+/// `max_stakes` histories on the same row.
+#[derive(Debug, Clone, Copy)]
+pub struct OddsRetention {
+    /// Snapshots older than this (relative to the write's own timestamp) are dropped.
+    pub max_age_secs: i64,
+    /// The history is also capped at this many snapshots regardless of age.
+    pub max_entries: usize,
+    /// Collapse interior snapshots of a run where no price changed, keeping only the
+    /// run's first and last entry so its start/end are still visible to candles.
+    pub dedup_unchanged: bool,
+}
+
+/// Builds the configured `OddsRetention` used to prune `odds_*`/`depth_*` history on write.
+pub fn retention_from_config(config: &crate::config::Config) -> OddsRetention {
+    OddsRetention {
+        max_age_secs: config.odds_history_max_age_secs,
+        max_entries: config.odds_history_max_entries,
+        dedup_unchanged: config.odds_history_dedup_unchanged,
+    }
+}
+
+pub async fn update_database_with_best_prices(
+    pool: &PgPool,
+    fixture_id: i64,
+    market_type: &str,
+    order_book: &OrderBook,
+    market_mappings: &HashMap<String, crate::monaco::types::MarketMapping>,
+    depth_levels: usize,
+    retention: OddsRetention,
+    transform: PriceTransform,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let timestamp = Utc::now().timestamp();
+    let field_name = format!("odds_{}", market_type);
+    let depth_field_name = format!("depth_{}", market_type);
+
+    // Fetch existing data
+    let existing = sqlx::query(&format!(
+        r#"
+        SELECT {}, {}, lines, ids, max_stakes, latest_t
+        FROM football_odds
+        WHERE fixture_id = $1 AND bookie = $2
+        "#,
+        field_name, depth_field_name
+    ))
+    .bind(fixture_id)
+    .bind("Monaco")
+    .fetch_optional(pool)
+    .await?;
+
+    if existing.is_none() {
+        return Ok(()); // Record doesn't exist yet
+    }
+
+    let row = existing.unwrap();
+
+    use sqlx::Row;
+    let mut odds_array: Vec<Value> = serde_json::from_value(row.get(field_name.as_str())).unwrap_or_default();
+    let mut depth_array: Vec<Value> = serde_json::from_value(row.get(depth_field_name.as_str())).unwrap_or_default();
+    let lines_data: Vec<Value> = serde_json::from_value(row.get("lines")).unwrap_or_default();
+    let mut max_stakes_data: Vec<Value> = serde_json::from_value(row.get("max_stakes")).unwrap_or_default();
+    let current_latest_t: Value = row.get("latest_t");
+
+    let latest_lines_entry = lines_data.last();
+
+    let (new_odds_entry, new_depth_entry, max_stakes_entry) = build_market_entries(
+        market_type,
+        order_book,
+        market_mappings,
+        latest_lines_entry,
+        depth_levels,
+        timestamp,
+        transform,
+    );
+
+    // Merge odds entry
+    odds_array = merge_odds_entry(odds_array, new_odds_entry);
+    depth_array = merge_odds_entry(depth_array, new_depth_entry);
+    odds_array = prune_odds_history(odds_array, timestamp, retention);
+    depth_array = prune_odds_history(depth_array, timestamp, retention);
+
+    // Update latest_t
+    let mut updated_latest_t = current_latest_t.as_object().unwrap().clone();
+    updated_latest_t.insert(format!("{}_ts", market_type), serde_json::json!(timestamp));
+    updated_latest_t.insert("stakes_ts".to_string(), serde_json::json!(timestamp));
+
+    // Update max stakes
+    if max_stakes_data.is_empty() {
+        max_stakes_data.push(max_stakes_entry);
+    } else {
+        *max_stakes_data.last_mut().unwrap() = max_stakes_entry; // Overwrite the latest entry
+    }
+
+    // Update database
+    sqlx::query(&format!(
+        r#"
+        UPDATE football_odds
+        SET {} = $1, {} = $2, max_stakes = $3, latest_t = $4
+        WHERE fixture_id = $5 AND bookie = $6
+        "#,
+        field_name, depth_field_name
+    ))
+    .bind(serde_json::to_value(&odds_array)?)
+    .bind(serde_json::to_value(&depth_array)?)
+    .bind(serde_json::to_value(&max_stakes_data)?)
+    .bind(Value::Object(updated_latest_t))
+    .bind(fixture_id)
+    .bind("Monaco")
+    .execute(pool)
+    .await?;
+
+    info!("✅ Updated {} odds for fixture_id={}", market_type, fixture_id);
+    Ok(())
+}
+
+/// Reads the best prices (and full depth, where requested) out of `order_book` for one
+/// market and shapes them into the `odds_{market_type}`/`depth_{market_type}`/`max_stakes`
+/// entries a single `football_odds` row stores, without touching the database. Shared by
+/// `update_database_with_best_prices` and `update_database_batch` so the two entry points
+/// can't drift on how a market gets turned into stored JSON.
+fn build_market_entries(
+    market_type: &str,
+    order_book: &OrderBook,
+    market_mappings: &HashMap<String, crate::monaco::types::MarketMapping>,
+    latest_lines_entry: Option<&Value>,
+    depth_levels: usize,
+    timestamp: i64,
+    transform: PriceTransform,
+) -> (Value, Value, Value) {
+    let mut new_odds_entry = serde_json::json!({ "t": timestamp });
+    // Full ladder depth, kept alongside (not instead of) the best-price entry above so
+    // existing readers of `odds_{market_type}` see no change.
+    let mut new_depth_entry = serde_json::json!({ "t": timestamp });
+
+    // Create new max stakes entry from current order book state
+    let mut max_stakes_entry = serde_json::json!({ "t": timestamp });
+
+    // Build odds entry based on market type
+    match market_type {
+        "x12" => {
+            let mut x12_prices = vec![0i32; 3];
+            let mut x12_stakes = [0.0, 0.0, 0.0];
+            let mut x12_depth: Vec<Value> = vec![Value::Null; 3];
+
+            for (outcome_id, price_levels) in order_book {
+                // Find outcome index
+                let mut outcome_index: Option<usize> = None;
+                for mapping in market_mappings.values() {
+                    if let Some(mappings) = &mapping.outcome_mappings {
+                        if let Some(&idx) = mappings.get(outcome_id) {
+                            outcome_index = Some(idx);
+                            break;
+                        }
+                    }
+                }
+
+                if let Some(idx) = outcome_index {
+                    if idx < 3 && !price_levels.is_empty() {
+                        let best_level = &price_levels[0];
+                        x12_prices[idx] = transform.apply(best_level.price);
+                        x12_stakes[idx] = best_level.liquidity;
+                        x12_depth[idx] = build_depth_entry(price_levels, depth_levels, transform);
+                    }
+                }
+            }
+
+            new_odds_entry["x12"] = serde_json::json!(x12_prices);
+            max_stakes_entry["max_stake_x12"] = serde_json::json!(x12_stakes);
+            new_depth_entry["x12"] = serde_json::json!(x12_depth);
+        }
+        "ah" | "ou" => {
+            // Get line values from latest lines entry
+            if let Some(lines) = latest_lines_entry {
+                let line_values: Vec<f64> = if market_type == "ah" {
+                    serde_json::from_value(lines["ah"].clone()).unwrap_or_default()
+                } else {
+                    serde_json::from_value(lines["ou"].clone()).unwrap_or_default()
+                };
+
+                if !line_values.is_empty() {
+                    let line_count = line_values.len();
+                    let (home_key, away_key) = if market_type == "ah" {
+                        ("ah_h", "ah_a")
+                    } else {
+                        ("ou_o", "ou_u")
+                    };
+
+                    new_odds_entry[home_key] = serde_json::json!(vec![0i32; line_count]);
+                    new_odds_entry[away_key] = serde_json::json!(vec![0i32; line_count]);
+
+                    // Initialize max stakes with zeros
+                    let stake_key = if market_type == "ah" { "max_stake_ah" } else { "max_stake_ou" };
+                    let mut home_stakes = vec![0.0; line_count];
+                    let mut away_stakes = vec![0.0; line_count];
+                    let mut home_depth: Vec<Value> = vec![Value::Null; line_count];
+                    let mut away_depth: Vec<Value> = vec![Value::Null; line_count];
+
+                    for (outcome_id, price_levels) in order_book {
+                        // Find line value and outcome index for this outcome
+                        let mut outcome_line_value: Option<f64> = None;
+                        let mut outcome_index: Option<usize> = None;
+
+                        for mapping in market_mappings.values() {
+                            if mapping.market_type == market_type {
+                                if let Some(mappings) = &mapping.outcome_mappings {
+                                    if let Some(&idx) = mappings.get(outcome_id) {
+                                        outcome_line_value = mapping.line_value;
+                                        outcome_index = Some(idx);
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+
+                        if let (Some(line_val), Some(out_idx)) = (outcome_line_value, outcome_index) {
+                            if let Some(line_index) = line_values.iter().position(|&v| v == line_val) {
+                                if !price_levels.is_empty() {
+                                    let best_level = &price_levels[0];
+                                    let transformed_price = transform.apply(best_level.price);
+                                    let is_home = out_idx % 2 == 0;
+
+                                    if is_home {
+                                        new_odds_entry[home_key][line_index] = serde_json::json!(transformed_price);
+                                        home_stakes[line_index] = best_level.liquidity;
+                                        home_depth[line_index] = build_depth_entry(price_levels, depth_levels, transform);
+                                    } else {
+                                        new_odds_entry[away_key][line_index] = serde_json::json!(transformed_price);
+                                        away_stakes[line_index] = best_level.liquidity;
+                                        away_depth[line_index] = build_depth_entry(price_levels, depth_levels, transform);
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    // Set the calculated max stakes
+                    max_stakes_entry[stake_key] = serde_json::json!({
+                        "h": home_stakes,
+                        "a": away_stakes
+                    });
+                    new_depth_entry[home_key] = serde_json::json!(home_depth);
+                    new_depth_entry[away_key] = serde_json::json!(away_depth);
+                }
+            }
+        }
+        _ => {}
+    }
+
+    (new_odds_entry, new_depth_entry, max_stakes_entry)
+}
+
+/// One `football_odds` row's mutable working state while a batch of updates is merged in
+/// memory, ahead of a single write-back. Mirrors the columns `update_database_with_best_prices`
+/// reads/writes one at a time, but holds all three markets at once since a batch can touch
+/// more than one market for the same fixture.
+struct FixtureRow {
+    odds_x12: Vec<Value>,
+    odds_ah: Vec<Value>,
+    odds_ou: Vec<Value>,
+    depth_x12: Vec<Value>,
+    depth_ah: Vec<Value>,
+    depth_ou: Vec<Value>,
+    lines: Vec<Value>,
+    max_stakes: Vec<Value>,
+    latest_t: Value,
+}
+
+impl FixtureRow {
+    fn odds_mut(&mut self, market_type: &str) -> Option<&mut Vec<Value>> {
+        match market_type {
+            "x12" => Some(&mut self.odds_x12),
+            "ah" => Some(&mut self.odds_ah),
+            "ou" => Some(&mut self.odds_ou),
+            _ => None,
+        }
+    }
+
+    fn depth_mut(&mut self, market_type: &str) -> Option<&mut Vec<Value>> {
+        match market_type {
+            "x12" => Some(&mut self.depth_x12),
+            "ah" => Some(&mut self.depth_ah),
+            "ou" => Some(&mut self.depth_ou),
+            _ => None,
+        }
+    }
+}
+
+/// Batched counterpart to `update_database_with_best_prices`: merges many
+/// `(fixture_id, market_type, order_book)` updates in memory off a single `SELECT ... WHERE
+/// fixture_id = ANY($1)`, then writes every touched row back in one `UPDATE ... FROM
+/// (SELECT ... UNNEST(...))` statement inside a transaction, instead of one round-trip pair
+/// per update. Fixtures with no existing `football_odds` row are skipped, same as the
+/// single-update path. `transforms` looks up the `PriceTransform` for each update's own
+/// market type, falling back to the default encoding if a market type has none configured.
+pub async fn update_database_batch(
+    pool: &PgPool,
+    updates: &[(i64, &str, &OrderBook)],
+    market_mappings: &HashMap<String, crate::monaco::types::MarketMapping>,
+    depth_levels: usize,
+    retention: OddsRetention,
+    transforms: &HashMap<String, PriceTransform>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if updates.is_empty() {
+        return Ok(());
+    }
+
+    let timestamp = Utc::now().timestamp();
+
+    let mut fixture_ids: Vec<i64> = updates.iter().map(|(fixture_id, _, _)| *fixture_id).collect();
+    fixture_ids.sort_unstable();
+    fixture_ids.dedup();
+
+    let rows = sqlx::query(
+        r#"
+        SELECT fixture_id, odds_x12, odds_ah, odds_ou, depth_x12, depth_ah, depth_ou, lines, max_stakes, latest_t
+        FROM football_odds
+        WHERE fixture_id = ANY($1) AND bookie = 'Monaco'
+        "#,
+    )
+    .bind(&fixture_ids)
+    .fetch_all(pool)
+    .await?;
+
+    use sqlx::Row;
+    let mut by_fixture: HashMap<i64, FixtureRow> = HashMap::new();
+    for row in rows {
+        let fixture_id: i64 = row.get("fixture_id");
+        by_fixture.insert(
+            fixture_id,
+            FixtureRow {
+                odds_x12: serde_json::from_value(row.get("odds_x12")).unwrap_or_default(),
+                odds_ah: serde_json::from_value(row.get("odds_ah")).unwrap_or_default(),
+                odds_ou: serde_json::from_value(row.get("odds_ou")).unwrap_or_default(),
+                depth_x12: serde_json::from_value(row.get("depth_x12")).unwrap_or_default(),
+                depth_ah: serde_json::from_value(row.get("depth_ah")).unwrap_or_default(),
+                depth_ou: serde_json::from_value(row.get("depth_ou")).unwrap_or_default(),
+                lines: serde_json::from_value(row.get("lines")).unwrap_or_default(),
+                max_stakes: serde_json::from_value(row.get("max_stakes")).unwrap_or_default(),
+                latest_t: row.get("latest_t"),
+            },
+        );
+    }
+
+    for (fixture_id, market_type, order_book) in updates {
+        let Some(fixture_row) = by_fixture.get_mut(fixture_id) else {
+            continue; // Record doesn't exist yet
+        };
+
+        let latest_lines_entry = fixture_row.lines.last().cloned();
+        let transform = transforms.get(*market_type).copied().unwrap_or_default();
+        let (new_odds_entry, new_depth_entry, max_stakes_entry) = build_market_entries(
+            market_type,
+            order_book,
+            market_mappings,
+            latest_lines_entry.as_ref(),
+            depth_levels,
+            timestamp,
+            transform,
+        );
+
+        if let Some(odds) = fixture_row.odds_mut(market_type) {
+            let merged = merge_odds_entry(std::mem::take(odds), new_odds_entry);
+            *odds = prune_odds_history(merged, timestamp, retention);
+        }
+        if let Some(depth) = fixture_row.depth_mut(market_type) {
+            let merged = merge_odds_entry(std::mem::take(depth), new_depth_entry);
+            *depth = prune_odds_history(merged, timestamp, retention);
+        }
+
+        if fixture_row.max_stakes.is_empty() {
+            fixture_row.max_stakes.push(max_stakes_entry);
+        } else {
+            *fixture_row.max_stakes.last_mut().unwrap() = max_stakes_entry;
+        }
+
+        let mut updated_latest_t = fixture_row.latest_t.as_object().cloned().unwrap_or_default();
+        updated_latest_t.insert(format!("{}_ts", market_type), serde_json::json!(timestamp));
+        updated_latest_t.insert("stakes_ts".to_string(), serde_json::json!(timestamp));
+        fixture_row.latest_t = Value::Object(updated_latest_t);
+    }
+
+    let touched_ids: Vec<i64> = by_fixture.keys().copied().collect();
+    let mut odds_x12 = Vec::with_capacity(touched_ids.len());
+    let mut odds_ah = Vec::with_capacity(touched_ids.len());
+    let mut odds_ou = Vec::with_capacity(touched_ids.len());
+    let mut depth_x12 = Vec::with_capacity(touched_ids.len());
+    let mut depth_ah = Vec::with_capacity(touched_ids.len());
+    let mut depth_ou = Vec::with_capacity(touched_ids.len());
+    let mut max_stakes = Vec::with_capacity(touched_ids.len());
+    let mut latest_t = Vec::with_capacity(touched_ids.len());
+
+    for &fixture_id in &touched_ids {
+        let row = &by_fixture[&fixture_id];
+        odds_x12.push(serde_json::to_value(&row.odds_x12)?);
+        odds_ah.push(serde_json::to_value(&row.odds_ah)?);
+        odds_ou.push(serde_json::to_value(&row.odds_ou)?);
+        depth_x12.push(serde_json::to_value(&row.depth_x12)?);
+        depth_ah.push(serde_json::to_value(&row.depth_ah)?);
+        depth_ou.push(serde_json::to_value(&row.depth_ou)?);
+        max_stakes.push(serde_json::to_value(&row.max_stakes)?);
+        latest_t.push(row.latest_t.clone());
+    }
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE football_odds AS f
+        SET odds_x12 = u.odds_x12,
+            odds_ah = u.odds_ah,
+            odds_ou = u.odds_ou,
+            depth_x12 = u.depth_x12,
+            depth_ah = u.depth_ah,
+            depth_ou = u.depth_ou,
+            max_stakes = u.max_stakes,
+            latest_t = u.latest_t
+        FROM (
+            SELECT *
+            FROM UNNEST($1::bigint[], $2::jsonb[], $3::jsonb[], $4::jsonb[], $5::jsonb[], $6::jsonb[], $7::jsonb[], $8::jsonb[], $9::jsonb[])
+                AS u(fixture_id, odds_x12, odds_ah, odds_ou, depth_x12, depth_ah, depth_ou, max_stakes, latest_t)
+        ) AS u
+        WHERE f.fixture_id = u.fixture_id AND f.bookie = 'Monaco'
+        "#,
+    )
+    .bind(&touched_ids)
+    .bind(&odds_x12)
+    .bind(&odds_ah)
+    .bind(&odds_ou)
+    .bind(&depth_x12)
+    .bind(&depth_ah)
+    .bind(&depth_ou)
+    .bind(&max_stakes)
+    .bind(&latest_t)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    info!("✅ Batch-updated odds for {} fixtures", touched_ids.len());
+    Ok(())
+}
+
+fn merge_odds_entry(mut existing: Vec<Value>, new_entry: Value) -> Vec<Value> {
+    let new_t = new_entry["t"].as_i64().unwrap_or(0);
+
+    if let Some(index) = existing.iter().position(|entry| entry["t"].as_i64().unwrap_or(0) == new_t) {
+        existing[index] = new_entry;
+    } else {
+        existing.push(new_entry);
+    }
+
+    existing.sort_by_key(|entry| entry["t"].as_i64().unwrap_or(0));
+    existing
+}
+
+/// Trims a (sorted, merged) `odds_*`/`depth_*` history down to `retention`'s bounds: drops
+/// anything older than `max_age_secs`, caps the remainder at `max_entries` by dropping the
+/// oldest survivors, then optionally collapses interior snapshots of an unchanged run.
+fn prune_odds_history(mut history: Vec<Value>, now: i64, retention: OddsRetention) -> Vec<Value> {
+    history.retain(|entry| now - entry["t"].as_i64().unwrap_or(0) <= retention.max_age_secs);
+
+    if history.len() > retention.max_entries {
+        let excess = history.len() - retention.max_entries;
+        history.drain(0..excess);
+    }
+
+    if retention.dedup_unchanged {
+        history = dedup_unchanged_runs(history);
+    }
+
+    history
+}
+
+/// Drops interior entries of a run of consecutive snapshots whose prices (everything but
+/// `t`) didn't change, keeping the run's first and last entry so its start/end timestamps -
+/// which candle reconstruction relies on - stay visible.
+fn dedup_unchanged_runs(history: Vec<Value>) -> Vec<Value> {
+    if history.len() < 3 {
+        return history;
+    }
+
+    let mut out = Vec::with_capacity(history.len());
+    out.push(history[0].clone());
+    for i in 1..history.len() - 1 {
+        let interior_of_flat_run = same_prices(&history[i], &history[i - 1]) && same_prices(&history[i], &history[i + 1]);
+        if !interior_of_flat_run {
+            out.push(history[i].clone());
+        }
+    }
+    out.push(history[history.len() - 1].clone());
+    out
+}
+
+/// Whether two snapshot entries quote the same prices, ignoring their (necessarily
+/// different) `t`.
+fn same_prices(a: &Value, b: &Value) -> bool {
+    let mut a_fields = a.as_object().cloned().unwrap_or_default();
+    let mut b_fields = b.as_object().cloned().unwrap_or_default();
+    a_fields.remove("t");
+    b_fields.remove("t");
+    a_fields == b_fields
+}
+
+/// Serializes one outcome/line's full ladder: the top `depth` levels with cumulative
+/// liquidity, plus a few depth-weighted average fill prices. `Value::Null` if the
+/// outcome has no levels at all, the same "nothing quoted yet" sentinel the best-price
+/// fields use for a zero entry.
+fn build_depth_entry(price_levels: &[PriceLevel], depth: usize, transform: PriceTransform) -> Value {
+    if price_levels.is_empty() {
+        return Value::Null;
+    }
+
+    let mut cum_liquidity = 0.0;
+    let levels: Vec<Value> = price_levels
+        .iter()
+        .take(depth)
+        .map(|level| {
+            cum_liquidity += level.liquidity;
+            serde_json::json!({
+                "price": transform.apply(level.price),
+                "liquidity": level.liquidity,
+                "cum_liquidity": cum_liquidity,
+            })
+        })
+        .collect();
+
+    let mut vwap = serde_json::Map::new();
+    for &size in &DEPTH_VWAP_SIZES {
+        if let Some(fill_price) = vwap_fill_price(price_levels, size) {
+            vwap.insert(size.to_string(), serde_json::json!(transform.apply(fill_price)));
+        }
+    }
+
+    serde_json::json!({ "levels": levels, "vwap": Value::Object(vwap) })
+}
+
+/// Walks the ladder from the best price down, filling `size` units of liquidity, and
+/// returns the liquidity-weighted average price paid. `None` if the ladder doesn't have
+/// enough total liquidity to fill the whole size.
+fn vwap_fill_price(price_levels: &[PriceLevel], size: f64) -> Option<f64> {
+    let mut remaining = size;
+    let mut cost = 0.0;
+
+    for level in price_levels {
+        if remaining <= 0.0 {
+            break;
+        }
+        let take = remaining.min(level.liquidity);
+        cost += take * level.price;
+        remaining -= take;
+    }
+
+    if remaining > 1e-9 {
+        return None;
+    }
+    Some(cost / size)
+}
@@ -0,0 +1,71 @@
+// Matched-bet / trade fill ingestion, normalized to a single schema regardless of
+// `status` (`New` for a fresh match, `Revoke` for a cancellation/void that flips a
+// prior fill rather than needing its own separate representation) - mirrors the
+// fill-unification approach from the Solana connector's trade-feed handling. This
+// gives the system a traded-volume record distinct from the quoted-odds order book.
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+/// Whether a fill is newly matched or reverses a previously reported one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FillStatus {
+    New,
+    Revoke,
+}
+
+impl FillStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            FillStatus::New => "New",
+            FillStatus::Revoke => "Revoke",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "New" => Some(FillStatus::New),
+            "Revoke" => Some(FillStatus::Revoke),
+            _ => None,
+        }
+    }
+}
+
+/// One matched bet, normalized across sources: `price` is the matched decimal odds passed
+/// through `transform_price` (the same integer encoding as the stored odds), liquidity traded as `stake`, and a `status`
+/// that lets a cancellation flip a prior fill instead of needing its own shape.
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub fixture_id: i64,
+    pub market_type: String,
+    pub outcome_id: String,
+    pub price: i32,
+    pub stake: f64,
+    pub side: String,
+    pub taker_ts: i64,
+    pub status: FillStatus,
+}
+
+/// Persist one fill. Unlike `engine_odds_candles`, fills are append-only: a `Revoke`
+/// is inserted as its own row rather than mutating the original, so the table is a
+/// ledger a consumer can net to zero rather than a point-in-time snapshot.
+pub async fn persist_fill(pool: &PgPool, fill: &Fill) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO fills
+            (fixture_id, market_type, outcome_id, price, stake, side, taker_ts, status)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(fill.fixture_id)
+    .bind(&fill.market_type)
+    .bind(&fill.outcome_id)
+    .bind(fill.price)
+    .bind(fill.stake)
+    .bind(&fill.side)
+    .bind(fill.taker_ts)
+    .bind(fill.status.as_str())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -1,11 +1,17 @@
 use serde_json::Value;
-use sqlx::PgPool;
+use sqlx::{PgPool, Row};
 use tracing::info;
 
+use crate::monaco::team_aliases;
+
+const MONACO_SOURCE: &str = "Monaco";
+
 pub async fn find_fixture_by_event(
     pool: &PgPool,
     event: &Value,
     event_id: &str,
+    similarity_threshold: f64,
+    window_hours: i64,
 ) -> Result<Option<i64>, Box<dyn std::error::Error + Send + Sync>> {
     // Parse team names from event name (e.g., "Manchester United v Liverpool")
     let event_name = match event["name"].as_str() {
@@ -62,17 +68,51 @@ pub async fn find_fixture_by_event(
         }
     };
 
-    // Find matching fixture
-    // Allow 24 hour window for start time matching
-    let fixture_result = sqlx::query_scalar::<_, i64>(
+    // First, canonicalize each parsed name through the alias table - an exact, previously
+    // learned spelling beats re-running the fuzzy match every time.
+    let home_team_id = team_aliases::resolve_alias(pool, home_team, MONACO_SOURCE).await?;
+    let away_team_id = team_aliases::resolve_alias(pool, away_team, MONACO_SOURCE).await?;
+
+    if let (Some(home_id), Some(away_id)) = (home_team_id, away_team_id) {
+        let fixture_result = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT id FROM football_fixtures
+            WHERE league_id = $1
+              AND home_team_id = $2
+              AND away_team_id = $3
+              AND date BETWEEN $4 - make_interval(hours => $5) AND $4 + make_interval(hours => $5)
+            ORDER BY date
+            LIMIT 1
+            "#
+        )
+        .bind(league_id)
+        .bind(home_id)
+        .bind(away_id)
+        .bind(expected_start_time)
+        .bind(window_hours as f64)
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some(fixture_id) = fixture_result {
+            info!("✅ Mapped event_id={} to fixture_id={} via alias ({} v {})", event_id, fixture_id, home_team, away_team);
+            return Ok(Some(fixture_id));
+        }
+    }
+
+    // Fall back to LIKE plus trigram fuzzy match, allowing a configurable window for start
+    // time matching.
+    let fixture_row = sqlx::query(
         r#"
-        SELECT id FROM football_fixtures
+        SELECT id, home_team_id, away_team_id,
+               similarity(home_team_name, $4)::float8 AS home_similarity,
+               similarity(away_team_name, $5)::float8 AS away_similarity
+        FROM football_fixtures
         WHERE league_id = $1
           AND (
               (LOWER(home_team_name) LIKE LOWER($2) AND LOWER(away_team_name) LIKE LOWER($3))
-              OR (similarity(home_team_name, $4) > 0.6 AND similarity(away_team_name, $5) > 0.6)
+              OR (similarity(home_team_name, $4) > $7 AND similarity(away_team_name, $5) > $7)
           )
-          AND date BETWEEN $6 - INTERVAL '24 hours' AND $6 + INTERVAL '24 hours'
+          AND date BETWEEN $6 - make_interval(hours => $8) AND $6 + make_interval(hours => $8)
         ORDER BY date
         LIMIT 1
         "#
@@ -83,12 +123,38 @@ pub async fn find_fixture_by_event(
     .bind(home_team)
     .bind(away_team)
     .bind(expected_start_time)
+    .bind(similarity_threshold)
+    .bind(window_hours as f64)
     .fetch_optional(pool)
     .await?;
 
-    if let Some(fixture_id) = fixture_result {
-        info!("âœ… Mapped event_id={} to fixture_id={} ({} v {})", event_id, fixture_id, home_team, away_team);
+    let Some(row) = fixture_row else {
+        // Neither an alias nor a fuzzy match resolved these spellings - queue them for an
+        // operator to review instead of failing silently every time this event is refetched.
+        let _ = team_aliases::log_unresolved(pool, home_team, MONACO_SOURCE).await;
+        let _ = team_aliases::log_unresolved(pool, away_team, MONACO_SOURCE).await;
+        return Ok(None);
+    };
+
+    let fixture_id: i64 = row.get("id");
+    let fixture_home_id: Option<i32> = row.get("home_team_id");
+    let fixture_away_id: Option<i32> = row.get("away_team_id");
+    let home_similarity: Option<f64> = row.get("home_similarity");
+    let away_similarity: Option<f64> = row.get("away_similarity");
+
+    // A fuzzy match for a previously-unseen spelling is learned as a new alias so the next
+    // lookup for this event resolves exactly.
+    if home_team_id.is_none() {
+        if let (Some(team_id), Some(confidence)) = (fixture_home_id, home_similarity) {
+            let _ = team_aliases::record_alias(pool, team_id, home_team, MONACO_SOURCE, confidence).await;
+        }
+    }
+    if away_team_id.is_none() {
+        if let (Some(team_id), Some(confidence)) = (fixture_away_id, away_similarity) {
+            let _ = team_aliases::record_alias(pool, team_id, away_team, MONACO_SOURCE, confidence).await;
+        }
     }
 
-    Ok(fixture_result)
+    info!("✅ Mapped event_id={} to fixture_id={} ({} v {})", event_id, fixture_id, home_team, away_team);
+    Ok(Some(fixture_id))
 }
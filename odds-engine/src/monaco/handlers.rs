@@ -1,7 +1,8 @@
+use crate::candles::{self, CandleKey};
 use crate::processor_client::OddsUpdate;
 use crate::shared::types::PriceLevel;
+use crate::source::OddsSource;
 use crate::AppState;
-use crate::monaco::stream::MonacoWebSocketClient;
 use crate::monaco::types::MarketMapping;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -9,59 +10,59 @@ use std::sync::Arc;
 use tracing::info;
 use chrono::{DateTime, Utc};
 
-/// Start the Monaco ingestion engine
-pub async fn start_ingestion_engine(state: Arc<AppState>, monaco_ws: MonacoWebSocketClient) {
-    info!("🔥 Monaco Ingestion Engine Started");
-    
-    // Start Monaco WebSocket (authentication already completed during market fetch)
-    let ws_client = Arc::new(monaco_ws);
-    let ws_client_clone = ws_client.clone();
-    tokio::spawn(async move {
-        info!("🚀 Launching Monaco WebSocket connection...");
-        ws_client_clone.start().await;
-    });
-
-    // Subscribe to Monaco messages
-    let mut rx = ws_client.subscribe();
-    info!("📻 Subscribed to Monaco message stream");
-
-    let mut message_count = 0;
-    
+/// Run an ingestion engine over any `OddsSource`; both Monaco and Betfair are fed
+/// through here, and nothing here is Monaco-specific: `source` supplies the connection, the
+/// bookie_id/decimals/bookmaker to tag updates with, and which raw messages count as
+/// price updates. Market-status events are still dispatched by raw `type` for now,
+/// since only Monaco emits them.
+pub async fn start_ingestion_engine(state: Arc<AppState>, source: Arc<dyn OddsSource>) {
+    info!("🔥 {} Ingestion Engine Started", source.bookmaker());
+
+    let mut rx = source.stream().await;
+    info!("📻 Subscribed to {} message stream", source.bookmaker());
+
+    let bookie_id = source.bookie_id();
+
     while let Ok(msg) = rx.recv().await {
-        message_count += 1;
-
-        // Process messages
-        if let Some(msg_type) = msg["type"].as_str() {
-            match msg_type {
-                "MarketPriceUpdate" => {
-                    let state_clone = state.clone();
-                    tokio::spawn(async move {
-                        if let Err(e) = handle_price_update(state_clone, msg).await {
-                            tracing::error!("Error handling price update: {}", e);
-                        }
-                    });
+        let msg_type = msg["type"].as_str().unwrap_or("unknown");
+        state.metrics.record_received(msg_type, bookie_id);
+
+        if source.parse(&msg).is_some() {
+            state.metrics.record_processed(msg_type, bookie_id);
+            let state_clone = state.clone();
+            let source_clone = source.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_price_update(state_clone, source_clone, msg).await {
+                    tracing::error!("Error handling price update: {}", e);
                 }
-                "MarketStatusUpdate" => {
-                    let state_clone = state.clone();
-                    tokio::spawn(async move {
-                        if let Err(e) = handle_market_status_update(state_clone, msg).await {
-                            tracing::error!("Error handling market status update: {}", e);
-                        }
-                    });
+            });
+        } else if msg_type == "MarketStatusUpdate" {
+            state.metrics.record_processed(msg_type, bookie_id);
+            let state_clone = state.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_market_status_update(state_clone, msg).await {
+                    tracing::error!("Error handling market status update: {}", e);
                 }
-                _ => {}
-            }
-        }
-        
-        if message_count % 200 == 0 {
-            info!("📊 Monaco: Processed {} messages total", message_count);
+            });
+        } else if msg_type == "MatchedBetUpdate" {
+            state.metrics.record_processed(msg_type, bookie_id);
+            let state_clone = state.clone();
+            let source_clone = source.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_matched_bet_update(state_clone, source_clone, msg).await {
+                    tracing::error!("Error handling matched bet update: {}", e);
+                }
+            });
+        } else {
+            state.metrics.record_dropped(msg_type, bookie_id);
         }
     }
 }
 
-/// Handle Monaco price updates
+/// Handle a price update from `source`
 pub async fn handle_price_update(
     state: Arc<AppState>,
+    source: Arc<dyn OddsSource>,
     message: Value,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Extract message fields
@@ -87,6 +88,7 @@ pub async fn handle_price_update(
         None => {
             // Market not yet mapped - need to fetch and process
             // For now, skip
+            state.metrics.record_unmapped_market("MarketPriceUpdate", source.bookie_id());
             return Ok(());
         }
     };
@@ -98,51 +100,157 @@ pub async fn handle_price_update(
     };
 
     // Update OrderBook
-    let order_book = {
+    let (previous_order_book, order_book) = {
         let mut ob = state.order_book.lock().await;
-        ob.update(
+        let previous = ob
+            .get_order_book(fixture_id, &market_mapping.market_type)
+            .cloned()
+            .unwrap_or_default();
+        let update_started = std::time::Instant::now();
+        let updated = ob.update(
             fixture_id,
             &message,
             &market_mapping.market_type,
             market_mapping.outcome_mappings.as_ref(),
-        )
+        );
+        state
+            .metrics
+            .record_order_book_update_ms(source.bookie_id(), update_started.elapsed().as_secs_f64() * 1000.0);
+        (previous, updated)
+    };
+
+    // Depth-of-book side channel: send a full ladder checkpoint the first time this
+    // market is seen, then only the levels that changed on every tick after, dropping
+    // updates whose derived sequence is older than what's already been applied.
+    if let Some(ref depth_client) = state.depth_client {
+        let mut tracker = state.depth_tracker.lock().await;
+        let sequence = tracker.derive_sequence(fixture_id, &market_mapping.market_type, &message);
+        if tracker.accept(fixture_id, &market_mapping.market_type, sequence) {
+            if tracker.needs_checkpoint(fixture_id, &market_mapping.market_type) {
+                let levels = order_book
+                    .iter()
+                    .map(|(outcome_id, price_levels)| {
+                        let dtos = price_levels
+                            .iter()
+                            .map(|l| crate::depth::PriceLevelDto { price: l.price, liquidity: l.liquidity })
+                            .collect();
+                        (outcome_id.clone(), dtos)
+                    })
+                    .collect();
+                depth_client
+                    .send(crate::depth::BookMessage::Checkpoint(crate::depth::BookCheckpoint {
+                        fixture_id,
+                        market_type: market_mapping.market_type.clone(),
+                        sequence,
+                        levels,
+                    }))
+                    .await;
+            } else {
+                let mut changed = HashMap::new();
+                for outcome_id in order_book.keys().chain(previous_order_book.keys()).collect::<std::collections::HashSet<_>>() {
+                    let old_levels = previous_order_book.get(outcome_id).map(|v| v.as_slice()).unwrap_or(&[]);
+                    let new_levels = order_book.get(outcome_id).map(|v| v.as_slice()).unwrap_or(&[]);
+                    let diff = crate::depth::diff_levels(old_levels, new_levels);
+                    if !diff.is_empty() {
+                        changed.insert(outcome_id.clone(), diff);
+                    }
+                }
+                if !changed.is_empty() {
+                    depth_client
+                        .send(crate::depth::BookMessage::Update(crate::depth::LevelUpdate {
+                            fixture_id,
+                            market_type: market_mapping.market_type.clone(),
+                            sequence,
+                            changed,
+                        }))
+                        .await;
+                }
+            }
+        }
+    }
+
+    // Feed the OHLC candle aggregator from the same top-of-book snapshot, so
+    // downstream consumers get historical odds-movement series rather than only
+    // the latest snapshot. Ticks on receive time rather than Monaco's `validAt`,
+    // since that's what "the bucket rolled over" means from this process's side.
+    let tick_timestamp = chrono::Utc::now().timestamp_millis();
+    let closed_candles = {
+        let mut aggregator = state.candle_aggregator.lock().await;
+        let mut closed = Vec::new();
+        for (outcome_id, price_levels) in &order_book {
+            if let Some(level) = price_levels.first() {
+                let key = CandleKey {
+                    fixture_id,
+                    market_type: market_mapping.market_type.clone(),
+                    outcome_id: outcome_id.clone(),
+                };
+                closed.extend(
+                    aggregator
+                        .record(key.clone(), level.price, level.liquidity, tick_timestamp)
+                        .into_iter()
+                        .map(|(interval_secs, candle)| (key.clone(), interval_secs, candle)),
+                );
+            }
+        }
+        closed
     };
+    for (key, interval_secs, candle) in closed_candles {
+        if let Err(e) = candles::persist_candle(&state.db, interval_secs, &key, &candle, true).await {
+            tracing::error!("Failed to persist candle: {}", e);
+        }
+    }
 
     // Get all market mappings for this fixture (needed for database update)
     let mappings = get_fixture_mappings(&state, fixture_id);
 
     // Update database with best prices
+    let config = state.config.load_full();
     super::db::update_database_with_best_prices(
         &state.db,
         fixture_id,
         &market_mapping.market_type,
         &order_book,
         &mappings,
+        super::db::DEFAULT_DEPTH_LEVELS,
+        super::db::retention_from_config(&config),
+        super::db::transform_for_market(&market_mapping.market_type, &config),
     )
     .await?;
 
-    // Send update to odds-processor
-    if let Some(ref client) = state.processor_client {
-        // Extract validAt timestamp from Monaco message for latency measurement
-        // This represents when Monaco actually published the odds, providing more accurate latency
-        let start_timestamp = if let Some(valid_at_str) = message["prices"][0]["validAt"].as_str() {
-            // Parse ISO 8601 timestamp from validAt field
-            if let Ok(valid_at_dt) = DateTime::parse_from_rfc3339(valid_at_str) {
-                valid_at_dt.with_timezone(&Utc).timestamp_millis()
-            } else {
-                // Fallback to received timestamp if parsing fails
-                message["_received_at"]
-                    .as_i64()
-                    .unwrap_or(chrono::Utc::now().timestamp_millis())
-            }
+    // Extract validAt timestamp from Monaco message for latency measurement
+    // This represents when Monaco actually published the odds, providing more accurate latency
+    let start_timestamp = if let Some(valid_at_str) = message["prices"][0]["validAt"].as_str() {
+        // Parse ISO 8601 timestamp from validAt field
+        if let Ok(valid_at_dt) = DateTime::parse_from_rfc3339(valid_at_str) {
+            valid_at_dt.with_timezone(&Utc).timestamp_millis()
         } else {
-            // Fallback to received timestamp if validAt is not available
+            // Fallback to received timestamp if parsing fails
             message["_received_at"]
                 .as_i64()
                 .unwrap_or(chrono::Utc::now().timestamp_millis())
-        };
+        }
+    } else {
+        // Fallback to received timestamp if validAt is not available
+        message["_received_at"]
+            .as_i64()
+            .unwrap_or(chrono::Utc::now().timestamp_millis())
+    };
+    state.metrics.record_ingest_lag_ms(
+        source.bookie_id(),
+        (chrono::Utc::now().timestamp_millis() - start_timestamp) as f64,
+    );
 
-        let update = build_odds_update(fixture_id, &market_mapping, &order_book, start_timestamp);
+    // Send update to odds-processor
+    if let Some(ref client) = state.processor_client {
+        let update = build_odds_update(
+            fixture_id,
+            source.bookie_id(),
+            source.decimals(),
+            source.bookmaker(),
+            &market_mapping,
+            &order_book,
+            start_timestamp,
+        );
         if let Some(update) = update {
             let _ = client.send(&update).await;
         }
@@ -202,6 +310,20 @@ pub async fn handle_market_status_update(
         ob.remove(fixture_id, &market_mapping.market_type);
     }
 
+    // Force-close any in-progress candles for this market instead of leaving them
+    // open forever: the order book was just zeroed, so there's no next tick left to
+    // roll the bucket over naturally.
+    let flushed_candles = state
+        .candle_aggregator
+        .lock()
+        .await
+        .flush_market(fixture_id, &market_mapping.market_type);
+    for (key, interval_secs, candle) in flushed_candles {
+        if let Err(e) = candles::persist_candle(&state.db, interval_secs, &key, &candle, true).await {
+            tracing::error!("Failed to persist candle: {}", e);
+        }
+    }
+
     // Create empty order book (all outcomes with empty price levels)
     let empty_order_book = {
         let mut book = HashMap::new();
@@ -217,18 +339,96 @@ pub async fn handle_market_status_update(
     let mappings = get_fixture_mappings(&state, fixture_id);
 
     // Update database with zeroed prices
+    let config = state.config.load_full();
     super::db::update_database_with_best_prices(
         &state.db,
         fixture_id,
         &market_mapping.market_type,
         &empty_order_book,
         &mappings,
+        super::db::DEFAULT_DEPTH_LEVELS,
+        super::db::retention_from_config(&config),
+        super::db::transform_for_market(&market_mapping.market_type, &config),
     )
     .await?;
 
     Ok(())
 }
 
+/// Handle a Monaco matched-bet/trade message: normalize each bet into a `Fill` and
+/// persist it as a traded-volume record distinct from the quoted-odds order book.
+pub async fn handle_matched_bet_update(
+    state: Arc<AppState>,
+    source: Arc<dyn OddsSource>,
+    message: Value,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let market_id = match message["marketId"].as_str() {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+
+    let event_id = match message["eventId"].as_str() {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+
+    let bets = match message["bets"].as_array() {
+        Some(b) if !b.is_empty() => b,
+        _ => return Ok(()),
+    };
+
+    // Lookup market mapping
+    let mapping_key = format!("{}-{}", event_id, market_id);
+    let market_mapping = match state.market_mapping.get(&mapping_key) {
+        Some(mapping) => mapping.clone(),
+        None => return Ok(()),
+    };
+
+    let fixture_id = match market_mapping.fixture_id {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+
+    for bet in bets {
+        let outcome_id = match bet["outcomeId"].as_str() {
+            Some(id) => id.to_string(),
+            None => continue,
+        };
+
+        let price = match bet["price"].as_f64() {
+            Some(p) => p,
+            None => continue,
+        };
+
+        let stake = bet["stake"].as_f64().unwrap_or(0.0);
+        let side = bet["side"].as_str().unwrap_or("Against").to_string();
+        let taker_ts = bet["takerTs"]
+            .as_i64()
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+        let status = bet["status"]
+            .as_str()
+            .and_then(crate::monaco::fills::FillStatus::from_str)
+            .unwrap_or(crate::monaco::fills::FillStatus::New);
+
+        let fill = crate::monaco::fills::Fill {
+            fixture_id,
+            market_type: market_mapping.market_type.clone(),
+            outcome_id,
+            price: super::db::transform_price(price, source.decimals()),
+            stake,
+            side,
+            taker_ts,
+            status,
+        };
+
+        if let Err(e) = crate::monaco::fills::persist_fill(&state.db, &fill).await {
+            tracing::error!("Failed to persist fill: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
 // --- Helper Functions ---
 
 /// Get all market mappings for a fixture
@@ -242,16 +442,18 @@ fn get_fixture_mappings(state: &AppState, fixture_id: i64) -> HashMap<String, Ma
     mappings
 }
 
-/// Build OddsUpdate for sending to odds-processor
+/// Build OddsUpdate for sending to odds-processor. `bookie_id`/`decimals`/`bookmaker`
+/// come from the source adapter rather than being hardcoded here, so this function
+/// works the same regardless of which exchange fed the order book.
 fn build_odds_update(
     fixture_id: i64,
+    bookie_id: i64,
+    decimals: i32,
+    bookmaker: &str,
     market_mapping: &MarketMapping,
     order_book: &HashMap<String, Vec<PriceLevel>>,
     start_timestamp: i64,
 ) -> Option<OddsUpdate> {
-    // Monaco bookie_id = 1, decimals = 3
-    let bookie_id = 1i64;
-    let decimals = 3i32;
     let timestamp = chrono::Utc::now().timestamp_millis();
 
     // Build IDs from outcome mappings
@@ -271,7 +473,7 @@ fn build_odds_update(
                 if let Some(mappings) = &market_mapping.outcome_mappings {
                     if let Some(&idx) = mappings.get(outcome_id) {
                         if idx < 3 && !price_levels.is_empty() {
-                            x12_odds[idx] = super::db::transform_price(price_levels[0].price, 3);
+                            x12_odds[idx] = super::db::transform_price(price_levels[0].price, decimals);
                         }
                     }
                 }
@@ -280,7 +482,7 @@ fn build_odds_update(
             Some(OddsUpdate {
                 fixture_id,
                 bookie_id,
-                bookmaker: "Monaco".to_string(),
+                bookmaker: bookmaker.to_string(),
                 timestamp,
                 start: start_timestamp,
                 decimals,
@@ -296,7 +498,7 @@ fn build_odds_update(
             let mut update = OddsUpdate {
                 fixture_id,
                 bookie_id,
-                bookmaker: "Monaco".to_string(),
+                bookmaker: bookmaker.to_string(),
                 timestamp,
                 start: start_timestamp,
                 decimals,
@@ -314,7 +516,7 @@ fn build_odds_update(
                     if let Some(mappings) = &market_mapping.outcome_mappings {
                         if let Some(&idx) = mappings.get(outcome_id) {
                             if !price_levels.is_empty() {
-                                let price = super::db::transform_price(price_levels[0].price, 3);
+                                let price = super::db::transform_price(price_levels[0].price, decimals);
                                 if idx % 2 == 0 {
                                     home_odds = price;
                                 } else {
@@ -1,10 +1,13 @@
 use crate::database::monaco_persistence;
+use crate::metrics::Metrics;
 use crate::monaco::fixture_mapping;
+use crate::monaco::market_rules::MarketRules;
 use crate::monaco::types::{MarketMapping, MonacoMarket};
 use dashmap::DashMap;
 use sqlx::PgPool;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::Mutex;
 use tracing::info;
 
@@ -17,6 +20,38 @@ pub async fn fetch_and_process_markets(
     market_mapping: &DashMap<String, MarketMapping>,
     event_to_fixture: &DashMap<String, i64>,
     order_book: &Arc<Mutex<MonacoOrderBook>>,
+    market_rules: &MarketRules,
+    metrics: &Metrics,
+    team_alias_similarity_threshold: f64,
+    team_alias_window_hours: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let cycle_started = Instant::now();
+    let result = fetch_and_process_markets_inner(
+        api_client,
+        pool,
+        market_mapping,
+        event_to_fixture,
+        order_book,
+        market_rules,
+        metrics,
+        team_alias_similarity_threshold,
+        team_alias_window_hours,
+    )
+    .await;
+    metrics.record_market_fetch_cycle_ms(cycle_started.elapsed().as_secs_f64() * 1000.0);
+    result
+}
+
+async fn fetch_and_process_markets_inner(
+    api_client: &Arc<Mutex<crate::monaco::client::MonacoApiClient>>,
+    pool: &PgPool,
+    market_mapping: &DashMap<String, MarketMapping>,
+    event_to_fixture: &DashMap<String, i64>,
+    order_book: &Arc<Mutex<MonacoOrderBook>>,
+    market_rules: &MarketRules,
+    metrics: &Metrics,
+    team_alias_similarity_threshold: f64,
+    team_alias_window_hours: i64,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     info!("🔄 Fetching markets from Monaco API...");
 
@@ -26,6 +61,35 @@ pub async fn fetch_and_process_markets(
         client.fetch_all_markets(None).await?
     };
 
+    process_markets_data(
+        markets_data,
+        pool,
+        market_mapping,
+        event_to_fixture,
+        order_book,
+        market_rules,
+        metrics,
+        team_alias_similarity_threshold,
+        team_alias_window_hours,
+    )
+    .await
+}
+
+/// Categorize/map/persist one already-fetched `{markets, events}` payload. Shared by the live
+/// loop above (fed from `fetch_all_markets`) and `backfill::run_backfill` (fed from
+/// `fetch_all_markets_in_range`), so a historical replay goes through exactly the same
+/// idempotent mapping + persistence path as live ingestion rather than a parallel one.
+pub async fn process_markets_data(
+    markets_data: serde_json::Value,
+    pool: &PgPool,
+    market_mapping: &DashMap<String, MarketMapping>,
+    event_to_fixture: &DashMap<String, i64>,
+    order_book: &Arc<Mutex<MonacoOrderBook>>,
+    market_rules: &MarketRules,
+    metrics: &Metrics,
+    team_alias_similarity_threshold: f64,
+    team_alias_window_hours: i64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Parse markets from response
     let markets: Vec<MonacoMarket> = if let Some(markets_val) = markets_data.get("markets") {
         if markets_val.is_array() {
@@ -59,6 +123,7 @@ pub async fn fetch_and_process_markets(
     };
 
     info!("✅ Fetched {} markets and {} events from Monaco", markets.len(), events.len());
+    metrics.record_markets_fetched(markets.len() as u64);
 
     // Create events map
     let mut events_map: HashMap<String, serde_json::Value> = HashMap::new();
@@ -79,23 +144,19 @@ pub async fn fetch_and_process_markets(
             .ok_or("No market type ID found")?
             .clone();
 
-        let market_type = match map_market_type(&market_type_id) {
-            Some(mt) => mt,
+        let market_type = match market_rules.resolve(&market_type_id) {
+            Some(mt) => mt.to_string(),
             None => {
                 markets_skipped_type += 1;
+                metrics.record_market_skipped_unmapped_type(&market_type_id);
                 continue;
             }
         };
 
         markets_processed += 1;
+        metrics.record_market_processed(&market_type);
 
-        let line_value = if market_type == "ah" {
-            get_handicap_value(market)
-        } else if market_type == "ou" {
-            get_total_value(market)
-        } else {
-            None
-        };
+        let line_value = market_rules.extract_line(market, &market_type_id);
 
         // Get event ID from the reference structure
         let event_id = market.event.ids.first()
@@ -144,13 +205,20 @@ pub async fn fetch_and_process_markets(
             Some(e) => e.clone(),
             None => {
                 events_without_data += 1;
+                metrics.record_event_without_data();
                 info!("⚠️  Event {} has no event data, skipping", event_id);
                 continue;
             }
         };
 
         // Try to find matching fixture
-        match fixture_mapping::find_fixture_by_event(pool, &event, &event_id).await {
+        match fixture_mapping::find_fixture_by_event(
+            pool,
+            &event,
+            &event_id,
+            team_alias_similarity_threshold,
+            team_alias_window_hours,
+        ).await {
             Ok(Some(fixture_id)) => {
                 // Update market mappings with fixture_id
                 for market in &event_markets {
@@ -162,18 +230,41 @@ pub async fn fetch_and_process_markets(
 
                 event_to_fixture.insert(event_id.clone(), fixture_id);
 
+                // Record each market's raw price snapshot idempotently before deriving any
+                // state from it, so a re-fetch/backfill of the same response never double-counts.
+                for market in &event_markets {
+                    let market_type_id = match market.market_type.ids.first() {
+                        Some(id) => id,
+                        None => continue,
+                    };
+                    let Some(market_type) = market_rules.resolve(market_type_id) else {
+                        continue;
+                    };
+                    if let Err(e) = monaco_persistence::persist_market_update(
+                        pool,
+                        fixture_id,
+                        market,
+                        market_type,
+                        market.sequence(),
+                    ).await {
+                        tracing::error!("Error persisting raw market update for market_id={}: {}", market.id, e);
+                    }
+                }
+
                 // Initialize database record
-                if let Err(e) = monaco_persistence::ensure_fixture_odds_record(pool, fixture_id, event_markets.clone()).await {
+                if let Err(e) = monaco_persistence::ensure_fixture_odds_record(pool, fixture_id, event_markets.clone(), market_rules).await {
                     tracing::error!("Error creating fixture odds record for fixture_id={}: {}", fixture_id, e);
                 } else {
                     // Initialize OrderBook
                     let mut ob = order_book.lock().await;
-                    ob.initialize(fixture_id, &event_markets, map_market_type);
+                    ob.initialize(fixture_id, &event_markets, market_rules);
                     fixtures_found += 1;
+                    metrics.record_fixture_matched();
                 }
             }
             Ok(None) => {
                 events_no_fixture_match += 1;
+                metrics.record_event_no_fixture_match();
             }
             Err(e) => {
                 tracing::error!("Error finding fixture for event {}: {}", event_id, e);
@@ -190,27 +281,3 @@ pub async fn fetch_and_process_markets(
     Ok(())
 }
 
-fn map_market_type(market_type_id: &str) -> Option<String> {
-    match market_type_id {
-        "FOOTBALL_FULL_TIME_RESULT" => Some("x12".to_string()),
-        "FOOTBALL_FULL_TIME_RESULT_HANDICAP" => Some("ah".to_string()),
-        "FOOTBALL_OVER_UNDER_TOTAL_GOALS" => Some("ou".to_string()),
-        _ => None,
-    }
-}
-
-fn get_handicap_value(market: &MonacoMarket) -> Option<f64> {
-    // Match: "Goal Handicap +1.5" or similar (same as Next.js)
-    let re = regex::Regex::new(r"Goal Handicap ([\+\-\d\.]+)").ok()?;
-    re.captures(&market.name)?.get(1)?.as_str().parse().ok()
-}
-
-fn get_total_value(market: &MonacoMarket) -> Option<f64> {
-    // Try to parse from market_value first, then fall back to name parsing
-    if let Some(ref market_value) = market.market_value {
-        market_value.parse().ok()
-    } else {
-        let re = regex::Regex::new(r"Total Goals Over/Under ([\d.]+)").ok()?;
-        re.captures(&market.name)?.get(1)?.as_str().parse().ok()
-    }
-}
@@ -0,0 +1,111 @@
+// Declarative replacement for the hardcoded `map_market_type` match and the
+// `get_handicap_value`/`get_total_value` regex helpers that used to live duplicated in
+// both `market_init.rs` and `persistence.rs`. Rules are loaded once at startup from a
+// TOML file so operators can add new Monaco market types (both-teams-to-score,
+// double-chance, correct-score, ...) without recompiling.
+use crate::monaco::types::MonacoMarket;
+use regex::Regex;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    rule: Vec<RawRule>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawRule {
+    market_type_id: String,
+    canonical: String,
+    #[serde(default)]
+    line: LineSource,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+enum LineSource {
+    #[default]
+    None,
+    MarketValue,
+    /// `pattern` must contain a named capture group `line` (e.g.
+    /// `Goal Handicap (?P<line>[+-\d.]+)`).
+    Name { pattern: String },
+}
+
+enum CompiledLineSource {
+    None,
+    MarketValue,
+    Name(Regex),
+}
+
+struct CompiledRule {
+    /// Lowercased, so `resolve`/`extract_line` can match case-insensitively.
+    market_type_id: String,
+    canonical: String,
+    line: CompiledLineSource,
+}
+
+/// The loaded, validated set of market-type/line-value mapping rules.
+pub struct MarketRules {
+    rules: Vec<CompiledRule>,
+}
+
+impl MarketRules {
+    /// Loads and validates the rules from a TOML file, compiling every regex up front so
+    /// a malformed rule fails fast at startup instead of silently dropping markets the
+    /// way the old `None => continue` branch did.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read market rules file '{}': {}", path, e))?;
+        let file: RulesFile = toml::from_str(&raw)
+            .map_err(|e| format!("failed to parse market rules file '{}': {}", path, e))?;
+
+        let mut compiled = Vec::with_capacity(file.rule.len());
+        for rule in file.rule {
+            let line = match rule.line {
+                LineSource::None => CompiledLineSource::None,
+                LineSource::MarketValue => CompiledLineSource::MarketValue,
+                LineSource::Name { pattern } => {
+                    let re = Regex::new(&pattern).map_err(|e| {
+                        format!(
+                            "invalid regex '{}' for market_type_id '{}': {}",
+                            pattern, rule.market_type_id, e
+                        )
+                    })?;
+                    CompiledLineSource::Name(re)
+                }
+            };
+            compiled.push(CompiledRule {
+                market_type_id: rule.market_type_id.to_lowercase(),
+                canonical: rule.canonical,
+                line,
+            });
+        }
+
+        Ok(Self { rules: compiled })
+    }
+
+    /// Case-insensitive lookup of the canonical internal market type ("x12"/"ah"/"ou"/...)
+    /// for a Monaco `market_type_id`.
+    pub fn resolve(&self, market_type_id: &str) -> Option<&str> {
+        let needle = market_type_id.to_lowercase();
+        self.rules
+            .iter()
+            .find(|r| r.market_type_id == needle)
+            .map(|r| r.canonical.as_str())
+    }
+
+    /// Extracts the line value (handicap/total) for `market` per its rule's
+    /// extraction strategy. Returns `None` for markets with no line (e.g. `x12`), that
+    /// matched no rule, or whose pattern didn't match.
+    pub fn extract_line(&self, market: &MonacoMarket, market_type_id: &str) -> Option<f64> {
+        let needle = market_type_id.to_lowercase();
+        let rule = self.rules.iter().find(|r| r.market_type_id == needle)?;
+        match &rule.line {
+            CompiledLineSource::None => None,
+            CompiledLineSource::MarketValue => market.market_value.as_ref()?.parse().ok(),
+            CompiledLineSource::Name(re) => {
+                re.captures(&market.name)?.name("line")?.as_str().parse().ok()
+            }
+        }
+    }
+}
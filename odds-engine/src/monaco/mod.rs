@@ -4,6 +4,11 @@ pub mod stream;
 pub mod market_init;
 pub mod db;
 pub mod handlers;
+pub mod fixture_mapping;
+pub mod team_aliases;
+pub mod market_rules;
+pub mod backfill;
 
 pub mod persistence;
 pub mod order_book;
+pub mod fills;
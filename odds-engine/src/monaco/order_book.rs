@@ -25,12 +25,17 @@ impl MonacoOrderBook {
         &mut self,
         fixture_id: i64,
         markets: &[crate::monaco::types::MonacoMarket],
-        map_market_type: fn(&str) -> Option<String>,
+        market_rules: &crate::monaco::market_rules::MarketRules,
     ) {
-        for market in markets {
+        // Process oldest-`modifiedAt`-first so a stale market snapshot applied out of order
+        // (e.g. during a backfill replay) can't overwrite a price that's already newer.
+        let mut ordered: Vec<&crate::monaco::types::MonacoMarket> = markets.iter().collect();
+        ordered.sort_by_key(|m| m.sequence());
+
+        for market in ordered {
             let market_type_id = market.market_type.ids.first().unwrap();
-            let market_type = match map_market_type(market_type_id) {
-                Some(mt) => mt,
+            let market_type = match market_rules.resolve(market_type_id) {
+                Some(mt) => mt.to_string(),
                 None => continue,
             };
 
@@ -1,11 +1,19 @@
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use serde_json::Value;
+use sqlx::{PgPool, Row};
 use std::collections::HashMap;
 use tracing::info;
 use chrono::Utc;
 
 use crate::monaco::types::MonacoMarket;
 
+/// How many structure snapshots to retain per `(fixture_id, bookie)` before the oldest is
+/// pruned, mirroring the filter DSL's field-history ring buffer.
+const MAX_STRUCTURE_ENTRIES: usize = 500;
+
+/// Snapshots older than this are pruned opportunistically on write, independent of count.
+const MAX_STRUCTURE_ENTRY_AGE_SECS: i64 = 90 * 24 * 60 * 60;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LinesEntry {
     pub t: i64,
@@ -13,6 +21,12 @@ pub struct LinesEntry {
     pub ou: Option<Vec<f64>>,
 }
 
+impl LinesEntry {
+    fn same_content(&self, other: &LinesEntry) -> bool {
+        self.ah == other.ah && self.ou == other.ou
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IdsEntry {
     pub t: i64,
@@ -20,7 +34,13 @@ pub struct IdsEntry {
     pub line_ids: LineIds,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl IdsEntry {
+    fn same_content(&self, other: &IdsEntry) -> bool {
+        self.line_id == other.line_id && self.line_ids == other.line_ids
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct LineIds {
     pub x12: Vec<String>,
     pub ah: Vec<String>,
@@ -35,12 +55,90 @@ pub struct MaxStakesEntry {
     pub max_stake_ou: Option<MaxStakeAhOu>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl MaxStakesEntry {
+    fn same_content(&self, other: &MaxStakesEntry) -> bool {
+        self.max_stake_x12 == other.max_stake_x12
+            && self.max_stake_ah == other.max_stake_ah
+            && self.max_stake_ou == other.max_stake_ou
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct MaxStakeAhOu {
     pub h: Vec<f64>, // Home/Over stakes
     pub a: Vec<f64>, // Away/Under stakes
 }
 
+/// The fixture's structure as it stood at a point in time: the latest entry in each history
+/// whose own timestamp is `<= t`, for backtesting against a point-in-time view instead of
+/// just the live snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct FixtureStructureAt {
+    pub lines: Option<LinesEntry>,
+    pub ids: Option<IdsEntry>,
+    pub max_stakes: Option<MaxStakesEntry>,
+}
+
+/// Append `entry` to `history` unless it's identical (ignoring `t`) to the last entry,
+/// pruning by age/count afterward. Returns whether `entry` was actually new.
+fn append_if_changed<T: Clone>(
+    history: &mut Vec<T>,
+    entry: T,
+    same_content: impl Fn(&T, &T) -> bool,
+) -> bool {
+    if let Some(last) = history.last() {
+        if same_content(last, &entry) {
+            return false;
+        }
+    }
+    history.push(entry);
+    true
+}
+
+fn prune_retention<T>(history: &mut Vec<T>, now: i64, get_t: impl Fn(&T) -> i64) {
+    history.retain(|e| now - get_t(e) <= MAX_STRUCTURE_ENTRY_AGE_SECS);
+    if history.len() > MAX_STRUCTURE_ENTRIES {
+        let excess = history.len() - MAX_STRUCTURE_ENTRIES;
+        history.drain(0..excess);
+    }
+}
+
+/// Entries are only appended (or the newest one replaced) with the current timestamp, so each history
+/// is already oldest-first; the last one with `t <= t` is simply the first match scanning
+/// from the newest end.
+fn latest_at_or_before<T: Clone>(history: &[T], t: i64, get_t: impl Fn(&T) -> i64) -> Option<T> {
+    history.iter().rev().find(|e| get_t(e) <= t).cloned()
+}
+
+/// Returns the fixture's `(lines, ids, max_stakes)` structure as it stood at timestamp `t`,
+/// for backtesting against a historical snapshot instead of the live one.
+pub async fn get_fixture_structure_at(
+    pool: &PgPool,
+    fixture_id: i64,
+    bookie: &str,
+    t: i64,
+) -> Result<Option<FixtureStructureAt>, Box<dyn std::error::Error + Send + Sync>> {
+    let row = sqlx::query(
+        "SELECT lines, ids, max_stakes FROM football_odds WHERE fixture_id = $1 AND bookie = $2"
+    )
+    .bind(fixture_id)
+    .bind(bookie)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(row) = row else { return Ok(None); };
+
+    let lines_history: Vec<LinesEntry> = serde_json::from_value(row.get("lines")).unwrap_or_default();
+    let ids_history: Vec<IdsEntry> = serde_json::from_value(row.get("ids")).unwrap_or_default();
+    let max_stakes_history: Vec<MaxStakesEntry> = serde_json::from_value(row.get("max_stakes")).unwrap_or_default();
+
+    Ok(Some(FixtureStructureAt {
+        lines: latest_at_or_before(&lines_history, t, |e| e.t),
+        ids: latest_at_or_before(&ids_history, t, |e| e.t),
+        max_stakes: latest_at_or_before(&max_stakes_history, t, |e| e.t),
+    }))
+}
+
 #[derive(Debug, Clone)]
 pub struct FixtureStructure {
     #[allow(dead_code)]
@@ -51,10 +149,57 @@ pub struct FixtureStructure {
     pub line_index_map: HashMap<String, usize>,
 }
 
+/// Persist one raw price-update row per outcome in `market`, keyed on
+/// `(update_id, outcome_index)` so re-fetching or replaying the same Monaco API response is
+/// naturally idempotent - mirrors keying fills on signature + log_index. `update_id` combines
+/// the market id with `sequence` (`MonacoMarket::sequence()`, the parsed `modifiedAt`) so an
+/// unchanged market re-fetched later produces the exact same key and is a no-op, while a
+/// genuinely updated market gets its own row instead of clobbering the old one.
+pub async fn persist_market_update(
+    pool: &PgPool,
+    fixture_id: i64,
+    market: &MonacoMarket,
+    market_type: &str,
+    sequence: i64,
+) -> Result<(), sqlx::Error> {
+    let Some(prices) = &market.prices else { return Ok(()); };
+    let update_id = format!("{}-{}", market.id, sequence);
+
+    for price in prices {
+        let Some(outcome_index) = market.market_outcomes.ids.iter().position(|id| id == &price.outcome_id) else {
+            continue;
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO monaco_market_updates
+                (update_id, outcome_index, fixture_id, market_id, market_type, outcome_id, side, price, liquidity, sequence)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (update_id, outcome_index) DO NOTHING
+            "#,
+        )
+        .bind(&update_id)
+        .bind(outcome_index as i32)
+        .bind(fixture_id)
+        .bind(&market.id)
+        .bind(market_type)
+        .bind(&price.outcome_id)
+        .bind(&price.side)
+        .bind(price.price)
+        .bind(price.liquidity)
+        .bind(sequence)
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
 pub async fn ensure_fixture_odds_record(
     pool: &PgPool,
     fixture_id: i64,
     markets: Vec<MonacoMarket>,
+    market_rules: &super::market_rules::MarketRules,
 ) -> Result<HashMap<String, usize>, Box<dyn std::error::Error + Send + Sync>> {
     if markets.is_empty() {
         return Ok(HashMap::new());
@@ -64,7 +209,7 @@ pub async fn ensure_fixture_odds_record(
     let timestamp = Utc::now().timestamp();
 
     // Build the fixture structure
-    let structure = build_fixture_structure(&markets, timestamp);
+    let structure = build_fixture_structure(&markets, timestamp, market_rules);
 
     // Serialize JSON fields
     let lines_json = serde_json::to_value(&vec![structure.lines_entry.clone()])?;
@@ -114,7 +259,40 @@ pub async fn ensure_fixture_odds_record(
 
         info!("✅ Database INSERT completed for fixture_id={}", fixture_id);
     } else {
-        // Update existing record
+        // Update existing record: append to the structure history rather than overwriting it,
+        // so `lines`/`ids`/`max_stakes` keep every distinct snapshot (see `get_fixture_structure_at`).
+        let row = sqlx::query(
+            "SELECT lines, ids, max_stakes, latest_t FROM football_odds WHERE fixture_id = $1 AND bookie = $2"
+        )
+        .bind(fixture_id)
+        .bind("Monaco")
+        .fetch_one(pool)
+        .await?;
+
+        let mut lines_history: Vec<LinesEntry> = serde_json::from_value(row.get("lines")).unwrap_or_default();
+        let mut ids_history: Vec<IdsEntry> = serde_json::from_value(row.get("ids")).unwrap_or_default();
+        let mut max_stakes_history: Vec<MaxStakesEntry> = serde_json::from_value(row.get("max_stakes")).unwrap_or_default();
+        let current_latest_t: Value = row.get("latest_t");
+        let mut updated_latest_t = current_latest_t.as_object().cloned().unwrap_or_default();
+
+        let lines_changed = append_if_changed(&mut lines_history, structure.lines_entry.clone(), LinesEntry::same_content);
+        let ids_changed = append_if_changed(&mut ids_history, structure.ids_entry.clone(), IdsEntry::same_content);
+        let stakes_changed = append_if_changed(&mut max_stakes_history, structure.max_stakes_entry.clone(), MaxStakesEntry::same_content);
+
+        prune_retention(&mut lines_history, timestamp, |e| e.t);
+        prune_retention(&mut ids_history, timestamp, |e| e.t);
+        prune_retention(&mut max_stakes_history, timestamp, |e| e.t);
+
+        if lines_changed {
+            updated_latest_t.insert("lines_ts".to_string(), serde_json::json!(timestamp));
+        }
+        if ids_changed {
+            updated_latest_t.insert("ids_ts".to_string(), serde_json::json!(timestamp));
+        }
+        if stakes_changed {
+            updated_latest_t.insert("stakes_ts".to_string(), serde_json::json!(timestamp));
+        }
+
         sqlx::query(
             r#"
             UPDATE football_odds
@@ -122,10 +300,10 @@ pub async fn ensure_fixture_odds_record(
             WHERE fixture_id = $6 AND bookie = $7
             "#
         )
-        .bind(&lines_json)
-        .bind(&ids_json)
-        .bind(&max_stakes_json)
-        .bind(&latest_t)
+        .bind(serde_json::to_value(&lines_history)?)
+        .bind(serde_json::to_value(&ids_history)?)
+        .bind(serde_json::to_value(&max_stakes_history)?)
+        .bind(Value::Object(updated_latest_t))
         .bind(1i64) // Monaco bookie_id
         .bind(fixture_id)
         .bind("Monaco")
@@ -138,7 +316,11 @@ pub async fn ensure_fixture_odds_record(
     Ok(structure.line_index_map)
 }
 
-fn build_fixture_structure(markets: &[MonacoMarket], timestamp: i64) -> FixtureStructure {
+fn build_fixture_structure(
+    markets: &[MonacoMarket],
+    timestamp: i64,
+    market_rules: &super::market_rules::MarketRules,
+) -> FixtureStructure {
     let mut lines_entry = LinesEntry {
         t: timestamp,
         ah: None,
@@ -171,17 +353,17 @@ fn build_fixture_structure(markets: &[MonacoMarket], timestamp: i64) -> FixtureS
     // Categorize markets
     for market in markets {
         let market_type_id = market.market_type.ids.first().unwrap();
-        let market_type = map_market_type(market_type_id);
-        
-        match market_type.as_deref() {
+        let market_type = market_rules.resolve(market_type_id);
+
+        match market_type {
             Some("x12") => x12_markets.push(market.clone()),
             Some("ah") => {
-                if let Some(value) = get_handicap_value(market) {
+                if let Some(value) = market_rules.extract_line(market, market_type_id) {
                     ah_lines.push((value, market.clone()));
                 }
             }
             Some("ou") => {
-                if let Some(value) = get_total_value(market) {
+                if let Some(value) = market_rules.extract_line(market, market_type_id) {
                     ou_lines.push((value, market.clone()));
                 }
             }
@@ -312,23 +494,3 @@ fn build_fixture_structure(markets: &[MonacoMarket], timestamp: i64) -> FixtureS
     }
 }
 
-fn map_market_type(market_type_id: &str) -> Option<String> {
-    match market_type_id {
-        "FOOTBALL_FULL_TIME_RESULT" => Some("x12".to_string()),
-        "FOOTBALL_FULL_TIME_RESULT_HANDICAP" => Some("ah".to_string()),
-        "FOOTBALL_OVER_UNDER_TOTAL_GOALS" => Some("ou".to_string()),
-        _ => None,
-    }
-}
-
-fn get_handicap_value(market: &MonacoMarket) -> Option<f64> {
-    // Match: "Goal Handicap +1.5" or similar
-    let re = regex::Regex::new(r"Goal Handicap ([+-]?[\d.]+)").ok()?;
-    re.captures(&market.name)?.get(1)?.as_str().parse().ok()
-}
-
-fn get_total_value(market: &MonacoMarket) -> Option<f64> {
-    // Match: "Total Goals Over/Under 2.5" or similar
-    let re = regex::Regex::new(r"Total Goals Over/Under ([\d.]+)").ok()?;
-    re.captures(&market.name)?.get(1)?.as_str().parse().ok()
-}
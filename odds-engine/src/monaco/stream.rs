@@ -1,34 +1,147 @@
 use futures::{SinkExt, StreamExt};
 use futures::stream::SplitSink;
+use governor::{Quota, RateLimiter};
+use governor::clock::DefaultClock;
+use governor::state::{InMemoryState, NotKeyed};
 use serde_json::Value;
+use std::collections::BTreeMap;
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tokio::time::{sleep, Duration, interval};
+use tokio::time::{sleep, Duration, interval, Instant};
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message, WebSocketStream, MaybeTlsStream};
 use tokio::net::TcpStream;
-use tracing::{info, error, debug};
+use tracing::{info, error, debug, warn};
 use url::Url;
 
 /// Keepalive ping interval in seconds
 const PING_INTERVAL_SECS: u64 = 60;
 
+/// How long an outstanding ping may go unanswered before the connection is considered
+/// half-open and torn down. Two intervals, so one missed pong alone isn't fatal.
+const PONG_TIMEOUT_SECS: u64 = PING_INTERVAL_SECS * 2;
+
+/// Outbound message quota: every `Message::Text` written to Monaco (auth, subscribe,
+/// re-auth, ping) draws from this shared token bucket rather than relying on a fixed
+/// sleep between messages, so concurrent bursts (reconnect replay plus a concurrent
+/// `add_subscription`, say) can't outrun Monaco's server-side rate limiting.
+const OUTBOUND_MESSAGES_PER_SEC: u32 = 8;
+
+type WsRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+
 use crate::monaco::client::MonacoApiClient;
+use crate::monaco::types::{MarketPriceUpdate, MarketStatusUpdate};
 
 type WsWriter = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
 
+/// One `subscribe` request, either still in flight or already confirmed active.
+#[derive(Debug, Clone)]
+struct PendingRequest {
+    subscription_type: String,
+    subscription_ids: Vec<String>,
+}
+
+/// Tracks every subscription the caller wants active, independent of any single
+/// connection, so `connect_and_listen` can replay the whole set on reconnect instead of
+/// the fixed vector `send_subscriptions` used to hardcode. Requests are assigned a
+/// monotonically increasing id while in flight and promoted to `confirmed` once
+/// acknowledged; Monaco's stream protocol has no dedicated per-request subscribe-ack
+/// message today, so a successful write is treated as the ack.
+#[derive(Default)]
+struct SubscriptionManager {
+    next_id: AtomicU64,
+    pending: Mutex<BTreeMap<u64, PendingRequest>>,
+    confirmed: Mutex<BTreeMap<u64, PendingRequest>>,
+}
+
+impl SubscriptionManager {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    async fn begin_request(&self, subscription_type: &str, subscription_ids: &[String]) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.pending.lock().await.insert(
+            id,
+            PendingRequest {
+                subscription_type: subscription_type.to_string(),
+                subscription_ids: subscription_ids.to_vec(),
+            },
+        );
+        id
+    }
+
+    async fn confirm(&self, id: u64) {
+        let request = self.pending.lock().await.remove(&id);
+        if let Some(request) = request {
+            self.confirmed.lock().await.insert(id, request);
+        }
+    }
+
+    /// Drop every request (pending or confirmed) matching `subscription_type`/`subscription_ids`.
+    async fn remove(&self, subscription_type: &str, subscription_ids: &[String]) {
+        let matches = |r: &PendingRequest| r.subscription_type == subscription_type && r.subscription_ids == subscription_ids;
+        self.pending.lock().await.retain(|_, r| !matches(r));
+        self.confirmed.lock().await.retain(|_, r| !matches(r));
+    }
+
+    async fn is_empty(&self) -> bool {
+        self.pending.lock().await.is_empty() && self.confirmed.lock().await.is_empty()
+    }
+
+    /// Every tracked request, confirmed or still pending from before this connection
+    /// existed, replayed in full on reconnect.
+    async fn all_requests(&self) -> Vec<(u64, PendingRequest)> {
+        let pending = self.pending.lock().await;
+        let confirmed = self.confirmed.lock().await;
+        pending
+            .iter()
+            .chain(confirmed.iter())
+            .map(|(&id, r)| (id, r.clone()))
+            .collect()
+    }
+}
+
 pub struct MonacoWebSocketClient {
     stream_url: String,
     api_client: Arc<Mutex<MonacoApiClient>>,
     tx: tokio::sync::broadcast::Sender<Value>, // Broadcast raw JSON messages to internal handlers
+    /// Demultiplexed, typed fan-out of `MarketPriceUpdate`/`MarketStatusUpdate`, so a
+    /// consumer that only cares about one message kind doesn't have to re-parse
+    /// `data["type"]` itself on every message from the shared raw stream.
+    price_tx: tokio::sync::broadcast::Sender<MarketPriceUpdate>,
+    status_tx: tokio::sync::broadcast::Sender<MarketStatusUpdate>,
+    /// Count of messages that matched a known `type` but failed to deserialize into its
+    /// typed shape; these are dropped rather than tearing down the socket.
+    dropped_messages: AtomicU64,
+    subscriptions: Arc<SubscriptionManager>,
+    /// Writer for whichever connection is currently live, if any; lets
+    /// `add_subscription`/`remove_subscription` send immediately instead of only taking
+    /// effect on the next reconnect.
+    active_writer: Arc<Mutex<Option<Arc<Mutex<WsWriter>>>>>,
+    /// Token bucket shared by every outbound `Message::Text`, regardless of which
+    /// connection it's sent on or which method sent it.
+    rate_limiter: WsRateLimiter,
 }
 
 impl MonacoWebSocketClient {
     pub fn new(stream_url: String, api_client: Arc<Mutex<MonacoApiClient>>) -> Self {
         let (tx, _) = tokio::sync::broadcast::channel(100);
+        let (price_tx, _) = tokio::sync::broadcast::channel(100);
+        let (status_tx, _) = tokio::sync::broadcast::channel(100);
         Self {
             stream_url,
             api_client,
             tx,
+            price_tx,
+            status_tx,
+            dropped_messages: AtomicU64::new(0),
+            subscriptions: Arc::new(SubscriptionManager::new()),
+            active_writer: Arc::new(Mutex::new(None)),
+            rate_limiter: RateLimiter::direct(Quota::per_second(
+                NonZeroU32::new(OUTBOUND_MESSAGES_PER_SEC).unwrap(),
+            )),
         }
     }
 
@@ -36,6 +149,70 @@ impl MonacoWebSocketClient {
         self.tx.subscribe()
     }
 
+    /// Typed stream of `MarketPriceUpdate` notifications only.
+    pub fn subscribe_market_price(&self) -> tokio::sync::broadcast::Receiver<MarketPriceUpdate> {
+        self.price_tx.subscribe()
+    }
+
+    /// Typed stream of `MarketStatusUpdate` notifications only.
+    pub fn subscribe_market_status(&self) -> tokio::sync::broadcast::Receiver<MarketStatusUpdate> {
+        self.status_tx.subscribe()
+    }
+
+    /// Count of messages dropped so far for matching a known `type` but failing to
+    /// deserialize into its typed shape.
+    pub fn dropped_messages(&self) -> u64 {
+        self.dropped_messages.load(Ordering::Relaxed)
+    }
+
+    /// Add a subscription to the tracked set, sending it immediately if a connection is
+    /// currently live. Survives arbitrary reconnect storms: whenever a new connection
+    /// comes up, the whole tracked set (including this one) is replayed automatically.
+    pub async fn add_subscription(&self, subscription_type: &str, subscription_ids: Vec<String>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let id = self.subscriptions.begin_request(subscription_type, &subscription_ids).await;
+        if let Some(writer) = self.active_writer.lock().await.clone() {
+            self.send_subscribe(&writer, subscription_type, &subscription_ids).await?;
+            self.subscriptions.confirm(id).await;
+        }
+        Ok(())
+    }
+
+    /// Remove a subscription from the tracked set, sending an `unsubscribe` immediately
+    /// if a connection is currently live.
+    pub async fn remove_subscription(&self, subscription_type: &str, subscription_ids: Vec<String>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.subscriptions.remove(subscription_type, &subscription_ids).await;
+        if let Some(writer) = self.active_writer.lock().await.clone() {
+            let msg = serde_json::json!({
+                "action": "unsubscribe",
+                "subscriptionType": subscription_type,
+                "subscriptionIds": subscription_ids
+            });
+            self.send_limited(&writer, Message::Text(msg.to_string())).await?;
+            info!("📡 Unsubscribed from {}", subscription_type);
+        }
+        Ok(())
+    }
+
+    async fn send_subscribe(&self, write: &Arc<Mutex<WsWriter>>, subscription_type: &str, subscription_ids: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let sub_msg = serde_json::json!({
+            "action": "subscribe",
+            "subscriptionType": subscription_type,
+            "subscriptionIds": subscription_ids
+        });
+        self.send_limited(write, Message::Text(sub_msg.to_string())).await?;
+        info!("📡 Subscribed to {}", subscription_type);
+        Ok(())
+    }
+
+    /// Send one outbound frame, waiting on the shared token bucket first. Centralizes
+    /// flood protection so every call site (auth, subscribe, re-auth, ping) gets it for
+    /// free instead of coordinating sleeps against each other.
+    async fn send_limited(&self, write: &Arc<Mutex<WsWriter>>, msg: Message) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.rate_limiter.until_ready().await;
+        write.lock().await.send(msg).await?;
+        Ok(())
+    }
+
     pub async fn subscribe_token_refresh(&self) -> tokio::sync::broadcast::Receiver<String> {
         let client = self.api_client.lock().await;
         client.subscribe_token_refresh()
@@ -85,29 +262,24 @@ impl MonacoWebSocketClient {
             "accessToken": access_token
         });
 
-        let mut writer = write.lock().await;
-        writer.send(Message::Text(auth_msg.to_string())).await?;
+        self.send_limited(write, Message::Text(auth_msg.to_string())).await?;
         info!("🔐 Sent authentication message to Monaco");
         Ok(())
     }
 
-    /// Send subscription messages on WebSocket
+    /// Replay the whole tracked subscription set on the given connection, seeding the
+    /// default `MarketPriceUpdate`/`MarketStatusUpdate` subscriptions the first time this
+    /// client ever connects. Pacing between requests is handled by the shared token
+    /// bucket in `send_limited` rather than a fixed sleep here.
     async fn send_subscriptions(&self, write: &Arc<Mutex<WsWriter>>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let subscriptions = vec![
-            ("MarketPriceUpdate", vec!["*"]),
-            ("MarketStatusUpdate", vec!["*"]),
-        ];
-
-        let mut writer = write.lock().await;
-        for (sub_type, ids) in subscriptions {
-            let sub_msg = serde_json::json!({
-                "action": "subscribe",
-                "subscriptionType": sub_type,
-                "subscriptionIds": ids
-            });
-            writer.send(Message::Text(sub_msg.to_string())).await?;
-            info!("📡 Subscribed to {}", sub_type);
-            sleep(Duration::from_millis(100)).await; // Rate limit protection
+        if self.subscriptions.is_empty().await {
+            self.subscriptions.begin_request("MarketPriceUpdate", &["*".to_string()]).await;
+            self.subscriptions.begin_request("MarketStatusUpdate", &["*".to_string()]).await;
+        }
+
+        for (id, request) in self.subscriptions.all_requests().await {
+            self.send_subscribe(write, &request.subscription_type, &request.subscription_ids).await?;
+            self.subscriptions.confirm(id).await;
         }
         Ok(())
     }
@@ -121,9 +293,24 @@ impl MonacoWebSocketClient {
 
         let (write, mut read) = ws_stream.split();
         let write = Arc::new(Mutex::new(write));
+        *self.active_writer.lock().await = Some(write.clone());
+
+        let result = self.drive_connection(&write, &mut read).await;
 
+        // Whatever took this connection down, `add_subscription`/`remove_subscription`
+        // must stop trying to write to it immediately rather than on the next reconnect.
+        *self.active_writer.lock().await = None;
+        result
+    }
+
+    /// Run the authenticate/subscribe/read loop over an already-established connection.
+    async fn drive_connection(
+        &self,
+        write: &Arc<Mutex<WsWriter>>,
+        read: &mut futures::stream::SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Initial authentication
-        self.send_auth(&write).await?;
+        self.send_auth(write).await?;
 
         // Track if we've subscribed (only subscribe once per connection)
         let mut subscribed = false;
@@ -134,6 +321,12 @@ impl MonacoWebSocketClient {
         // Keepalive ping interval
         let mut ping_interval = interval(Duration::from_secs(PING_INTERVAL_SECS));
 
+        // Liveness tracking: when the most recently sent ping went out, and when the last
+        // pong came back, so a silently dead TCP connection (no RST, `read.next()` just
+        // hangs) gets noticed instead of wedging the loop forever.
+        let mut last_ping_at: Option<Instant> = None;
+        let mut last_pong_at: Option<Instant> = None;
+
         loop {
             tokio::select! {
                 // Handle incoming WebSocket messages
@@ -156,11 +349,34 @@ impl MonacoWebSocketClient {
                                 let received_at = chrono::Utc::now().timestamp_millis();
                                 data["_received_at"] = serde_json::json!(received_at);
 
-                                // Log received message type
+                                // Log received message type, and fan out to the typed
+                                // per-kind channels for anyone who doesn't want to re-parse
+                                // `data["type"]` themselves.
                                 if let Some(msg_type) = data["type"].as_str() {
                                     debug!("📨 Received: {} (broadcasting to {} subscribers)", msg_type, self.tx.receiver_count());
+                                    match msg_type {
+                                        "MarketPriceUpdate" => match serde_json::from_value::<MarketPriceUpdate>(data.clone()) {
+                                            Ok(update) => {
+                                                let _ = self.price_tx.send(update);
+                                            }
+                                            Err(e) => {
+                                                self.dropped_messages.fetch_add(1, Ordering::Relaxed);
+                                                warn!("⚠️ Dropping malformed MarketPriceUpdate: {}", e);
+                                            }
+                                        },
+                                        "MarketStatusUpdate" => match serde_json::from_value::<MarketStatusUpdate>(data.clone()) {
+                                            Ok(update) => {
+                                                let _ = self.status_tx.send(update);
+                                            }
+                                            Err(e) => {
+                                                self.dropped_messages.fetch_add(1, Ordering::Relaxed);
+                                                warn!("⚠️ Dropping malformed MarketStatusUpdate: {}", e);
+                                            }
+                                        },
+                                        _ => {}
+                                    }
                                 }
-                                // Forward other messages
+                                // Forward the raw message too, for existing consumers of `subscribe()`.
                                 let _ = self.tx.send(data);
                             }
                         }
@@ -169,12 +385,15 @@ impl MonacoWebSocketClient {
                             let mut writer = write.lock().await;
                             let _ = writer.send(Message::Pong(data)).await;
                         }
+                        Some(Ok(Message::Pong(_))) => {
+                            last_pong_at = Some(Instant::now());
+                        }
                         Some(Ok(Message::Close(_))) => {
                             info!("🔌 Monaco WebSocket closed by server");
                             return Ok(());
                         }
                         Some(Ok(_)) => {
-                            // Ignore other message types (Binary, Pong, etc.)
+                            // Ignore other message types (Binary, etc.)
                         }
                         Some(Err(e)) => {
                             return Err(e.into());
@@ -196,21 +415,34 @@ impl MonacoWebSocketClient {
                         "action": "authenticate",
                         "accessToken": new_token
                     });
-                    
-                    let mut writer = write.lock().await;
-                    if let Err(e) = writer.send(Message::Text(auth_msg.to_string())).await {
+
+                    if let Err(e) = self.send_limited(write, Message::Text(auth_msg.to_string())).await {
                         error!("❌ Failed to send re-authentication: {}", e);
-                        return Err(e.into());
+                        return Err(e);
                     }
                     info!("🔐 Sent re-authentication message (no reconnection needed)");
                 }
 
-                // Send periodic ping to keep connection alive
+                // Send periodic ping to keep connection alive, and check the previous one
+                // was actually answered before sending the next.
                 _ = ping_interval.tick() => {
-                    let mut writer = write.lock().await;
-                    if let Err(e) = writer.send(Message::Ping(vec![])).await {
+                    let mut outstanding = false;
+                    if let Some(sent_at) = last_ping_at {
+                        let answered = last_pong_at.map_or(false, |pong_at| pong_at >= sent_at);
+                        if !answered && sent_at.elapsed() >= Duration::from_secs(PONG_TIMEOUT_SECS) {
+                            error!("❌ No pong received within {}s, treating connection as half-open", PONG_TIMEOUT_SECS);
+                            return Err("Monaco WebSocket pong timeout".into());
+                        }
+                        outstanding = !answered;
+                    }
+
+                    if let Err(e) = self.send_limited(write, Message::Ping(vec![])).await {
                         error!("❌ Failed to send keepalive ping: {}", e);
-                        return Err(e.into());
+                        return Err(e);
+                    }
+                    // Keep timing the oldest unanswered ping, so the timeout can actually elapse.
+                    if !outstanding {
+                        last_ping_at = Some(Instant::now());
                     }
                     debug!("💓 Sent keepalive ping");
                 }
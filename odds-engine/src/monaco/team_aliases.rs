@@ -0,0 +1,76 @@
+// Persistent team-alias resolution backing `fixture_mapping::find_fixture_by_event`.
+//
+// `football_teams` already carries a canonical name plus a JSONB `mappings` array consumed by
+// the Pinnacle-side `shared::fixture_matching::find_matching_fixture`, but that array has no
+// per-bookie provenance and nothing ever writes to it from live traffic. `team_name_aliases`
+// is the Monaco-side equivalent: a flat, append-only table keyed by `(source, alias)` so a
+// once-accepted fuzzy match becomes an exact lookup next time, with enough provenance
+// (`confidence`, `provenance`) for an operator to audit or revoke it. `team_alias_review_queue`
+// collects names neither table resolved, so unmatched fixtures don't just vanish silently.
+use sqlx::PgPool;
+use tracing::info;
+
+/// Canonicalizes `raw_name` (as spelled by `source`, e.g. `"Monaco"`) to a `football_teams.id`
+/// via a previously-recorded alias. `None` means "no alias yet", not "no such team" - callers
+/// should fall back to fuzzy matching rather than treating it as a hard failure.
+pub async fn resolve_alias(pool: &PgPool, raw_name: &str, source: &str) -> Result<Option<i32>, sqlx::Error> {
+    sqlx::query_scalar::<_, i32>(
+        r#"
+        SELECT team_id FROM team_name_aliases
+        WHERE source = $1 AND LOWER(alias) = LOWER($2)
+        LIMIT 1
+        "#,
+    )
+    .bind(source)
+    .bind(raw_name)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Records a spelling learned from an accepted fuzzy match, so future lookups for the same
+/// `(source, raw_name)` resolve exactly instead of re-running trigram similarity. `confidence`
+/// is the similarity score that triggered the match, kept for operator review.
+pub async fn record_alias(
+    pool: &PgPool,
+    team_id: i32,
+    raw_name: &str,
+    source: &str,
+    confidence: f64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO team_name_aliases (team_id, alias, source, confidence, provenance, created_at)
+        VALUES ($1, $2, $3, $4, 'auto_fuzzy_match', NOW())
+        ON CONFLICT (source, alias) DO NOTHING
+        "#,
+    )
+    .bind(team_id)
+    .bind(raw_name)
+    .bind(source)
+    .bind(confidence)
+    .execute(pool)
+    .await?;
+
+    info!(
+        "📝 Learned team alias \"{}\" ({}) -> team_id={} (confidence={:.2})",
+        raw_name, source, team_id, confidence
+    );
+    Ok(())
+}
+
+/// Logs a team name that resolved via neither an alias nor the fuzzy-match fallback, so
+/// operators have a queue to review and either approve a manual alias or add the team.
+pub async fn log_unresolved(pool: &PgPool, raw_name: &str, source: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO team_alias_review_queue (raw_name, source, seen_at)
+        VALUES ($1, $2, NOW())
+        "#,
+    )
+    .bind(raw_name)
+    .bind(source)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -44,6 +44,19 @@ pub struct MonacoMarket {
     pub modified_at: Option<String>,
 }
 
+impl MonacoMarket {
+    /// Ordering key derived from `modifiedAt`, used so a late-arriving, stale fetch can't
+    /// clobber a newer price when (re)initializing the order book or replaying a backfill.
+    /// Markets with no (or unparseable) `modifiedAt` sort first, treated as oldest.
+    pub fn sequence(&self) -> i64 {
+        self.modified_at
+            .as_deref()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.timestamp_millis())
+            .unwrap_or(0)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketReference {
     #[serde(rename = "_ids")]
@@ -68,6 +81,41 @@ pub struct MonacoPrice {
     pub liquidity: f64,
 }
 
+/// One `MarketPriceUpdate` notification off the Monaco stream. Distinct from `MonacoPrice`
+/// above (that one nests under a fetched `MonacoMarket`, this one arrives standalone and
+/// additionally carries `validAt`), so it isn't reused despite the similar shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketPriceUpdate {
+    #[serde(rename = "marketId")]
+    pub market_id: String,
+    #[serde(rename = "eventId")]
+    pub event_id: String,
+    pub prices: Vec<MarketPriceEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketPriceEntry {
+    pub side: String,
+    #[serde(rename = "outcomeId")]
+    pub outcome_id: String,
+    pub price: f64,
+    pub liquidity: f64,
+    #[serde(rename = "validAt")]
+    pub valid_at: Option<String>,
+}
+
+/// One `MarketStatusUpdate` notification off the Monaco stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketStatusUpdate {
+    #[serde(rename = "marketId")]
+    pub market_id: String,
+    #[serde(rename = "eventId")]
+    pub event_id: String,
+    pub status: Option<String>,
+    #[serde(rename = "inPlayStatus")]
+    pub in_play_status: Option<String>,
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketMapping {
@@ -2,6 +2,7 @@ pub mod types;
 pub mod client;
 pub mod db;
 
+use crate::fair_odds::FairProbMethod;
 use crate::pinnacle::client::PinnacleApiClient;
 use crate::pinnacle::db::PinnacleDbService;
 use crate::pinnacle::types::PinnaclePeriod;
@@ -18,10 +19,10 @@ pub struct PinnacleService {
 }
 
 impl PinnacleService {
-    pub fn new(pool: PgPool, processor_client: Option<Arc<ProcessorClient>>) -> Self {
+    pub fn new(pool: PgPool, processor_client: Option<Arc<ProcessorClient>>, fair_prob_method: FairProbMethod) -> Self {
         Self {
             client: PinnacleApiClient::new(),
-            db: PinnacleDbService::new(pool),
+            db: PinnacleDbService::new(pool, fair_prob_method),
             processor_client,
         }
     }
@@ -1,10 +1,30 @@
 use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::io::AsyncWriteExt;
 use tokio::net::TcpStream;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
+use tokio::time::{sleep, Duration};
 use tracing::{info, warn};
 
+/// First byte sent on connect to ask odds-processor for length-prefixed MessagePack
+/// framing instead of the legacy newline-delimited JSON protocol. Matches the
+/// `MESSAGEPACK_HANDSHAKE` byte odds-processor's TCP listener sniffs for.
+const MESSAGEPACK_HANDSHAKE: u8 = 0x02;
+
+/// How `OddsUpdate`s are serialized on the wire to odds-processor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireFormat {
+    /// Legacy newline-delimited JSON. Kept as the default for backward compatibility with
+    /// older odds-processor deployments that don't sniff a handshake byte.
+    NewlineJson,
+    /// Length-prefixed MessagePack: roughly half the bytes-on-wire for the numeric-heavy
+    /// `x12`/`ah_*`/`ou_*` arrays, and cheaper to parse on the processor side. Negotiated
+    /// with a one-time handshake byte right after connecting.
+    MessagePack,
+}
+
 /// Update to send to odds-processor
 /// Matches the same data format stored in football_odds table
 #[derive(Debug, Clone, Serialize)]
@@ -73,83 +93,152 @@ impl Default for OddsUpdate {
     }
 }
 
-/// Client for sending updates to odds-processor
+/// Client for sending updates to odds-processor.
+///
+/// `send` only enqueues onto a bounded in-memory ring buffer; a background writer task
+/// (started by `create_processor_client`) owns the actual `TcpStream` and drains the queue
+/// in order, reconnecting with the same exponential-backoff strategy as
+/// `MonacoWebSocketClient::start` whenever the link drops. This makes the processor link an
+/// at-least-once, best-effort channel: a burst of updates survives odds-processor briefly
+/// restarting instead of being dropped on the first write error. Overflow still drops the
+/// oldest entries, so the buffer trades unbounded memory growth for outright loss only when
+/// the backlog has grown far beyond what odds-processor could plausibly catch up on.
 pub struct ProcessorClient {
     addr: String,
-    stream: Mutex<Option<TcpStream>>,
+    capacity: usize,
+    format: WireFormat,
+    queue: Mutex<VecDeque<OddsUpdate>>,
+    notify: Notify,
+    dropped: AtomicU64,
 }
 
 impl ProcessorClient {
-    pub fn new(addr: &str) -> Self {
+    pub fn new(addr: &str, capacity: usize, format: WireFormat) -> Self {
         Self {
             addr: addr.to_string(),
-            stream: Mutex::new(None),
+            capacity,
+            format,
+            queue: Mutex::new(VecDeque::with_capacity(capacity.min(1024))),
+            notify: Notify::new(),
+            dropped: AtomicU64::new(0),
         }
     }
 
-    /// Connect to odds-processor
-    async fn connect(&self) -> Result<(), std::io::Error> {
-        let mut stream_guard = self.stream.lock().await;
-        
-        if stream_guard.is_some() {
-            return Ok(());
+    /// Number of updates dropped so far for overflowing the queue.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Enqueue an update to be sent to odds-processor. Never fails: if the background
+    /// writer is behind (or the queue is full), the update is buffered (or the oldest
+    /// buffered entry is dropped to make room) rather than blocking or erroring the caller.
+    pub async fn send(&self, update: &OddsUpdate) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut queue = self.queue.lock().await;
+        if queue.len() >= self.capacity {
+            queue.pop_front();
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            warn!("⚠️ Processor queue full (capacity={}), dropped oldest update (total dropped: {})", self.capacity, dropped);
         }
+        queue.push_back(update.clone());
+        drop(queue);
+        self.notify.notify_one();
+        Ok(())
+    }
 
-        info!("🔌 Connecting to odds-processor at {}...", self.addr);
-        match TcpStream::connect(&self.addr).await {
-            Ok(stream) => {
-                info!("✅ Connected to odds-processor at {}", self.addr);
-                *stream_guard = Some(stream);
-                Ok(())
-            }
-            Err(e) => {
-                warn!("❌ Failed to connect to odds-processor at {}: {}", self.addr, e);
-                Err(e)
+    /// Wait for and pop the next queued update, in FIFO order.
+    async fn dequeue(&self) -> OddsUpdate {
+        loop {
+            {
+                let mut queue = self.queue.lock().await;
+                if let Some(update) = queue.pop_front() {
+                    return update;
+                }
             }
+            self.notify.notified().await;
         }
     }
 
-    /// Send an update to odds-processor
-    pub async fn send(&self, update: &OddsUpdate) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Try to connect if not connected
-        if let Err(e) = self.connect().await {
-            // We return the error here so the caller knows the update wasn't sent.
-            // In the main loop, we can decide whether to log this as error or warn.
-            return Err(Box::new(e));
+    /// Push an update back onto the front of the queue, for a write that failed partway
+    /// through and needs to be retried once reconnected.
+    async fn requeue_front(&self, update: OddsUpdate) {
+        self.queue.lock().await.push_front(update);
+    }
+
+    /// Connect once, then drain the queue in order until the connection fails.
+    async fn connect_and_drain(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        info!("🔌 Connecting to odds-processor at {}...", self.addr);
+        let mut stream = TcpStream::connect(&self.addr).await?;
+        info!("✅ Connected to odds-processor at {}", self.addr);
+
+        if self.format == WireFormat::MessagePack {
+            stream.write_u8(MESSAGEPACK_HANDSHAKE).await?;
+        }
+
+        loop {
+            let update = self.dequeue().await;
+
+            let write_result = match self.format {
+                WireFormat::NewlineJson => {
+                    let json = serde_json::to_string(&update)?;
+                    let line = format!("{}\n", json);
+                    stream.write_all(line.as_bytes()).await
+                }
+                WireFormat::MessagePack => {
+                    let bytes = rmp_serde::to_vec(&update)?;
+                    match stream.write_u32(bytes.len() as u32).await {
+                        Ok(_) => stream.write_all(&bytes).await,
+                        Err(e) => Err(e),
+                    }
+                }
+            };
+
+            if let Err(e) = write_result {
+                warn!("⚠️ Failed to send to odds-processor: {}. Dropping connection.", e);
+                self.requeue_front(update).await;
+                return Err(e.into());
+            }
         }
+    }
+
+    /// Run the reconnect-with-backoff loop forever, draining the queue whenever connected.
+    /// Mirrors `MonacoWebSocketClient::start`'s exponential backoff (capped at 60s).
+    async fn run(self: Arc<Self>) {
+        let mut retry_count: u32 = 0;
+        let max_backoff_secs = 60;
+
+        loop {
+            let backoff_secs = if retry_count == 0 {
+                0
+            } else {
+                std::cmp::min(2u64.pow(retry_count.saturating_sub(1)), max_backoff_secs)
+            };
+
+            if backoff_secs > 0 {
+                info!("⏳ Waiting {}s before reconnecting to odds-processor (attempt {})...", backoff_secs, retry_count + 1);
+                sleep(Duration::from_secs(backoff_secs)).await;
+            }
 
-        let mut stream_guard = self.stream.lock().await;
-        
-        if let Some(ref mut stream) = *stream_guard {
-            let json = serde_json::to_string(update)?;
-            let line = format!("{}\n", json);
-            
-            match stream.write_all(line.as_bytes()).await {
+            match self.connect_and_drain().await {
                 Ok(_) => {
-                    // debug!("📤 Sent update to odds-processor: fixture_id={}", update.fixture_id);
-                    Ok(())
+                    retry_count = 0;
                 }
                 Err(e) => {
-                    warn!("⚠️ Failed to send to odds-processor: {}. Dropping connection.", e);
-                    *stream_guard = None;
-                    Err(Box::new(e))
+                    retry_count = retry_count.saturating_add(1);
+                    warn!("❌ odds-processor connection error (attempt {}): {}", retry_count, e);
                 }
             }
-        } else {
-            // This should theoretically not happen if connect() succeeded, 
-            // but if the lock was released and re-acquired (not possible here as we hold it),
-            // or if logic changes.
-            Err("No connection available".into())
         }
     }
 }
 
-/// Create a shared processor client
-pub fn create_processor_client(enabled: bool, port: u16) -> Option<Arc<ProcessorClient>> {
+/// Create a shared processor client and spawn its background writer task.
+pub fn create_processor_client(enabled: bool, port: u16, queue_capacity: usize, format: WireFormat) -> Option<Arc<ProcessorClient>> {
     if enabled {
         let addr = format!("127.0.0.1:{}", port);
-        info!("🔗 Processor client will connect to {}", addr);
-        Some(Arc::new(ProcessorClient::new(&addr)))
+        info!("🔗 Processor client will connect to {} (queue capacity: {}, format: {:?})", addr, queue_capacity, format);
+        let client = Arc::new(ProcessorClient::new(&addr, queue_capacity, format));
+        tokio::spawn(client.clone().run());
+        Some(client)
     } else {
         info!("📡 Processor client disabled");
         None
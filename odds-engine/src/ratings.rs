@@ -0,0 +1,232 @@
+use chrono::{NaiveDateTime, Utc};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// A single observed 1X2 result, expressed as Pinnacle's no-vig probabilities rather than
+/// an actual final score — we're fitting to the market's opinion, not match outcomes.
+struct Observation {
+    home_team: i32,
+    away_team: i32,
+    date: NaiveDateTime,
+    p_home: f64,
+    p_away: f64,
+}
+
+/// Bradley-Terry latent team-strength ratings for one league, fit from accumulated
+/// no-vig Pinnacle probabilities. `P(home beats away) = 1 / (1 + exp(-(r_home - r_away + h)))`.
+#[derive(Debug, Clone, Default)]
+pub struct TeamRatings {
+    pub league_id: i32,
+    pub ratings: HashMap<i32, f64>,
+    pub home_advantage: f64,
+}
+
+const FIT_ITERATIONS: u32 = 200;
+const LEARNING_RATE: f64 = 0.05;
+
+impl TeamRatings {
+    /// Fit ratings for `league_id` from every fixture in `football_fixtures` that has a
+    /// Pinnacle fair-probability 1X2 quote, decaying older observations by
+    /// `exp(-Δt / half_life_days)`.
+    pub async fn fit(pool: &PgPool, league_id: i32, half_life_days: f64) -> Result<Self, sqlx::Error> {
+        let observations = Self::load_observations(pool, league_id).await?;
+
+        let mut ratings: HashMap<i32, f64> = HashMap::new();
+        for obs in &observations {
+            ratings.entry(obs.home_team).or_insert(0.0);
+            ratings.entry(obs.away_team).or_insert(0.0);
+        }
+
+        if ratings.is_empty() {
+            return Ok(Self { league_id, ratings, home_advantage: 0.0 });
+        }
+
+        let now = Utc::now().naive_utc();
+        let weights: Vec<f64> = observations
+            .iter()
+            .map(|obs| {
+                let age_days = (now - obs.date).num_seconds() as f64 / 86_400.0;
+                (-age_days.max(0.0) / half_life_days).exp()
+            })
+            .collect();
+
+        let mut home_advantage = 0.0_f64;
+
+        // MM-style iterative least-squares: nudge each team's rating and the shared
+        // home-advantage term to reduce the weighted log-odds residual against the
+        // observed market probability, re-deriving `p_home`/`(1-p_home)` as a two-way
+        // split (draws are excluded from the fit and folded back in at `predict` time).
+        for _ in 0..FIT_ITERATIONS {
+            let mut rating_grad: HashMap<i32, f64> = HashMap::new();
+            let mut h_grad = 0.0_f64;
+
+            for (obs, &w) in observations.iter().zip(weights.iter()) {
+                let two_way = obs.p_home + obs.p_away;
+                if two_way <= 0.0 {
+                    continue;
+                }
+                let observed = obs.p_home / two_way;
+
+                let r_home = ratings[&obs.home_team];
+                let r_away = ratings[&obs.away_team];
+                let logit = r_home - r_away + home_advantage;
+                let predicted = 1.0 / (1.0 + (-logit).exp());
+
+                let residual = w * (observed - predicted);
+                *rating_grad.entry(obs.home_team).or_insert(0.0) += residual;
+                *rating_grad.entry(obs.away_team).or_insert(0.0) -= residual;
+                h_grad += residual;
+            }
+
+            for (team, grad) in rating_grad {
+                *ratings.get_mut(&team).unwrap() += LEARNING_RATE * grad;
+            }
+            home_advantage += LEARNING_RATE * h_grad / observations.len().max(1) as f64;
+        }
+
+        // Anchor the scale: shift all ratings so the mean is zero (Bradley-Terry is only
+        // identified up to an additive constant).
+        let mean: f64 = ratings.values().sum::<f64>() / ratings.len() as f64;
+        for r in ratings.values_mut() {
+            *r -= mean;
+        }
+
+        info!(
+            "Fit team ratings for league {}: {} teams, home_advantage={:.3}",
+            league_id,
+            ratings.len(),
+            home_advantage
+        );
+
+        Ok(Self { league_id, ratings, home_advantage })
+    }
+
+    async fn load_observations(pool: &PgPool, league_id: i32) -> Result<Vec<Observation>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT f.home_team_id, f.away_team_id, f.date, o.fair_probs
+            FROM football_fixtures f
+            JOIN football_odds o ON o.fixture_id = f.id
+            WHERE f.league_id = $1
+              AND o.bookie = 'Pinnacle'
+              AND o.fair_probs IS NOT NULL
+              AND f.home_team_id IS NOT NULL
+              AND f.away_team_id IS NOT NULL
+            "#,
+        )
+        .bind(league_id as i64)
+        .fetch_all(pool)
+        .await?;
+
+        let mut observations = Vec::new();
+        for row in rows {
+            let home_team: i32 = row.get("home_team_id");
+            let away_team: i32 = row.get("away_team_id");
+            let date: NaiveDateTime = row.get("date");
+            let fair_probs: serde_json::Value = row.get("fair_probs");
+
+            let Some(latest) = fair_probs.as_array().and_then(|a| a.last()) else {
+                continue;
+            };
+            let Some(fair) = latest.get("fair").and_then(|v| v.as_array()) else {
+                continue;
+            };
+            if fair.len() != 3 {
+                continue;
+            }
+            let (Some(p_home), Some(p_away)) = (fair[0].as_f64(), fair[2].as_f64()) else {
+                continue;
+            };
+
+            observations.push(Observation { home_team, away_team, date, p_home, p_away });
+        }
+
+        Ok(observations)
+    }
+
+    /// Estimate a fair 1X2 distribution for a fixture that hasn't been quoted. The
+    /// Bradley-Terry term gives a two-way (home win vs. away win) split; the draw
+    /// probability is folded back in via the standard sqrt-product trick
+    /// (`P(draw) ∝ sqrt(P(home)·P(away))`), which keeps draws proportionally more likely
+    /// the closer the two sides are rated.
+    pub fn predict(&self, home_team: i32, away_team: i32) -> Option<[f64; 3]> {
+        let r_home = *self.ratings.get(&home_team)?;
+        let r_away = *self.ratings.get(&away_team)?;
+
+        let logit = r_home - r_away + self.home_advantage;
+        let p_home_two_way = 1.0 / (1.0 + (-logit).exp());
+        let p_away_two_way = 1.0 - p_home_two_way;
+
+        let draw_weight = (p_home_two_way * p_away_two_way).sqrt();
+        let total = p_home_two_way + p_away_two_way + draw_weight;
+
+        Some([p_home_two_way / total, draw_weight / total, p_away_two_way / total])
+    }
+}
+
+/// Periodically refits ratings for every league with fixtures on record. Runs as its own
+/// background task; predictions are served by re-fetching the latest `team_ratings` rows
+/// rather than keeping the fit in memory, so every consumer sees the same snapshot.
+pub async fn run(pool: PgPool, refresh_interval: Duration, half_life_days: f64) {
+    info!("Starting Team Ratings Service (half-life: {} days)", half_life_days);
+    let mut interval = tokio::time::interval(refresh_interval);
+
+    loop {
+        interval.tick().await;
+
+        let league_ids = match load_known_league_ids(&pool).await {
+            Ok(ids) => ids,
+            Err(e) => {
+                error!("Failed to load league ids for ratings refresh: {}", e);
+                continue;
+            }
+        };
+
+        for league_id in league_ids {
+            match TeamRatings::fit(&pool, league_id, half_life_days).await {
+                Ok(ratings) if !ratings.ratings.is_empty() => {
+                    if let Err(e) = persist_ratings(&pool, &ratings).await {
+                        error!("Failed to persist ratings for league {}: {}", league_id, e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => error!("Failed to fit ratings for league {}: {}", league_id, e),
+            }
+        }
+    }
+}
+
+async fn load_known_league_ids(pool: &PgPool) -> Result<Vec<i32>, sqlx::Error> {
+    let rows = sqlx::query("SELECT DISTINCT league_id FROM football_fixtures WHERE league_id IS NOT NULL")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.iter().map(|r| r.get("league_id")).collect())
+}
+
+/// Persist a fitted `TeamRatings` snapshot to `team_ratings`, one row per team.
+pub async fn persist_ratings(pool: &PgPool, ratings: &TeamRatings) -> Result<(), sqlx::Error> {
+    let as_of = Utc::now();
+
+    for (&team_id, &rating) in &ratings.ratings {
+        sqlx::query(
+            r#"
+            INSERT INTO team_ratings (league_id, team_id, rating, home_advantage, as_of)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (league_id, team_id, as_of) DO UPDATE SET
+                rating = EXCLUDED.rating,
+                home_advantage = EXCLUDED.home_advantage
+            "#,
+        )
+        .bind(ratings.league_id)
+        .bind(team_id)
+        .bind(rating)
+        .bind(ratings.home_advantage)
+        .bind(as_of)
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
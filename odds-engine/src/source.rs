@@ -0,0 +1,112 @@
+// Generalizes ingestion beyond Monaco: an `OddsSource` is anything that can stream raw
+// exchange messages and pick the price updates out of them, so `start_ingestion_engine`
+// and `build_odds_update` don't need to hardcode which exchange they're talking to.
+// Mirrors how `odds-processor`'s `Fanout` trait lets the broadcast backend vary
+// independently of the code that publishes to it.
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::monaco::stream::MonacoWebSocketClient;
+
+/// One outcome's best price and available liquidity, normalized across exchanges.
+pub type NormalizedOutcome = (String, f64, f64);
+
+/// A price update from any odds source, normalized to the shape
+/// `handle_price_update` understands regardless of which exchange it came from.
+#[derive(Debug, Clone)]
+pub struct NormalizedUpdate {
+    pub market_id: String,
+    pub event_id: String,
+    pub outcomes: Vec<NormalizedOutcome>,
+    pub valid_at: Option<String>,
+}
+
+/// An exchange/bookmaker feed the engine can ingest. Monaco is the first
+/// implementation; adding a new book means implementing this trait rather than
+/// touching `handle_price_update`/`handle_market_status_update`.
+#[async_trait]
+pub trait OddsSource: Send + Sync {
+    /// odds-processor's `bookie_id` for updates from this source.
+    fn bookie_id(&self) -> i64;
+    /// Decimal places of this source's integer-encoded odds (`OddsUpdate::decimals`);
+    /// passed to `transform_price`, which does not change its encoding by it.
+    fn decimals(&self) -> i32;
+    /// odds-processor's `bookmaker` label for this source's updates.
+    fn bookmaker(&self) -> &str;
+
+    /// Start the underlying connection (if not already running) and return a
+    /// receiver of this source's raw messages.
+    async fn stream(&self) -> broadcast::Receiver<Value>;
+
+    /// Parse one raw message into a normalized price update, or `None` if it's not
+    /// a price update this source cares about (e.g. an auth ack or a status event,
+    /// which are still dispatched by raw `type` for now).
+    fn parse(&self, raw: &Value) -> Option<NormalizedUpdate>;
+}
+
+/// `OddsSource` adapter over the existing Monaco WebSocket client.
+pub struct MonacoSource {
+    client: Arc<MonacoWebSocketClient>,
+}
+
+impl MonacoSource {
+    pub fn new(client: Arc<MonacoWebSocketClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl OddsSource for MonacoSource {
+    fn bookie_id(&self) -> i64 {
+        1
+    }
+
+    fn decimals(&self) -> i32 {
+        3
+    }
+
+    fn bookmaker(&self) -> &str {
+        "Monaco"
+    }
+
+    async fn stream(&self) -> broadcast::Receiver<Value> {
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            client.start().await;
+        });
+        self.client.subscribe()
+    }
+
+    fn parse(&self, raw: &Value) -> Option<NormalizedUpdate> {
+        if raw["type"].as_str() != Some("MarketPriceUpdate") {
+            return None;
+        }
+
+        let market_id = raw["marketId"].as_str()?.to_string();
+        let event_id = raw["eventId"].as_str()?.to_string();
+        let prices = raw["prices"].as_array()?;
+        if prices.is_empty() {
+            return None;
+        }
+
+        let outcomes = prices
+            .iter()
+            .filter_map(|p| {
+                let outcome_id = p["outcomeId"].as_str()?.to_string();
+                let price = p["price"].as_f64()?;
+                let liquidity = p["liquidity"].as_f64().unwrap_or(0.0);
+                Some((outcome_id, price, liquidity))
+            })
+            .collect();
+        let valid_at = prices[0]["validAt"].as_str().map(|s| s.to_string());
+
+        Some(NormalizedUpdate {
+            market_id,
+            event_id,
+            outcomes,
+            valid_at,
+        })
+    }
+}
@@ -0,0 +1,264 @@
+// Cross-bookmaker arbitrage and value-bet detection over the live in-memory `Cache`.
+// Unlike odds-engine's periodic `ArbitrageService` (which sweeps `football_odds` on an
+// interval), this runs inline after every `Cache::apply_update`, scanning only whichever
+// fixture just changed rather than the whole table.
+use crate::calculations::fair_odds::{calculate_fair_odds, FairOddsMethod};
+use crate::types::{BookmakerOdds, FixtureData};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One bookmaker's price for one outcome, gathered while hunting for the best available
+/// offer per outcome. Mirrors the external `MarkedOffer(bookmaker_index, outcome, odds)`
+/// triple, using the bookmaker's name instead of an index since the cache is keyed that way.
+#[derive(Debug, Clone)]
+struct MarkedOffer {
+    bookmaker: String,
+    outcome: &'static str,
+    decimal_odds: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpportunityKind {
+    /// Sum of inverse odds across the best-priced legs is below 1.0: a risk-free profit
+    /// regardless of which outcome occurs.
+    Arbitrage,
+    /// A single book's price exceeds the fair-odds consensus by at least the configured edge.
+    ValueBet,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbLeg {
+    pub bookmaker: String,
+    pub outcome: String,
+    pub decimal_odds: f64,
+    /// Fraction of total stake to place on this leg. `1.0` for a value bet (single leg).
+    pub stake_fraction: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbOpportunity {
+    pub kind: OpportunityKind,
+    /// e.g. "x12", "ah@-0.5", "ou@2.5"
+    pub market: String,
+    pub legs: Vec<ArbLeg>,
+    /// Guaranteed return (arb) or edge over consensus (value bet), as a fraction of stake.
+    pub expected_return: f64,
+}
+
+pub struct ArbConfig {
+    pub value_bet_edge: f64,
+    pub min_scan_delay_secs: i64,
+    pub max_scan_delay_secs: i64,
+    /// De-margining method used to derive the fair-odds consensus value bets are scored
+    /// against; mirrors whatever `Cache` was built with so the two stay consistent.
+    pub fair_odds_method: FairOddsMethod,
+}
+
+/// Scans fixtures for arbs/value bets, deduping so a steady opportunity isn't re-broadcast
+/// on every touch of the fixture.
+#[derive(Default)]
+pub struct ArbScanner {
+    // (fixture_id, market) -> (fingerprint of the last emitted opportunity, emitted_at ms)
+    last_emitted: HashMap<(i64, String), (u64, i64)>,
+}
+
+impl ArbScanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scan one fixture's bookmakers for arbs/value bets, returning only opportunities
+    /// that are new, changed, or due for a keepalive re-emit per `config`'s scan delays.
+    pub fn scan_fixture(&mut self, fixture: &FixtureData, config: &ArbConfig, now_ms: i64) -> Vec<ArbOpportunity> {
+        let mut found = Vec::new();
+
+        if let Some(offers) = best_x12_offers(fixture) {
+            found.extend(evaluate_market("x12", offers, config));
+        }
+        for (line, offers) in best_two_way_offers(fixture, "home", "away", |o| (&o.ah_lines, &o.ah_h, &o.ah_a)) {
+            found.extend(evaluate_market(&format!("ah@{}", line), offers, config));
+        }
+        for (line, offers) in best_two_way_offers(fixture, "over", "under", |o| (&o.ou_lines, &o.ou_o, &o.ou_u)) {
+            found.extend(evaluate_market(&format!("ou@{}", line), offers, config));
+        }
+
+        found
+            .into_iter()
+            .filter(|opp| self.should_emit(fixture.fixture_id, opp, config, now_ms))
+            .collect()
+    }
+
+    fn should_emit(&mut self, fixture_id: i64, opp: &ArbOpportunity, config: &ArbConfig, now_ms: i64) -> bool {
+        let key = (fixture_id, opp.market.clone());
+        let fingerprint = fingerprint(opp);
+
+        let emit = match self.last_emitted.get(&key) {
+            None => true,
+            Some(&(last_fingerprint, last_at)) => {
+                let elapsed_secs = (now_ms - last_at) / 1000;
+                if elapsed_secs < config.min_scan_delay_secs {
+                    false
+                } else {
+                    last_fingerprint != fingerprint || elapsed_secs >= config.max_scan_delay_secs
+                }
+            }
+        };
+
+        if emit {
+            self.last_emitted.insert(key, (fingerprint, now_ms));
+        }
+        emit
+    }
+}
+
+fn fingerprint(opp: &ArbOpportunity) -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    opp.market.hash(&mut hasher);
+    matches!(opp.kind, OpportunityKind::ValueBet).hash(&mut hasher);
+    for leg in &opp.legs {
+        leg.bookmaker.hash(&mut hasher);
+        leg.outcome.hash(&mut hasher);
+        leg.decimal_odds.to_bits().hash(&mut hasher);
+        leg.stake_fraction.to_bits().hash(&mut hasher);
+    }
+    opp.expected_return.to_bits().hash(&mut hasher);
+    hasher.finish()
+}
+
+fn consider(best: &mut HashMap<&'static str, MarkedOffer>, bookmaker: &str, outcome: &'static str, raw: Option<i32>, scale: f64) {
+    let Some(raw) = raw else { return };
+    if raw <= 0 {
+        return;
+    }
+    let decimal_odds = raw as f64 / scale;
+    let better = best.get(outcome).map(|o| decimal_odds > o.decimal_odds).unwrap_or(true);
+    if better {
+        best.insert(
+            outcome,
+            MarkedOffer {
+                bookmaker: bookmaker.to_string(),
+                outcome,
+                decimal_odds,
+            },
+        );
+    }
+}
+
+fn best_x12_offers(fixture: &FixtureData) -> Option<Vec<MarkedOffer>> {
+    let mut best: HashMap<&'static str, MarkedOffer> = HashMap::new();
+    for (name, odds) in &fixture.bookmakers {
+        let scale = 10f64.powi(odds.decimals);
+        consider(&mut best, name, "home", odds.x12_h, scale);
+        consider(&mut best, name, "draw", odds.x12_x, scale);
+        consider(&mut best, name, "away", odds.x12_a, scale);
+    }
+    if best.len() < 3 {
+        return None;
+    }
+    Some(["home", "draw", "away"].iter().map(|k| best.remove(k).unwrap()).collect())
+}
+
+/// Groups each bookmaker's per-line two-way prices (AH home/away, OU over/under) by line,
+/// then keeps only the best offer per outcome within each line.
+fn best_two_way_offers<'a>(
+    fixture: &'a FixtureData,
+    side_a: &'static str,
+    side_b: &'static str,
+    get: impl Fn(&'a BookmakerOdds) -> (&'a Vec<f64>, &'a Vec<i32>, &'a Vec<i32>),
+) -> Vec<(String, Vec<MarkedOffer>)> {
+    let mut best: HashMap<String, HashMap<&'static str, MarkedOffer>> = HashMap::new();
+    for (name, odds) in &fixture.bookmakers {
+        let (lines, a_side, b_side) = get(odds);
+        let scale = 10f64.powi(odds.decimals);
+        for (i, line) in lines.iter().enumerate() {
+            let entry = best.entry(format!("{}", line)).or_default();
+            consider(entry, name, side_a, a_side.get(i).copied(), scale);
+            consider(entry, name, side_b, b_side.get(i).copied(), scale);
+        }
+    }
+    best.into_iter()
+        .filter_map(|(line, mut outcomes)| {
+            if outcomes.len() < 2 {
+                return None;
+            }
+            let offers = [side_a, side_b].iter().map(|k| outcomes.remove(k).unwrap()).collect();
+            Some((line, offers))
+        })
+        .collect()
+}
+
+/// Apply `calculate_fair_odds` to the best price per outcome, treating them as one
+/// synthetic book's prices, to get the margin-removed consensus fair odds per outcome.
+fn fair_consensus(offers: &[MarkedOffer], method: FairOddsMethod) -> Option<Vec<f64>> {
+    const DECIMALS: i32 = 4;
+    let scale = 10f64.powi(DECIMALS);
+    let basis: Vec<i32> = offers.iter().map(|o| (o.decimal_odds * scale).round() as i32).collect();
+    let fair = calculate_fair_odds(&basis, DECIMALS, offers.len(), method)?;
+    Some(fair.iter().map(|&f| f as f64 / scale).collect())
+}
+
+fn arbitrage_opportunity(market: &str, offers: &[MarkedOffer]) -> Option<ArbOpportunity> {
+    let inv_sum: f64 = offers.iter().map(|o| 1.0 / o.decimal_odds).sum();
+    if inv_sum >= 1.0 {
+        return None;
+    }
+    let legs = offers
+        .iter()
+        .map(|o| ArbLeg {
+            bookmaker: o.bookmaker.clone(),
+            outcome: o.outcome.to_string(),
+            decimal_odds: o.decimal_odds,
+            stake_fraction: (1.0 / o.decimal_odds) / inv_sum,
+        })
+        .collect();
+    Some(ArbOpportunity {
+        kind: OpportunityKind::Arbitrage,
+        market: market.to_string(),
+        legs,
+        expected_return: 1.0 / inv_sum - 1.0,
+    })
+}
+
+fn value_bet_opportunities(market: &str, offers: &[MarkedOffer], config: &ArbConfig) -> Vec<ArbOpportunity> {
+    let Some(fair) = fair_consensus(offers, config.fair_odds_method) else {
+        return Vec::new();
+    };
+    offers
+        .iter()
+        .zip(fair.iter())
+        .filter_map(|(offer, &fair_odds)| {
+            if fair_odds <= 0.0 {
+                return None;
+            }
+            let edge = offer.decimal_odds / fair_odds - 1.0;
+            if edge < config.value_bet_edge {
+                return None;
+            }
+            Some(ArbOpportunity {
+                kind: OpportunityKind::ValueBet,
+                market: market.to_string(),
+                legs: vec![ArbLeg {
+                    bookmaker: offer.bookmaker.clone(),
+                    outcome: offer.outcome.to_string(),
+                    decimal_odds: offer.decimal_odds,
+                    stake_fraction: 1.0,
+                }],
+                expected_return: edge,
+            })
+        })
+        .collect()
+}
+
+/// A pure arb already guarantees the best possible per-leg edge, so value bets are only
+/// reported when no arb exists for the market.
+fn evaluate_market(market: &str, offers: Vec<MarkedOffer>, config: &ArbConfig) -> Vec<ArbOpportunity> {
+    if let Some(arb) = arbitrage_opportunity(market, &offers) {
+        vec![arb]
+    } else {
+        value_bet_opportunities(market, &offers, config)
+    }
+}
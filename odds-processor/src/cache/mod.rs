@@ -1,27 +1,83 @@
 use crate::types::{BookmakerOdds, FixtureData, OddsUpdate};
-use crate::calculations::fair_odds::calculate_fair_odds;
+use crate::calculations::fair_odds::{calculate_fair_odds, FairOddsMethod};
+use serde_json::Value;
 use std::collections::{HashMap, BTreeMap};
 use tracing::info;
 
+/// Read a per-market timestamp (e.g. `x12_ts`) out of a `latest_t` JSON blob.
+fn latest_t_field(latest_t: &Option<Value>, key: &str) -> Option<i64> {
+    latest_t.as_ref()?.get(key)?.as_i64()
+}
+
+/// Merge an incoming `latest_t` blob into the stored one, keeping the max timestamp per
+/// key instead of assigning the incoming object wholesale. An update only carries a
+/// timestamp for the segment it actually touched (see `build_latest_t` in odds-engine), so
+/// overwriting the whole blob would roll back the other segments' high-water marks and let
+/// a later, already-applied update pass the freshness check and clobber good data.
+fn merge_latest_t(stored: &Option<Value>, incoming: &Option<Value>) -> Option<Value> {
+    let incoming_map = match incoming.as_ref().and_then(|v| v.as_object()) {
+        Some(m) => m,
+        None => return stored.clone(),
+    };
+
+    let mut merged = stored.as_ref().and_then(|v| v.as_object()).cloned().unwrap_or_default();
+    for (key, incoming_val) in incoming_map {
+        let take_incoming = match merged.get(key).and_then(|v| v.as_i64()) {
+            Some(stored_ts) => incoming_val.as_i64().map(|v| v > stored_ts).unwrap_or(true),
+            None => true,
+        };
+        if take_incoming {
+            merged.insert(key.clone(), incoming_val.clone());
+        }
+    }
+    Some(Value::Object(merged))
+}
+
 pub struct Cache {
     pub fixtures: HashMap<i64, FixtureData>,
     // (timestamp, fixture_id) -> ()
     // Ordered by timestamp, so first entry is oldest
     eviction_queue: BTreeMap<(i64, i64), ()>,
     max_fixtures: usize,
+    stale_rejected: u64,
+    // Monotonically increasing, bumped once per applied update. Stamped onto the touched
+    // fixture's `version` so resuming clients can ask for "what changed since version N".
+    sequence: u64,
+    /// Margin-removal method used to populate each bookmaker's `fair_*` fields.
+    fair_odds_method: FairOddsMethod,
 }
 
 impl Cache {
-    pub fn new(max_fixtures: usize) -> Self {
+    pub fn new(max_fixtures: usize, fair_odds_method: FairOddsMethod) -> Self {
         Self {
             fixtures: HashMap::new(),
             eviction_queue: BTreeMap::new(),
             max_fixtures,
+            stale_rejected: 0,
+            sequence: 0,
+            fair_odds_method,
         }
     }
 
+    /// Number of update segments discarded so far for being out-of-order/stale.
+    pub fn stale_rejected(&self) -> u64 {
+        self.stale_rejected
+    }
+
+    /// Current high-water mark of `sequence`; handed back to clients as a resumption
+    /// cursor once a snapshot/delta reflecting it has been sent.
+    pub fn current_sequence(&self) -> u64 {
+        self.sequence
+    }
+
     /// Apply an odds update and recalculate top odds
     pub fn apply_update(&mut self, update: OddsUpdate) -> Option<&FixtureData> {
+        // A revoked price is withdrawn entirely rather than merged into the bookmaker's
+        // existing odds, so it takes its own path.
+        if update.revoked {
+            return self.apply_revoke(update);
+        }
+
         // Check if we need to evict (only if new fixture and at capacity)
         if !self.fixtures.contains_key(&update.fixture_id) && self.fixtures.len() >= self.max_fixtures {
             self.evict_oldest();
@@ -42,106 +98,197 @@ impl Cache {
             .entry(update.bookmaker.clone())
             .or_insert_with(BookmakerOdds::default);
 
-        // Push current to history if it has data (newest first, max 20 snapshots)
-        if bookie_odds.current.timestamp > 0 {
-            bookie_odds.history.push_front(bookie_odds.current.clone());
-            if bookie_odds.history.len() > 20 {
-                bookie_odds.history.pop_back();
-            }
+        // Out-of-order guard: drop the whole update outright if it isn't strictly newer
+        // than the last one applied for this (fixture, bookmaker) pair. `timestamp == 0`
+        // means the bookmaker entry was just created and has never been touched.
+        if bookie_odds.timestamp != 0 && update.timestamp <= bookie_odds.timestamp {
+            self.stale_rejected += 1;
+            return self.fixtures.get(&update.fixture_id);
         }
 
         // Apply base fields
         bookie_odds.bookie_id = update.bookie_id;
         bookie_odds.decimals = update.decimals;
-        bookie_odds.current.timestamp = update.timestamp;
 
-        // Apply odds (only update fields that are Some)
+        // Gate each market segment on its own high-water mark, keyed off `latest_t`
+        // (falling back to the top-level timestamp when a segment has no specific one),
+        // so a late-arriving/out-of-order update can't clobber a newer snapshot.
+        let stored_x12_ts = latest_t_field(&bookie_odds.latest_t, "x12_ts").unwrap_or(bookie_odds.timestamp);
+        let stored_ah_ts = latest_t_field(&bookie_odds.latest_t, "ah_ts").unwrap_or(bookie_odds.timestamp);
+        let stored_ou_ts = latest_t_field(&bookie_odds.latest_t, "ou_ts").unwrap_or(bookie_odds.timestamp);
+
+        let incoming_x12_ts = latest_t_field(&update.latest_t, "x12_ts").unwrap_or(update.timestamp);
+        let incoming_ah_ts = latest_t_field(&update.latest_t, "ah_ts").unwrap_or(update.timestamp);
+        let incoming_ou_ts = latest_t_field(&update.latest_t, "ou_ts").unwrap_or(update.timestamp);
+
+        let x12_is_fresh = incoming_x12_ts > stored_x12_ts;
+        let ah_is_fresh = incoming_ah_ts > stored_ah_ts;
+        let ou_is_fresh = incoming_ou_ts > stored_ou_ts;
+
+        // Apply odds (only update fields that are Some, and only when strictly newer)
         if let Some(x12) = update.x12 {
-            bookie_odds.current.x12_h = Some(x12[0]);
-            bookie_odds.current.x12_x = Some(x12[1]);
-            bookie_odds.current.x12_a = Some(x12[2]);
-        }
-        if let Some(ah_lines) = update.ah_lines {
-            bookie_odds.current.ah_lines = ah_lines;
-        }
-        if let Some(ah_h) = update.ah_h {
-            bookie_odds.current.ah_h = ah_h;
-        }
-        if let Some(ah_a) = update.ah_a {
-            bookie_odds.current.ah_a = ah_a;
-        }
-        if let Some(ou_lines) = update.ou_lines {
-            bookie_odds.current.ou_lines = ou_lines;
+            if x12_is_fresh {
+                bookie_odds.x12_h = Some(x12[0]);
+                bookie_odds.x12_x = Some(x12[1]);
+                bookie_odds.x12_a = Some(x12[2]);
+            } else {
+                self.stale_rejected += 1;
+            }
         }
-        if let Some(ou_o) = update.ou_o {
-            bookie_odds.current.ou_o = ou_o;
+        if ah_is_fresh {
+            if let Some(ah_lines) = update.ah_lines {
+                bookie_odds.ah_lines = ah_lines;
+            }
+            if let Some(ah_h) = update.ah_h {
+                bookie_odds.ah_h = ah_h;
+            }
+            if let Some(ah_a) = update.ah_a {
+                bookie_odds.ah_a = ah_a;
+            }
+        } else if update.ah_lines.is_some() || update.ah_h.is_some() || update.ah_a.is_some() {
+            self.stale_rejected += 1;
         }
-        if let Some(ou_u) = update.ou_u {
-            bookie_odds.current.ou_u = ou_u;
+        if ou_is_fresh {
+            if let Some(ou_lines) = update.ou_lines {
+                bookie_odds.ou_lines = ou_lines;
+            }
+            if let Some(ou_o) = update.ou_o {
+                bookie_odds.ou_o = ou_o;
+            }
+            if let Some(ou_u) = update.ou_u {
+                bookie_odds.ou_u = ou_u;
+            }
+        } else if update.ou_lines.is_some() || update.ou_o.is_some() || update.ou_u.is_some() {
+            self.stale_rejected += 1;
         }
 
         // Apply DB-format fields (ids, max_stakes, latest_t)
         if update.ids.is_some() {
-            bookie_odds.current.ids = update.ids;
+            bookie_odds.ids = update.ids;
         }
         if update.max_stakes.is_some() {
-            bookie_odds.current.max_stakes = update.max_stakes;
+            bookie_odds.max_stakes = update.max_stakes;
         }
         if update.latest_t.is_some() {
-            bookie_odds.current.latest_t = update.latest_t;
+            bookie_odds.latest_t = merge_latest_t(&bookie_odds.latest_t, &update.latest_t);
         }
+        bookie_odds.timestamp = bookie_odds.timestamp.max(update.timestamp);
 
         // Update fixture timestamp
-        fixture.last_update = update.timestamp;
-        
+        fixture.last_update = fixture.last_update.max(update.timestamp);
+
+        // Stamp this touch with the next sequence number for resumable subscriptions.
+        self.sequence += 1;
+        fixture.version = self.sequence;
+
         // Add back to eviction queue with new timestamp
         self.eviction_queue.insert((fixture.last_update, fixture.fixture_id), ());
 
         // Recalculate fair odds for this bookmaker
         // X12 - calculate fair odds if all three outcomes are present
-        if let (Some(h), Some(x), Some(a)) = (bookie_odds.current.x12_h, bookie_odds.current.x12_x, bookie_odds.current.x12_a) {
+        if let (Some(h), Some(x), Some(a)) = (bookie_odds.x12_h, bookie_odds.x12_x, bookie_odds.x12_a) {
             let x12_odds = [h, x, a];
-            if let Some(fair) = calculate_fair_odds(&x12_odds, bookie_odds.decimals, 3) {
-                bookie_odds.current.fair_x12_h = Some(fair[0]);
-                bookie_odds.current.fair_x12_x = Some(fair[1]);
-                bookie_odds.current.fair_x12_a = Some(fair[2]);
+            if let Some(fair) = calculate_fair_odds(&x12_odds, bookie_odds.decimals, 3, self.fair_odds_method) {
+                bookie_odds.fair_x12_h = Some(fair[0]);
+                bookie_odds.fair_x12_x = Some(fair[1]);
+                bookie_odds.fair_x12_a = Some(fair[2]);
             }
         }
 
         // AH
-        bookie_odds.current.fair_ah_h.clear();
-        bookie_odds.current.fair_ah_a.clear();
-        for i in 0..bookie_odds.current.ah_lines.len() {
-             let h = *bookie_odds.current.ah_h.get(i).unwrap_or(&0);
-             let a = *bookie_odds.current.ah_a.get(i).unwrap_or(&0);
+        bookie_odds.fair_ah_h.clear();
+        bookie_odds.fair_ah_a.clear();
+        for i in 0..bookie_odds.ah_lines.len() {
+             let h = *bookie_odds.ah_h.get(i).unwrap_or(&0);
+             let a = *bookie_odds.ah_a.get(i).unwrap_or(&0);
              let odds = [h, a];
 
-             if let Some(fair) = calculate_fair_odds(&odds, bookie_odds.decimals, 2) {
-                 bookie_odds.current.fair_ah_h.push(fair[0]);
-                 bookie_odds.current.fair_ah_a.push(fair[1]);
+             if let Some(fair) = calculate_fair_odds(&odds, bookie_odds.decimals, 2, self.fair_odds_method) {
+                 bookie_odds.fair_ah_h.push(fair[0]);
+                 bookie_odds.fair_ah_a.push(fair[1]);
              } else {
-                 bookie_odds.current.fair_ah_h.push(0);
-                 bookie_odds.current.fair_ah_a.push(0);
+                 bookie_odds.fair_ah_h.push(0);
+                 bookie_odds.fair_ah_a.push(0);
              }
         }
 
         // OU
-        bookie_odds.current.fair_ou_o.clear();
-        bookie_odds.current.fair_ou_u.clear();
-        for i in 0..bookie_odds.current.ou_lines.len() {
-             let o = *bookie_odds.current.ou_o.get(i).unwrap_or(&0);
-             let u = *bookie_odds.current.ou_u.get(i).unwrap_or(&0);
+        bookie_odds.fair_ou_o.clear();
+        bookie_odds.fair_ou_u.clear();
+        for i in 0..bookie_odds.ou_lines.len() {
+             let o = *bookie_odds.ou_o.get(i).unwrap_or(&0);
+             let u = *bookie_odds.ou_u.get(i).unwrap_or(&0);
              let odds = [o, u];
 
-             if let Some(fair) = calculate_fair_odds(&odds, bookie_odds.decimals, 2) {
-                 bookie_odds.current.fair_ou_o.push(fair[0]);
-                 bookie_odds.current.fair_ou_u.push(fair[1]);
+             if let Some(fair) = calculate_fair_odds(&odds, bookie_odds.decimals, 2, self.fair_odds_method) {
+                 bookie_odds.fair_ou_o.push(fair[0]);
+                 bookie_odds.fair_ou_u.push(fair[1]);
              } else {
-                 bookie_odds.current.fair_ou_o.push(0);
-                 bookie_odds.current.fair_ou_u.push(0);
+                 bookie_odds.fair_ou_o.push(0);
+                 bookie_odds.fair_ou_u.push(0);
              }
         }
 
+        // Feed the filter DSL's per-field `history` ring buffer with the values just
+        // written, keyed the same way the DSL resolves field paths, so per-line AH/OU
+        // fields get independent buffers.
+        let sample_ts = bookie_odds.timestamp;
+        let field_prefix = format!("bookmakers.{}.", update.bookmaker);
+        if let Some(v) = bookie_odds.x12_h {
+            fixture.history.record_sample(format!("{}x12_h", field_prefix), sample_ts, v as f64);
+        }
+        if let Some(v) = bookie_odds.x12_x {
+            fixture.history.record_sample(format!("{}x12_x", field_prefix), sample_ts, v as f64);
+        }
+        if let Some(v) = bookie_odds.x12_a {
+            fixture.history.record_sample(format!("{}x12_a", field_prefix), sample_ts, v as f64);
+        }
+        for (i, &line) in bookie_odds.ah_lines.iter().enumerate() {
+            if let Some(&h) = bookie_odds.ah_h.get(i) {
+                fixture.history.record_sample(format!("{}ah_h[{}]", field_prefix, line), sample_ts, h as f64);
+            }
+            if let Some(&a) = bookie_odds.ah_a.get(i) {
+                fixture.history.record_sample(format!("{}ah_a[{}]", field_prefix, line), sample_ts, a as f64);
+            }
+        }
+        for (i, &line) in bookie_odds.ou_lines.iter().enumerate() {
+            if let Some(&o) = bookie_odds.ou_o.get(i) {
+                fixture.history.record_sample(format!("{}ou_o[{}]", field_prefix, line), sample_ts, o as f64);
+            }
+            if let Some(&u) = bookie_odds.ou_u.get(i) {
+                fixture.history.record_sample(format!("{}ou_u[{}]", field_prefix, line), sample_ts, u as f64);
+            }
+        }
+
+        self.fixtures.get(&update.fixture_id)
+    }
+
+    /// Withdraw a bookmaker's entry from a fixture entirely (a `revoke` notification),
+    /// mirroring the external `FillUpdateStatus::Revoke` design. Bumps the fixture's
+    /// version like any other touch so the removal is picked up by subscribers.
+    fn apply_revoke(&mut self, update: OddsUpdate) -> Option<&FixtureData> {
+        let fixture = self.fixtures.get(&update.fixture_id)?;
+
+        let still_fresh = fixture
+            .bookmakers
+            .get(&update.bookmaker)
+            .map(|b| update.timestamp > b.timestamp)
+            .unwrap_or(true);
+        if !still_fresh {
+            self.stale_rejected += 1;
+            return self.fixtures.get(&update.fixture_id);
+        }
+
+        let old_key = (fixture.last_update, fixture.fixture_id);
+
+        let fixture = self.fixtures.get_mut(&update.fixture_id).unwrap();
+        fixture.bookmakers.remove(&update.bookmaker);
+        fixture.last_update = fixture.last_update.max(update.timestamp);
+        self.sequence += 1;
+        fixture.version = self.sequence;
+
+        self.eviction_queue.remove(&old_key);
+        self.eviction_queue.insert((fixture.last_update, fixture.fixture_id), ());
 
         self.fixtures.get(&update.fixture_id)
     }
@@ -3,15 +3,35 @@
 /// Maximum acceptable margin (12%) - odds with higher margin are unreliable
 const MAX_MARGIN: f64 = 0.12;
 
-/// Calculate fair odds using "Margin Weights Proportional to the Odds" method
-/// Formula: Of = (n * O) / (n - M * O)
-/// Where M is the bookmaker's margin: M = (Sum(1/O) - 1)
-/// 
+/// How to strip the bookmaker's margin out of a set of odds to recover fair/true odds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FairOddsMethod {
+    /// "Margin Weights Proportional to the Odds": spreads the margin across outcomes in
+    /// proportion to each outcome's own odds. Cheap, and the long-standing default.
+    Proportional,
+    /// Shin's model: solves for an implied insider-trading fraction `z` and backs true
+    /// probabilities out of it. More accurate than proportional de-margining when one
+    /// outcome is a heavy favourite, which is common in football X12 markets.
+    Shin,
+}
+
+impl FairOddsMethod {
+    pub fn from_env_str(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "shin" => FairOddsMethod::Shin,
+            _ => FairOddsMethod::Proportional,
+        }
+    }
+}
+
+/// Calculate fair odds, removing the bookmaker's margin using `method`.
+///
 /// Returns None if:
 /// - Wrong number of odds provided
 /// - Any odds are zero or negative
 /// - Margin exceeds 12% (unreliable odds)
-pub fn calculate_fair_odds(odds: &[i32], decimals: i32, n: usize) -> Option<Vec<i32>> {
+/// - `method` fails to converge on a valid result
+pub fn calculate_fair_odds(odds: &[i32], decimals: i32, n: usize, method: FairOddsMethod) -> Option<Vec<i32>> {
     if odds.len() != n {
         return None;
     }
@@ -29,25 +49,92 @@ pub fn calculate_fair_odds(odds: &[i32], decimals: i32, n: usize) -> Option<Vec<
     // Calculate margin
     let sum_inv: f64 = decimal_odds.iter().map(|&o| 1.0 / o).sum();
     let margin = sum_inv - 1.0;
-    
+
     // Reject high margin odds (>12%) - unreliable for fair odds calculation
     if margin > MAX_MARGIN {
         return None;
     }
-    let mut fair_odds = Vec::with_capacity(n);
+
+    let fair_probs = match method {
+        FairOddsMethod::Proportional => proportional_fair_probs(&decimal_odds, margin, n),
+        FairOddsMethod::Shin => shin_fair_probs(&decimal_odds, sum_inv),
+    }?;
+
+    Some(
+        fair_probs
+            .into_iter()
+            .map(|p| (1.0 / p * 10f64.powi(decimals)).round() as i32)
+            .collect(),
+    )
+}
+
+/// "Margin Weights Proportional to the Odds": Of = (n * O) / (n - M * O).
+fn proportional_fair_probs(decimal_odds: &[f64], margin: f64, n: usize) -> Option<Vec<f64>> {
     let n_f64 = n as f64;
+    let mut fair_probs = Vec::with_capacity(n);
 
-    for &o in &decimal_odds {
+    for &o in decimal_odds {
         let denominator = n_f64 - margin * o;
         if denominator <= 0.0 {
             return None; // Invalid state
         }
         let fair = (n_f64 * o) / denominator;
-        
-        // Convert back to basis points
-        let fair_basis = (fair * 10f64.powi(decimals)).round() as i32;
-        fair_odds.push(fair_basis);
+        fair_probs.push(1.0 / fair);
+    }
+
+    Some(fair_probs)
+}
+
+/// Shin's model: given booking probabilities q_i = 1/O_i and booksum B = Σ q_i, find the
+/// insider-trading fraction z ∈ [0, ~0.25] such that
+///   p_i = (sqrt(z² + 4(1−z)·q_i²/B) − z) / (2(1−z))
+/// sums to 1, via bisection (Σ p_i is monotonic in z). Returns None if bisection fails to
+/// converge or any p_i <= 0.
+fn shin_fair_probs(decimal_odds: &[f64], booksum: f64) -> Option<Vec<f64>> {
+    const MAX_Z: f64 = 0.25;
+    const TOLERANCE: f64 = 1e-9;
+    const MAX_ITERATIONS: u32 = 100;
+
+    let q: Vec<f64> = decimal_odds.iter().map(|&o| 1.0 / o).collect();
+
+    let sum_probs = |z: f64| -> f64 {
+        q.iter()
+            .map(|&qi| ((z * z + 4.0 * (1.0 - z) * qi * qi / booksum).sqrt() - z) / (2.0 * (1.0 - z)))
+            .sum()
+    };
+
+    let mut lo = 0.0_f64;
+    let mut hi = MAX_Z;
+    let mut z = 0.0_f64;
+    let mut converged = false;
+
+    for _ in 0..MAX_ITERATIONS {
+        z = (lo + hi) / 2.0;
+        let total = sum_probs(z);
+        if (total - 1.0).abs() < TOLERANCE {
+            converged = true;
+            break;
+        }
+        // Σ p_i is decreasing in z, so overshooting 1 means z needs to move up.
+        if total > 1.0 {
+            lo = z;
+        } else {
+            hi = z;
+        }
+    }
+
+    if !converged {
+        return None;
+    }
+
+    let probs: Vec<f64> = q
+        .iter()
+        .map(|&qi| ((z * z + 4.0 * (1.0 - z) * qi * qi / booksum).sqrt() - z) / (2.0 * (1.0 - z)))
+        .collect();
+
+    if probs.iter().any(|&p| p <= 0.0) {
+        return None;
     }
 
-    Some(fair_odds)
+    Some(probs)
 }
@@ -0,0 +1,2 @@
+pub mod fair_odds;
+pub mod normalize;
@@ -0,0 +1,114 @@
+// Normalizes raw integer prices (scaled by `decimals`) into a canonical decimal-odds /
+// implied-probability / fair-probability representation, so clients that opt in via
+// `NORMALIZED_ODDS` never have to redo the `value / 10^decimals` conversion themselves.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::types::{BookmakerOdds, WsMessage};
+
+/// A single price expressed three ways: decimal odds, the implied probability that
+/// follows directly from it, and (when a fair counterpart exists) the margin-removed
+/// fair probability.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizedPrice {
+    pub decimal: f64,
+    pub implied_prob: f64,
+    pub fair_prob: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct NormalizedX12 {
+    pub home: Option<NormalizedPrice>,
+    pub draw: Option<NormalizedPrice>,
+    pub away: Option<NormalizedPrice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizedAhLine {
+    pub line: f64,
+    pub h: Option<NormalizedPrice>,
+    pub a: Option<NormalizedPrice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizedOuLine {
+    pub line: f64,
+    pub o: Option<NormalizedPrice>,
+    pub u: Option<NormalizedPrice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct NormalizedOdds {
+    pub x12: Option<NormalizedX12>,
+    pub ah: Vec<NormalizedAhLine>,
+    pub ou: Vec<NormalizedOuLine>,
+}
+
+fn price(raw: Option<i32>, fair_raw: Option<i32>, decimals: i32) -> Option<NormalizedPrice> {
+    let raw = raw?;
+    if raw <= 0 {
+        return None;
+    }
+    let scale = 10f64.powi(decimals);
+    let decimal = raw as f64 / scale;
+    let fair_prob = fair_raw
+        .filter(|&f| f > 0)
+        .map(|f| 1.0 / (f as f64 / scale));
+
+    Some(NormalizedPrice {
+        decimal,
+        implied_prob: 1.0 / decimal,
+        fair_prob,
+    })
+}
+
+/// Build the canonical view of one bookmaker's odds. `decimals` and line counts are
+/// taken from `odds` itself, so x12, AH, and OU are all normalized the same way.
+pub fn normalize(odds: &BookmakerOdds) -> NormalizedOdds {
+    let d = odds.decimals;
+
+    let x12 = if odds.x12_h.is_some() || odds.x12_x.is_some() || odds.x12_a.is_some() {
+        Some(NormalizedX12 {
+            home: price(odds.x12_h, odds.fair_x12_h, d),
+            draw: price(odds.x12_x, odds.fair_x12_x, d),
+            away: price(odds.x12_a, odds.fair_x12_a, d),
+        })
+    } else {
+        None
+    };
+
+    let ah = odds
+        .ah_lines
+        .iter()
+        .enumerate()
+        .map(|(i, &line)| NormalizedAhLine {
+            line,
+            h: price(odds.ah_h.get(i).copied(), odds.fair_ah_h.get(i).copied(), d),
+            a: price(odds.ah_a.get(i).copied(), odds.fair_ah_a.get(i).copied(), d),
+        })
+        .collect();
+
+    let ou = odds
+        .ou_lines
+        .iter()
+        .enumerate()
+        .map(|(i, &line)| NormalizedOuLine {
+            line,
+            o: price(odds.ou_o.get(i).copied(), odds.fair_ou_o.get(i).copied(), d),
+            u: price(odds.ou_u.get(i).copied(), odds.fair_ou_u.get(i).copied(), d),
+        })
+        .collect();
+
+    NormalizedOdds { x12, ah, ou }
+}
+
+/// Populate `msg.normalized` from `msg.bookmakers`. Called only when `NORMALIZED_ODDS`
+/// is enabled, so clients that don't opt in never pay for the extra serialization.
+pub fn attach(msg: &mut WsMessage) {
+    let normalized = msg
+        .bookmakers
+        .iter()
+        .map(|(name, odds)| (name.clone(), normalize(odds)))
+        .collect::<HashMap<_, _>>();
+    msg.normalized = Some(normalized);
+}
@@ -0,0 +1,372 @@
+// OHLC "candle" aggregation over the odds stream, plus a backfill path that
+// reconstructs history from the `football_odds` table for fixtures that
+// predate the service starting.
+use axum::extract::{Query, State};
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+
+use crate::network::stream::SharedState;
+
+/// Supported candle bucket widths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandleInterval {
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+}
+
+impl CandleInterval {
+    pub fn as_secs(&self) -> i64 {
+        match self {
+            CandleInterval::OneMinute => 60,
+            CandleInterval::FiveMinutes => 300,
+            CandleInterval::OneHour => 3600,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            CandleInterval::OneMinute => "1m",
+            CandleInterval::FiveMinutes => "5m",
+            CandleInterval::OneHour => "1h",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "1m" => Some(CandleInterval::OneMinute),
+            "5m" => Some(CandleInterval::FiveMinutes),
+            "1h" => Some(CandleInterval::OneHour),
+            _ => None,
+        }
+    }
+}
+
+/// Identifies a single candle series: one bookmaker's price for one outcome
+/// of one market, on one fixture, at one bucket width.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CandleKey {
+    pub fixture_id: i64,
+    pub bookmaker: String,
+    pub market: String,
+    pub outcome: String,
+    pub interval: CandleInterval,
+}
+
+/// A single OHLC bucket, expressed in decimal odds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    pub bucket_start: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Number of ticks folded into this bucket, including the opening one.
+    pub count: u64,
+}
+
+impl Candle {
+    fn open_at(bucket_start: i64, price: f64) -> Self {
+        Self {
+            bucket_start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            count: 1,
+        }
+    }
+
+    fn apply(&mut self, price: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.count += 1;
+    }
+}
+
+/// Accumulates live ticks into in-progress candles, handing back the
+/// just-closed candle whenever a tick rolls the bucket over.
+#[derive(Default)]
+pub struct CandleAggregator {
+    open_candles: HashMap<CandleKey, Candle>,
+}
+
+impl CandleAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a tick (decimal odds price at a millisecond timestamp). Returns
+    /// the candle that just closed, if this tick started a new bucket.
+    pub fn record(&mut self, key: CandleKey, price: f64, timestamp_ms: i64) -> Option<Candle> {
+        let interval_ms = key.interval.as_secs() * 1000;
+        let bucket_start = (timestamp_ms / interval_ms) * interval_ms;
+
+        match self.open_candles.get_mut(&key) {
+            Some(candle) if candle.bucket_start == bucket_start => {
+                candle.apply(price);
+                None
+            }
+            Some(candle) => {
+                let closed = candle.clone();
+                self.open_candles
+                    .insert(key, Candle::open_at(bucket_start, price));
+                Some(closed)
+            }
+            None => {
+                self.open_candles
+                    .insert(key, Candle::open_at(bucket_start, price));
+                None
+            }
+        }
+    }
+}
+
+/// Persist one completed candle, upserting on (fixture_id, bookmaker, market, outcome, interval, bucket_start).
+pub async fn persist_candle(pool: &PgPool, key: &CandleKey, candle: &Candle) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO odds_candles
+            (fixture_id, bookmaker, market, outcome, interval, bucket_start, open, high, low, close, count)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (fixture_id, bookmaker, market, outcome, interval, bucket_start)
+        DO UPDATE SET high = GREATEST(odds_candles.high, EXCLUDED.high),
+                      low = LEAST(odds_candles.low, EXCLUDED.low),
+                      close = EXCLUDED.close,
+                      count = odds_candles.count + EXCLUDED.count
+        "#,
+    )
+    .bind(key.fixture_id)
+    .bind(&key.bookmaker)
+    .bind(&key.market)
+    .bind(&key.outcome)
+    .bind(key.interval.as_str())
+    .bind(candle.bucket_start)
+    .bind(candle.open)
+    .bind(candle.high)
+    .bind(candle.low)
+    .bind(candle.close)
+    .bind(candle.count as i64)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn fetch_candles(
+    pool: &PgPool,
+    fixture_id: i64,
+    bookmaker: &str,
+    market: &str,
+    outcome: &str,
+    interval: CandleInterval,
+    from_ms: i64,
+    to_ms: i64,
+) -> Result<Vec<Candle>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT bucket_start, open, high, low, close, count
+        FROM odds_candles
+        WHERE fixture_id = $1 AND bookmaker = $2 AND market = $3 AND outcome = $4
+          AND interval = $5 AND bucket_start >= $6 AND bucket_start <= $7
+        ORDER BY bucket_start ASC
+        "#,
+    )
+    .bind(fixture_id)
+    .bind(bookmaker)
+    .bind(market)
+    .bind(outcome)
+    .bind(interval.as_str())
+    .bind(from_ms)
+    .bind(to_ms)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let count: i64 = row.get("count");
+            Candle {
+                bucket_start: row.get("bucket_start"),
+                open: row.get("open"),
+                high: row.get("high"),
+                low: row.get("low"),
+                close: row.get("close"),
+                count: count as u64,
+            }
+        })
+        .collect())
+}
+
+/// A single raw price observation extracted from `football_odds` history, used
+/// as the input to the rollup phase below. Kept separate from `Candle` so the
+/// extraction and rollup phases can be re-run independently of each other.
+struct RawTick {
+    bookmaker: String,
+    market: String,
+    outcome: String,
+    timestamp_ms: i64,
+    decimal_price: f64,
+}
+
+/// Phase 1: walk `football_odds` for a fixture and flatten its `odds_x12` /
+/// `odds_ah` / `odds_ou` history arrays into raw ticks.
+async fn extract_raw_ticks(pool: &PgPool, fixture_id: i64) -> Result<Vec<RawTick>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT bookie, decimals, odds_x12, odds_ah, odds_ou, lines
+        FROM football_odds
+        WHERE fixture_id = $1
+        "#,
+    )
+    .bind(fixture_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut ticks = Vec::new();
+
+    for row in rows {
+        let bookmaker: String = row.get("bookie");
+        let decimals: i32 = row.get("decimals");
+        let scale = 10f64.powi(decimals);
+
+        let to_decimal = |v: i64| -> f64 { v as f64 / scale };
+
+        let x12_history: Vec<serde_json::Value> =
+            serde_json::from_value(row.get("odds_x12")).unwrap_or_default();
+        for entry in &x12_history {
+            let Some(ts) = entry.get("t").and_then(|v| v.as_i64()) else { continue };
+            let Some(arr) = entry.get("x12").and_then(|v| v.as_array()) else { continue };
+            for (idx, outcome) in ["home", "draw", "away"].iter().enumerate() {
+                if let Some(price) = arr.get(idx).and_then(|v| v.as_i64()) {
+                    ticks.push(RawTick {
+                        bookmaker: bookmaker.clone(),
+                        market: "x12".to_string(),
+                        outcome: outcome.to_string(),
+                        timestamp_ms: ts * 1000,
+                        decimal_price: to_decimal(price),
+                    });
+                }
+            }
+        }
+
+        let ah_history: Vec<serde_json::Value> =
+            serde_json::from_value(row.get("odds_ah")).unwrap_or_default();
+        for entry in &ah_history {
+            let Some(ts) = entry.get("t").and_then(|v| v.as_i64()) else { continue };
+            for (field, outcome) in [("ah_h", "home"), ("ah_a", "away")] {
+                if let Some(arr) = entry.get(field).and_then(|v| v.as_array()) {
+                    for price in arr.iter().filter_map(|v| v.as_i64()) {
+                        ticks.push(RawTick {
+                            bookmaker: bookmaker.clone(),
+                            market: "ah".to_string(),
+                            outcome: outcome.to_string(),
+                            timestamp_ms: ts * 1000,
+                            decimal_price: to_decimal(price),
+                        });
+                    }
+                }
+            }
+        }
+
+        let ou_history: Vec<serde_json::Value> =
+            serde_json::from_value(row.get("odds_ou")).unwrap_or_default();
+        for entry in &ou_history {
+            let Some(ts) = entry.get("t").and_then(|v| v.as_i64()) else { continue };
+            for (field, outcome) in [("ou_o", "over"), ("ou_u", "under")] {
+                if let Some(arr) = entry.get(field).and_then(|v| v.as_array()) {
+                    for price in arr.iter().filter_map(|v| v.as_i64()) {
+                        ticks.push(RawTick {
+                            bookmaker: bookmaker.clone(),
+                            market: "ou".to_string(),
+                            outcome: outcome.to_string(),
+                            timestamp_ms: ts * 1000,
+                            decimal_price: to_decimal(price),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(ticks)
+}
+
+/// Phase 2: roll a flat list of raw ticks up into OHLC candles. Pure so it
+/// can be tested/re-run independently of the DB round-trip in phase 1.
+fn rollup_ticks(fixture_id: i64, ticks: Vec<RawTick>, interval: CandleInterval) -> HashMap<CandleKey, Vec<Candle>> {
+    let mut aggregator = CandleAggregator::new();
+    let mut series: HashMap<CandleKey, Vec<Candle>> = HashMap::new();
+
+    let mut sorted = ticks;
+    sorted.sort_by_key(|t| t.timestamp_ms);
+
+    for tick in sorted {
+        let key = CandleKey {
+            fixture_id,
+            bookmaker: tick.bookmaker,
+            market: tick.market,
+            outcome: tick.outcome,
+            interval,
+        };
+        if let Some(closed) = aggregator.record(key.clone(), tick.decimal_price, tick.timestamp_ms) {
+            series.entry(key).or_default().push(closed);
+        }
+    }
+
+    // Flush whatever candle is still open per key so the last bucket isn't lost.
+    for (key, candle) in aggregator.open_candles {
+        series.entry(key).or_default().push(candle);
+    }
+
+    series
+}
+
+/// Backfill candles for a single fixture from its stored `football_odds` history.
+pub async fn backfill_fixture(
+    pool: &PgPool,
+    fixture_id: i64,
+    interval: CandleInterval,
+) -> Result<(), sqlx::Error> {
+    let ticks = extract_raw_ticks(pool, fixture_id).await?;
+    let series = rollup_ticks(fixture_id, ticks, interval);
+
+    for (key, candles) in series {
+        for candle in candles {
+            persist_candle(pool, &key, &candle).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CandleQuery {
+    pub fixture_id: i64,
+    pub bookmaker: String,
+    pub market: String,
+    pub outcome: String,
+    pub interval: String,
+    pub from: i64,
+    pub to: i64,
+}
+
+/// HTTP endpoint: GET /candles?fixture_id=..&bookmaker=..&market=..&outcome=..&interval=1m&from=..&to=..
+pub async fn get_candles(State(state): State<SharedState>, Query(q): Query<CandleQuery>) -> impl IntoResponse {
+    let Some(interval) = CandleInterval::from_str(&q.interval) else {
+        return (axum::http::StatusCode::BAD_REQUEST, "invalid interval").into_response();
+    };
+
+    match fetch_candles(&state.db, q.fixture_id, &q.bookmaker, &q.market, &q.outcome, interval, q.from, q.to).await {
+        Ok(candles) => Json(candles).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to fetch candles: {}", e);
+            (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "failed to fetch candles").into_response()
+        }
+    }
+}
@@ -3,6 +3,37 @@ pub struct Config {
     pub ws_port: u16,
     pub max_fixtures: usize,
     pub database_url: String,
+    pub normalized_odds_enabled: bool,
+    pub raw_sink_enabled: bool,
+    pub raw_sink_batch_size: usize,
+    pub raw_sink_flush_interval_secs: u64,
+    pub raw_sink_warm_start: bool,
+    /// "in_process" (default, single node) or "redis" (cluster fanout via pub/sub).
+    pub fanout_backend: String,
+    pub redis_url: String,
+    pub redis_fanout_channel: String,
+    /// Identifies this node's entries in cluster-wide stats; defaults to a random id so
+    /// multiple instances never collide if `NODE_ID` isn't set explicitly.
+    pub node_id: String,
+    pub arb_enabled: bool,
+    /// Minimum edge over the fair-odds consensus (e.g. `0.02` = 2%) before a single book's
+    /// price is reported as a value bet.
+    pub arb_value_bet_edge: f64,
+    /// No opportunity for the same fixture and market is re-broadcast more often than this,
+    /// even if it changed.
+    pub arb_scan_min_delay_secs: u64,
+    /// An unchanged opportunity is still re-broadcast at least this often, as a keepalive.
+    pub arb_scan_max_delay_secs: u64,
+    pub trace_sink_enabled: bool,
+    pub trace_sink_batch_size: usize,
+    pub trace_sink_flush_interval_secs: u64,
+    /// "proportional" (default) or "shin" - which margin-removal method populates the
+    /// `fair_*` fields on each bookmaker's odds.
+    pub fair_odds_method: String,
+    /// See `filters::FilterContext::interpolate_lines`.
+    pub filter_interpolate_lines: bool,
+    /// See `filters::FilterContext::record_arithmetic_failures`.
+    pub filter_record_arithmetic_failures: bool,
 }
 
 impl Config {
@@ -22,6 +53,67 @@ impl Config {
                 .unwrap_or(1000),
             database_url: std::env::var("DATABASE_URL")
                 .expect("DATABASE_URL must be set"),
+            normalized_odds_enabled: std::env::var("NORMALIZED_ODDS")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            raw_sink_enabled: std::env::var("RAW_SINK_ENABLED")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            raw_sink_batch_size: std::env::var("RAW_SINK_BATCH_SIZE")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+            raw_sink_flush_interval_secs: std::env::var("RAW_SINK_FLUSH_INTERVAL_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            raw_sink_warm_start: std::env::var("RAW_SINK_WARM_START")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            fanout_backend: std::env::var("FANOUT_BACKEND").unwrap_or_else(|_| "in_process".to_string()),
+            redis_url: std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string()),
+            redis_fanout_channel: std::env::var("REDIS_FANOUT_CHANNEL")
+                .unwrap_or_else(|_| "odds_processor:fanout".to_string()),
+            node_id: std::env::var("NODE_ID").unwrap_or_else(|_| {
+                format!(
+                    "{}-{}",
+                    std::env::var("HOSTNAME").unwrap_or_else(|_| "node".to_string()),
+                    std::process::id()
+                )
+            }),
+            arb_enabled: std::env::var("ARB_ENABLED")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            arb_value_bet_edge: std::env::var("ARB_VALUE_BET_EDGE")
+                .unwrap_or_else(|_| "0.02".to_string())
+                .parse()
+                .unwrap_or(0.02),
+            arb_scan_min_delay_secs: std::env::var("ARB_SCAN_MIN_DELAY_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            arb_scan_max_delay_secs: std::env::var("ARB_SCAN_MAX_DELAY_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            trace_sink_enabled: std::env::var("TRACE_SINK_ENABLED")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            trace_sink_batch_size: std::env::var("TRACE_SINK_BATCH_SIZE")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+            trace_sink_flush_interval_secs: std::env::var("TRACE_SINK_FLUSH_INTERVAL_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            fair_odds_method: std::env::var("FAIR_ODDS_METHOD").unwrap_or_else(|_| "proportional".to_string()),
+            filter_interpolate_lines: std::env::var("FILTER_INTERPOLATE_LINES")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            filter_record_arithmetic_failures: std::env::var("FILTER_RECORD_ARITHMETIC_FAILURES")
+                .map(|v| v == "true")
+                .unwrap_or(false),
         }
     }
 }
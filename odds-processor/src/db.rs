@@ -200,11 +200,14 @@ pub fn start_db_listener(pool: PgPool, tx: broadcast::Sender<OddsUpdate>) {
             match listener.recv().await {
                 Ok(notification) => {
                     let payload = notification.payload();
-                    // Payload format: fixture_id|bookie
-                    let Some((fixture_id_str, bookie)) = payload.split_once('|') else {
+                    // Payload format: fixture_id|bookie|status, where status is "new"
+                    // (the default, for triggers that don't send one) or "revoke".
+                    let mut parts = payload.splitn(3, '|');
+                    let (Some(fixture_id_str), Some(bookie)) = (parts.next(), parts.next()) else {
                         warn!("Invalid notification payload: {}", payload);
                         continue;
                     };
+                    let status = parts.next().unwrap_or("new");
 
                     let fixture_id = match fixture_id_str.parse::<i64>() {
                         Ok(id) => id,
@@ -219,6 +222,20 @@ pub fn start_db_listener(pool: PgPool, tx: broadcast::Sender<OddsUpdate>) {
                         continue;
                     }
 
+                    if status == "revoke" {
+                        let update = OddsUpdate {
+                            fixture_id,
+                            bookmaker: bookie.to_string(),
+                            timestamp: chrono::Utc::now().timestamp_millis(),
+                            revoked: true,
+                            ..Default::default()
+                        };
+                        if let Err(e) = tx.send(update) {
+                            warn!("Failed to broadcast revoke: {}", e);
+                        }
+                        continue;
+                    }
+
                     // Fetch the full odds update
                     match fetch_single_odds(&pool, fixture_id, bookie).await {
                         Ok(Some(update)) => {
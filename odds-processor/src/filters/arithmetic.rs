@@ -1,6 +1,8 @@
-use super::types::{ComputedValue, ArithOp, ResolvedValue};
+use super::types::{ComputedValue, ArithOp, ResolvedValue, ValueOrComputed, ConditionalValue};
 use super::context::{FilterContext, ArithmeticResult, ArithmeticDetail};
-use super::path::{resolve_value_or_computed, extract_field_path, resolve_json_path};
+use super::path::{resolve_value_or_computed, extract_field_path, resolve_json_path, resolve_field};
+use super::evaluator::compare;
+use serde_json::Value;
 
 // ============================================================================
 // PUBLIC API
@@ -8,34 +10,35 @@ use super::path::{resolve_value_or_computed, extract_field_path, resolve_json_pa
 
 /// Evaluate arithmetic expression and store detailed results in context for tracing.
 pub fn evaluate_arithmetic_with_ctx(comp: &ComputedValue, ctx: &mut FilterContext) -> Option<ResolvedValue> {
-    let left_path = extract_field_path(&comp.left);
-    let right_path = extract_field_path(&comp.right);
-    
-    // Check if both sides are field paths that need smart line matching
-    if let (Some(l_path), Some(r_path)) = (&left_path, &right_path) {
-        if should_use_line_matching(l_path, r_path) {
-            let (result, details) = evaluate_with_line_matching(comp, ctx.data, l_path, r_path)?;
-            ctx.last_arithmetic_result = Some(ArithmeticResult { details });
-            return Some(result);
-        }
-    }
-    
-    ctx.last_arithmetic_result = None;
-    evaluate_standard_arithmetic(comp, ctx)
+    let (result, details) = evaluate_arithmetic_collecting(comp, ctx)?;
+    ctx.last_arithmetic_result = Some(ArithmeticResult { details });
+    Some(result)
 }
 
 /// Evaluate arithmetic expression without context mutation.
 pub fn evaluate_arithmetic(comp: &ComputedValue, ctx: &FilterContext) -> Option<ResolvedValue> {
+    evaluate_arithmetic_collecting(comp, ctx).map(|(result, _)| result)
+}
+
+/// Dispatches to line-matching or standard arithmetic and returns, alongside the result, every
+/// `ArithmeticDetail` produced anywhere in `comp`'s expression tree - this node's own pairings
+/// plus everything flattened up from nested operands (see `resolve_operand`). Shared by both
+/// public entry points above so a nested expression traces identically whether or not the
+/// caller wants context mutated.
+fn evaluate_arithmetic_collecting(
+    comp: &ComputedValue,
+    ctx: &FilterContext,
+) -> Option<(ResolvedValue, Vec<ArithmeticDetail>)> {
     let left_path = extract_field_path(&comp.left);
     let right_path = extract_field_path(&comp.right);
-    
+
+    // Check if both sides are field paths that need smart line matching
     if let (Some(l_path), Some(r_path)) = (&left_path, &right_path) {
         if should_use_line_matching(l_path, r_path) {
-            let (result, _) = evaluate_with_line_matching(comp, ctx.data, l_path, r_path)?;
-            return Some(result);
+            return evaluate_with_line_matching(comp, ctx.data, l_path, r_path, ctx.record_arithmetic_failures);
         }
     }
-    
+
     evaluate_standard_arithmetic(comp, ctx)
 }
 
@@ -43,16 +46,26 @@ pub fn evaluate_arithmetic(comp: &ComputedValue, ctx: &FilterContext) -> Option<
 // STANDARD ARITHMETIC
 // ============================================================================
 
-fn evaluate_standard_arithmetic(comp: &ComputedValue, ctx: &FilterContext) -> Option<ResolvedValue> {
-    let left = resolve_value_or_computed(&comp.left, ctx)?;
-    let right = resolve_value_or_computed(&comp.right, ctx)?;
-
+fn evaluate_standard_arithmetic(
+    comp: &ComputedValue,
+    ctx: &FilterContext,
+) -> Option<(ResolvedValue, Vec<ArithmeticDetail>)> {
+    // `history`'s right side can be a lookback count (`{"samples": N}`), a shape the
+    // generic literal resolver below doesn't understand (it only handles numbers/arrays),
+    // so it's pulled out before the right side is resolved at all.
     if let ArithOp::History = comp.op {
-        return evaluate_history(left, right, ctx);
+        let left = resolve_value_or_computed(&comp.left, ctx)?;
+        return Some((evaluate_history(left, &comp.right, ctx)?, Vec::new()));
     }
 
+    let (left, left_sub_details) = resolve_operand(&comp.left, ctx)?;
+    let (right, right_sub_details) = resolve_operand(&comp.right, ctx)?;
+    let record_failures = ctx.record_arithmetic_failures;
+
     let mut result_values = Vec::new();
     let mut result_paths = Vec::new();
+    let mut result_details = left_sub_details;
+    result_details.extend(right_sub_details);
 
     // Check if either side has line-based paths
     let left_has_lines = left.paths.iter().any(|p| p.contains('['));
@@ -93,10 +106,13 @@ fn evaluate_standard_arithmetic(comp: &ComputedValue, ctx: &FilterContext) -> Op
             if let Some((r_idx, _, _)) = best_match {
                 let l_val = left.values[l_idx];
                 let r_val = right.values[*r_idx];
-                if let Some(res) = perform_op(comp.op, l_val, r_val) {
+                let r_path = &right.paths[*r_idx];
+                let (value, detail) = apply_op(comp.op, l_val, r_val, l_path, r_path, record_failures);
+                if let Some(res) = value {
                     result_values.push(res);
                     result_paths.push(l_path.clone());
                 }
+                result_details.extend(detail);
             }
         }
     } else {
@@ -104,10 +120,14 @@ fn evaluate_standard_arithmetic(comp: &ComputedValue, ctx: &FilterContext) -> Op
         match (left.values.len(), right.values.len()) {
             (l, r) if l > 1 && r > 1 && l == r => {
                 for i in 0..l {
-                    if let Some(res) = perform_op(comp.op, left.values[i], right.values[i]) {
+                    let (value, detail) = apply_op(
+                        comp.op, left.values[i], right.values[i], &left.paths[i], &right.paths[i], record_failures,
+                    );
+                    if let Some(res) = value {
                         result_values.push(res);
                         result_paths.push(left.paths[i].clone());
                     }
+                    result_details.extend(detail);
                 }
             },
             (l, r) if l > 1 && r > 1 => {
@@ -115,18 +135,20 @@ fn evaluate_standard_arithmetic(comp: &ComputedValue, ctx: &FilterContext) -> Op
                 for (l_idx, l_path) in left.paths.iter().enumerate() {
                     // Extract the field name (last part after final dot or @)
                     let l_field = extract_field_suffix(l_path);
-                    
+
                     // Find matching field in right side
                     for (r_idx, r_path) in right.paths.iter().enumerate() {
                         let r_field = extract_field_suffix(r_path);
-                        
+
                         if l_field == r_field {
                             let l_val = left.values[l_idx];
                             let r_val = right.values[r_idx];
-                            if let Some(res) = perform_op(comp.op, l_val, r_val) {
+                            let (value, detail) = apply_op(comp.op, l_val, r_val, l_path, r_path, record_failures);
+                            if let Some(res) = value {
                                 result_values.push(res);
                                 result_paths.push(l_path.clone());
                             }
+                            result_details.extend(detail);
                             break;
                         }
                     }
@@ -135,40 +157,66 @@ fn evaluate_standard_arithmetic(comp: &ComputedValue, ctx: &FilterContext) -> Op
             (l, 1) if l > 1 => {
                 let r_val = right.values[0];
                 for i in 0..l {
-                    if let Some(res) = perform_op(comp.op, left.values[i], r_val) {
+                    let (value, detail) = apply_op(
+                        comp.op, left.values[i], r_val, &left.paths[i], &right.paths[0], record_failures,
+                    );
+                    if let Some(res) = value {
                         result_values.push(res);
                         result_paths.push(left.paths[i].clone());
                     }
+                    result_details.extend(detail);
                 }
             },
             (1, r) if r > 1 => {
                 let l_val = left.values[0];
                 for i in 0..r {
-                    if let Some(res) = perform_op(comp.op, l_val, right.values[i]) {
+                    let (value, detail) = apply_op(
+                        comp.op, l_val, right.values[i], &left.paths[0], &right.paths[i], record_failures,
+                    );
+                    if let Some(res) = value {
                         result_values.push(res);
                         result_paths.push(right.paths[i].clone());
                     }
+                    result_details.extend(detail);
                 }
             },
             _ => {
                 if let (Some(&l_val), Some(&r_val)) = (left.values.first(), right.values.first()) {
-                    if let Some(res) = perform_op(comp.op, l_val, r_val) {
+                    let (value, detail) = apply_op(
+                        comp.op, l_val, r_val, &left.paths[0], &right.paths[0], record_failures,
+                    );
+                    if let Some(res) = value {
                         result_values.push(res);
                         result_paths.push(left.paths[0].clone());
                     }
+                    result_details.extend(detail);
                 }
             }
         }
     }
-    
+
     if result_values.is_empty() {
         None
     } else {
-        Some(ResolvedValue {
-            values: result_values,
-            paths: result_paths,
-            source_path: format!("({})", comp),
-        })
+        Some((
+            ResolvedValue {
+                values: result_values,
+                paths: result_paths,
+                source_path: format!("({})", comp),
+            },
+            result_details,
+        ))
+    }
+}
+
+/// Resolve one operand of a `ComputedValue`. A nested `ComputedValue` operand recurses through
+/// `evaluate_arithmetic_collecting` so its own pairwise details flatten into the caller's
+/// trace; a plain field/literal operand has no sub-details of its own.
+fn resolve_operand(v: &ValueOrComputed, ctx: &FilterContext) -> Option<(ResolvedValue, Vec<ArithmeticDetail>)> {
+    match v {
+        ValueOrComputed::Computed(inner) => evaluate_arithmetic_collecting(inner, ctx),
+        ValueOrComputed::Conditional(cond) => evaluate_conditional(cond, ctx),
+        _ => Some((resolve_value_or_computed(v, ctx)?, Vec::new())),
     }
 }
 
@@ -224,62 +272,55 @@ fn is_matchable_field(path: &str) -> bool {
 
 /// Evaluate with line matching - returns both result and details
 fn evaluate_with_line_matching(
-    comp: &ComputedValue, 
+    comp: &ComputedValue,
     data: &serde_json::Value,
     left_path: &str,
-    right_path: &str
+    right_path: &str,
+    record_failures: bool,
 ) -> Option<(ResolvedValue, Vec<ArithmeticDetail>)> {
     let left_expanded = expand_aggregate_path(left_path);
     let right_expanded = expand_aggregate_path(right_path);
-    
-    let op_str = op_to_string(comp.op);
+
     let mut all_results = Vec::new();
     let mut all_paths = Vec::new();
     let mut all_details = Vec::new();
-    
+
     for (left_specific_path, left_side) in &left_expanded {
         let (right_specific_path, right_side) = find_matching_right_path(left_side, &right_expanded)?;
-        
+
         // Get parent path (everything before the last segment)
         let left_parent = left_specific_path.rsplit_once('.').map(|(p, _)| p)?;
         let right_parent = right_specific_path.rsplit_once('.').map(|(p, _)| p)?;
-        
+
         // X12 fields are scalars
         if left_side.contains("x12") {
             let temp_ctx = FilterContext::new(data);
             let left_val = resolve_json_path(left_specific_path, &temp_ctx)?.values.first().copied()?;
             let right_val = resolve_json_path(&right_specific_path, &temp_ctx)?.values.first().copied()?;
-            
-            if let Some(result) = perform_op(comp.op, left_val, right_val) {
-                let left_full = format!("{}.{}", left_parent, left_side);
-                let right_full = format!("{}.{}", right_parent, right_side);
-                
+
+            let left_full = format!("{}.{}", left_parent, left_side);
+            let right_full = format!("{}.{}", right_parent, right_side);
+            let (value, detail) = apply_op(comp.op, left_val, right_val, &left_full, &right_full, record_failures);
+            if let Some(result) = value {
                 all_results.push(result);
                 all_paths.push(left_full.clone());
-                all_details.push(ArithmeticDetail {
-                    left_path: left_full,
-                    left_value: left_val,
-                    right_path: right_full,
-                    right_value: right_val,
-                    result,
-                    operation: op_str.to_string(),
-                });
             }
+            all_details.extend(detail);
             continue;
         }
-        
+
         // AH/OU fields - line matching
         // Get raw arrays directly to keep indices aligned (don't use resolve_json_path which filters)
         let lines_key = if left_side.contains("ah") { "ah_lines" } else { "ou_lines" };
-        
+
         let left_lines_arr = get_array_at_path(data, &format!("{}.{}", left_parent, lines_key))?;
         let right_lines_arr = get_array_at_path(data, &format!("{}.{}", right_parent, lines_key))?;
         let left_odds_arr = get_array_at_path(data, left_specific_path)?;
         let right_odds_arr = get_array_at_path(data, &right_specific_path)?;
-        
+
         for (left_idx, left_line_val) in left_lines_arr.iter().enumerate() {
             let left_line = left_line_val.as_f64()?;
-            
+
             // Skip lines that don't exist on the right side
             let right_idx = match right_lines_arr.iter().position(|r| {
                 r.as_f64().map(|rv| (rv - left_line).abs() < 0.001).unwrap_or(false)
@@ -287,7 +328,7 @@ fn evaluate_with_line_matching(
                 Some(idx) => idx,
                 None => continue,
             };
-            
+
             // Get odds at the same index as the line (arrays are aligned)
             let l_val = match left_odds_arr.get(left_idx).and_then(|v| v.as_f64()) {
                 Some(v) if v > 1000.0 => v,  // Must be valid odds > 1.00
@@ -297,25 +338,20 @@ fn evaluate_with_line_matching(
                 Some(v) if v > 1000.0 => v,  // Must be valid odds > 1.00
                 _ => continue,
             };
-            
-            if let Some(result) = perform_op(comp.op, l_val, r_val) {
-                let left_path_with_line = format!("{}.{}[{}]", left_parent, left_side, left_line);
-                let right_path_with_line = format!("{}.{}[{}]", right_parent, right_side, left_line);
-                
+
+            let left_path_with_line = format!("{}.{}[{}]", left_parent, left_side, left_line);
+            let right_path_with_line = format!("{}.{}[{}]", right_parent, right_side, left_line);
+            let (value, detail) = apply_op(
+                comp.op, l_val, r_val, &left_path_with_line, &right_path_with_line, record_failures,
+            );
+            if let Some(result) = value {
                 all_results.push(result);
                 all_paths.push(left_path_with_line.clone());
-                all_details.push(ArithmeticDetail {
-                    left_path: left_path_with_line,
-                    left_value: l_val,
-                    right_path: right_path_with_line,
-                    right_value: r_val,
-                    result,
-                    operation: op_str.to_string(),
-                });
             }
+            all_details.extend(detail);
         }
     }
-    
+
     if all_results.is_empty() {
         None
     } else {
@@ -334,13 +370,86 @@ fn evaluate_with_line_matching(
 // HELPERS
 // ============================================================================
 
-fn perform_op(op: ArithOp, l: f64, r: f64) -> Option<f64> {
+/// Outcome of `perform_op_checked` - distinguishes a usable result from the two ways odds
+/// arithmetic goes wrong, so "checked arithmetic" mode can record *why* a pairing was
+/// dropped instead of just dropping it.
+#[derive(Debug, Clone, Copy)]
+enum OpOutcome {
+    Value(f64),
+    NonFinite,
+    DivByZero,
+}
+
+fn classify(v: f64) -> OpOutcome {
+    if v.is_finite() { OpOutcome::Value(v) } else { OpOutcome::NonFinite }
+}
+
+fn perform_op_checked(op: ArithOp, l: f64, r: f64) -> OpOutcome {
     match op {
-        ArithOp::Add => Some(l + r),
-        ArithOp::Subtract => Some(l - r),
-        ArithOp::Multiply => Some(l * r),
-        ArithOp::Divide => if r != 0.0 { Some(l / r) } else { None },
-        ArithOp::History => None, // Handled separately
+        ArithOp::Add => classify(l + r),
+        ArithOp::Subtract => classify(l - r),
+        ArithOp::Multiply => classify(l * r),
+        ArithOp::Divide => if r != 0.0 { classify(l / r) } else { OpOutcome::DivByZero },
+        ArithOp::History => OpOutcome::NonFinite, // Handled separately, never reached
+        // `powf` saturates to `f64::INFINITY` on overflow rather than panicking; `classify`
+        // then reports it as `NonFinite`, like any other overflow.
+        ArithOp::Power => classify(l.powf(r)),
+        ArithOp::Modulo => if r != 0.0 { classify(l % r) } else { OpOutcome::DivByZero },
+        ArithOp::Min => classify(l.min(r)),
+        ArithOp::Max => classify(l.max(r)),
+        // Unary: `r` is ignored.
+        ArithOp::Abs => classify(l.abs()),
+    }
+}
+
+fn perform_op(op: ArithOp, l: f64, r: f64) -> Option<f64> {
+    match perform_op_checked(op, l, r) {
+        OpOutcome::Value(v) => Some(v),
+        OpOutcome::NonFinite | OpOutcome::DivByZero => None,
+    }
+}
+
+/// Run `op` in checked mode and decide what to report: the value to fold into
+/// `ResolvedValue.values` (only on success), and the `ArithmeticDetail` to record (always on
+/// success; only on failure when `record_failures` is set, matching
+/// `FilterContext::record_arithmetic_failures`'s "skip silently" vs "record + skip" modes).
+fn apply_op(
+    op: ArithOp,
+    l_val: f64,
+    r_val: f64,
+    left_path: &str,
+    right_path: &str,
+    record_failures: bool,
+) -> (Option<f64>, Option<ArithmeticDetail>) {
+    let outcome = perform_op_checked(op, l_val, r_val);
+    let failure_reason = match outcome {
+        OpOutcome::Value(_) => None,
+        OpOutcome::DivByZero => Some("div_by_zero"),
+        OpOutcome::NonFinite => Some("non_finite"),
+    };
+
+    if failure_reason.is_some() && !record_failures {
+        return (None, None);
+    }
+
+    let result = match outcome {
+        OpOutcome::Value(v) => v,
+        OpOutcome::NonFinite | OpOutcome::DivByZero => f64::NAN,
+    };
+
+    let detail = ArithmeticDetail {
+        left_path: left_path.to_string(),
+        left_value: l_val,
+        right_path: right_path.to_string(),
+        right_value: r_val,
+        result,
+        operation: op_to_string(op).to_string(),
+        failure_reason: failure_reason.map(str::to_string),
+    };
+
+    match outcome {
+        OpOutcome::Value(v) => (Some(v), Some(detail)),
+        OpOutcome::NonFinite | OpOutcome::DivByZero => (None, Some(detail)),
     }
 }
 
@@ -351,6 +460,11 @@ fn op_to_string(op: ArithOp) -> &'static str {
         ArithOp::Multiply => "multiply",
         ArithOp::Divide => "divide",
         ArithOp::History => "history",
+        ArithOp::Power => "power",
+        ArithOp::Modulo => "modulo",
+        ArithOp::Min => "min",
+        ArithOp::Max => "max",
+        ArithOp::Abs => "abs",
     }
 }
 
@@ -411,72 +525,138 @@ fn find_matching_right_path(left_side: &str, right_expanded: &[(String, String)]
         .map(|(p, s)| (p.clone(), s.clone()))
 }
 
-/// Evaluate history operator.
-/// 
-/// Logic:
-/// - left: field path(s) like "bookmakers.Pinnacle.x12_h"
-/// - right: max age in milliseconds (e.g., 60000 = within last 60 seconds)
-/// 
-/// Returns the OLDEST historical value(s) that is still within the time window.
-/// This maximizes trend detection - comparing current to the oldest recent data.
-fn evaluate_history(left: ResolvedValue, right: ResolvedValue, ctx: &FilterContext) -> Option<ResolvedValue> {
-    // Right operand is the maximum age in milliseconds
-    let max_age_ms = right.values.first().copied()? as i64;
-    
+/// Lookback argument for the `history` operator, resolved from `comp.right`.
+#[derive(Clone, Copy)]
+enum HistoryLookback {
+    /// `{"samples": N}` - the reading N samples before the most recent one.
+    Count(usize),
+    /// A plain number - the newest reading at-or-before `now - max_age_ms`.
+    MaxAge(i64),
+}
+
+/// Evaluate the history operator.
+///
+/// - `left` resolves to the current field path(s), e.g. "bookmakers.Pinnacle.x12_h".
+/// - `right` resolves to a lookback: either `{"samples": N}` for N samples back, or a
+///   plain number of milliseconds for the nearest sample at-or-before `now - delta`.
+///
+/// Each field path in `left` gets its own ring buffer (per-line AH/OU fields included,
+/// since their path already carries the `[line]` suffix), so an empty/short buffer for
+/// one path just drops that path from the result rather than failing the whole call.
+fn evaluate_history(left: ResolvedValue, right: &ValueOrComputed, ctx: &FilterContext) -> Option<ResolvedValue> {
+    let provider = ctx.history_provider?;
+
+    let lookback = if let ValueOrComputed::Literal(Value::Object(map)) = right {
+        let samples = map.get("samples")?.as_u64()? as usize;
+        HistoryLookback::Count(samples)
+    } else {
+        let resolved = resolve_value_or_computed(right, ctx)?;
+        HistoryLookback::MaxAge(resolved.values.first().copied()? as i64)
+    };
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
     let mut result_values = Vec::new();
     let mut result_paths = Vec::new();
-    
+
     for path in &left.paths {
-        // Parse: "bookmakers.Pinnacle.x12_h" -> bookmaker="Pinnacle", field="x12_h"
-        let Some((bookmaker, field)) = parse_bookmaker_path(path) else { continue };
-        
-        // Get oldest historical snapshot within max_age_ms
-        let Some(provider) = ctx.history_provider else { continue };
-        let Some(snapshot) = provider.get_snapshot(bookmaker, max_age_ms) else { continue };
-        
-        // Resolve the field in the historical snapshot
-        let temp_ctx = FilterContext::new(&snapshot);
-        let Some(resolved) = resolve_json_path(field, &temp_ctx) else { continue };
-        
-        // Match values by line if applicable
-        for (val, p) in resolved.values.iter().zip(resolved.paths.iter()) {
-            // If original path has a line bracket, only include matching lines
-            if let Some(orig_line) = extract_line_from_path_str(path) {
-                if let Some(res_line) = extract_line_from_path_str(p) {
-                    // Only include if lines match (with tolerance for floating point)
-                    if (orig_line - res_line).abs() > 0.001 {
-                        continue;
-                    }
-                } else {
-                    // Historical path doesn't have a line bracket, skip it
-                    // This can happen if historical data structure is different
-                    continue;
-                }
-            }
+        let sample = match lookback {
+            HistoryLookback::Count(n) => provider.sample_count_back(path, n),
+            HistoryLookback::MaxAge(max_age_ms) => provider.sample_at_or_before(path, now_ms, max_age_ms),
+        };
+        let Some(value) = sample else { continue };
 
-            result_values.push(*val);
-            
-            let suffix = if let Some(ts) = snapshot.get("timestamp").and_then(|t| t.as_i64()) {
-                format!("@{}ms(t:{})", max_age_ms, ts)
-            } else {
-                format!("@{}ms", max_age_ms)
-            };
-            result_paths.push(format!("{}{}", path, suffix));
-        }
+        result_values.push(value);
+        let suffix = match lookback {
+            HistoryLookback::Count(n) => format!("@{}samples", n),
+            HistoryLookback::MaxAge(ms) => format!("@{}ms", ms),
+        };
+        result_paths.push(format!("{}{}", path, suffix));
     }
-    
+
     if result_values.is_empty() {
         None
     } else {
         Some(ResolvedValue {
             values: result_values,
             paths: result_paths,
-            source_path: format!("history({}, {})", left.source_path, max_age_ms),
+            source_path: format!("history({})", left.source_path),
         })
     }
 }
 
-fn parse_bookmaker_path(path: &str) -> Option<(&str, &str)> {
+/// Evaluate a `Conditional`/piecewise expression: the predicate field is tested per-line, and
+/// each line independently picks `then_expr` or `else_expr`. Reuses the same `[line]` matching
+/// and `> 1000.0` valid-odds guard `evaluate_with_line_matching` applies to AH/OU fields, since
+/// piecewise odds metrics are built out of the same line-aligned arrays. Returns one
+/// `ArithmeticDetail` per surviving line, tagged with which branch fired.
+pub fn evaluate_conditional(
+    cond: &ConditionalValue,
+    ctx: &FilterContext,
+) -> Option<(ResolvedValue, Vec<ArithmeticDetail>)> {
+    let predicate_field = resolve_field(&cond.predicate.field, ctx)?;
+    let threshold = cond.predicate.value.as_f64()?;
+
+    let then_branch = resolve_value_or_computed(&cond.then_expr, ctx);
+    let else_branch = resolve_value_or_computed(&cond.else_expr, ctx);
+
+    let mut result_values = Vec::new();
+    let mut result_paths = Vec::new();
+    let mut result_details = Vec::new();
+
+    for (idx, &p_val) in predicate_field.values.iter().enumerate() {
+        if p_val <= 1000.0 {
+            continue; // Must be valid odds > 1.00, same guard as AH/OU line matching
+        }
+        let p_path = &predicate_field.paths[idx];
+        let line = extract_line_from_path_str(p_path);
+
+        let branch_matched = compare(p_val, cond.predicate.op, threshold);
+        let branch = if branch_matched { &then_branch } else { &else_branch };
+        let Some(branch) = branch else { continue };
+
+        // Align this predicate line with the same line in the chosen branch; for scalar
+        // (non-line) fields fall back to matching by index.
+        let branch_idx = match line {
+            Some(l) => branch.paths.iter().position(|bp| {
+                extract_line_from_path_str(bp).map(|bl| (bl - l).abs() < 0.001).unwrap_or(false)
+            }),
+            None => Some(idx).filter(|&i| i < branch.values.len()),
+        };
+        let Some(branch_idx) = branch_idx else { continue };
+
+        let b_val = branch.values[branch_idx];
+        if b_val <= 1000.0 {
+            continue; // The branch value must also be valid odds
+        }
+
+        result_values.push(b_val);
+        result_paths.push(p_path.clone());
+        result_details.push(ArithmeticDetail {
+            left_path: p_path.clone(),
+            left_value: p_val,
+            right_path: branch.paths[branch_idx].clone(),
+            right_value: b_val,
+            result: b_val,
+            operation: if branch_matched { "conditional_then" } else { "conditional_else" }.to_string(),
+            failure_reason: None,
+        });
+    }
+
+    if result_values.is_empty() {
+        None
+    } else {
+        Some((
+            ResolvedValue {
+                values: result_values,
+                paths: result_paths,
+                source_path: format!("({})", cond),
+            },
+            result_details,
+        ))
+    }
+}
+
+pub(crate) fn parse_bookmaker_path(path: &str) -> Option<(&str, &str)> {
     let parts: Vec<&str> = path.split('.').collect();
     if parts.len() >= 3 && parts[0] == "bookmakers" {
         // parts[1] is bookmaker
@@ -4,9 +4,13 @@ use std::collections::HashMap;
 use super::types::ResolvedValue;
 
 pub trait HistoryProvider {
-    /// Get the oldest historical snapshot for a bookmaker that is still within `max_age_ms`.
-    /// Returns None if no snapshot exists within the time window.
-    fn get_snapshot(&self, bookmaker: &str, max_age_ms: i64) -> Option<Value>;
+    /// The reading for `field_path` `count` samples before the most recent one (0 = most
+    /// recent). Returns `None` if the buffer doesn't reach back that far.
+    fn sample_count_back(&self, field_path: &str, count: usize) -> Option<f64>;
+
+    /// The newest reading for `field_path` at-or-before `now_ms - max_age_ms`. Returns
+    /// `None` if the buffer doesn't reach back that far.
+    fn sample_at_or_before(&self, field_path: &str, now_ms: i64, max_age_ms: i64) -> Option<f64>;
 }
 
 /// Details about an operand in a computation
@@ -62,6 +66,22 @@ pub struct ArithmeticDetail {
     pub result: f64,
     /// The operation performed
     pub operation: String,
+    /// Set when `perform_op` produced a non-finite value or a guarded division/modulo by
+    /// zero instead of a usable result (only populated when
+    /// `FilterContext::record_arithmetic_failures` is on - otherwise the pair is dropped
+    /// silently and no detail is recorded at all).
+    pub failure_reason: Option<String>,
+}
+
+/// Opt-in evaluation behaviors for a `FilterContext`, off by default so existing filter
+/// behavior is unchanged unless a caller (ultimately, `FILTER_INTERPOLATE_LINES`/
+/// `FILTER_RECORD_ARITHMETIC_FAILURES` in `Config`) opts in.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FilterOptions {
+    /// See `FilterContext::interpolate_lines`.
+    pub interpolate_lines: bool,
+    /// See `FilterContext::record_arithmetic_failures`.
+    pub record_arithmetic_failures: bool,
 }
 
 pub struct FilterContext<'a> {
@@ -74,26 +94,47 @@ pub struct FilterContext<'a> {
     pub last_arithmetic_result: Option<ArithmeticResult>,
     /// Provider for historical data lookup
     pub history_provider: Option<&'a dyn HistoryProvider>,
+    /// When true, `ou_o[2.75]`-style line lookups that miss an exact stored line fall back
+    /// to interpolating between the two nearest neighbors instead of failing. Off by default
+    /// so exact-match behavior is unchanged unless a caller opts in.
+    pub interpolate_lines: bool,
+    /// When true, a pairing that `perform_op` rejects (NaN/infinity, or a guarded
+    /// division/modulo by zero) is recorded as an `ArithmeticDetail` with `failure_reason`
+    /// set instead of being dropped with no trace. Off by default so existing "skip
+    /// silently" behavior is unchanged unless a caller opts in.
+    pub record_arithmetic_failures: bool,
 }
 
 impl<'a> FilterContext<'a> {
     pub fn new(data: &'a Value) -> Self {
+        Self::with_options(data, FilterOptions::default())
+    }
+
+    pub fn with_options(data: &'a Value, options: FilterOptions) -> Self {
         Self {
             data,
             vars: HashMap::new(),
             match_traces: Vec::new(),
             last_arithmetic_result: None,
             history_provider: None,
+            interpolate_lines: options.interpolate_lines,
+            record_arithmetic_failures: options.record_arithmetic_failures,
         }
     }
 
     pub fn with_history(data: &'a Value, provider: &'a dyn HistoryProvider) -> Self {
+        Self::with_history_and_options(data, provider, FilterOptions::default())
+    }
+
+    pub fn with_history_and_options(data: &'a Value, provider: &'a dyn HistoryProvider, options: FilterOptions) -> Self {
         Self {
             data,
             vars: HashMap::new(),
             match_traces: Vec::new(),
             last_arithmetic_result: None,
             history_provider: Some(provider),
+            interpolate_lines: options.interpolate_lines,
+            record_arithmetic_failures: options.record_arithmetic_failures,
         }
     }
 
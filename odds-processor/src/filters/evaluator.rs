@@ -84,7 +84,7 @@ fn resolve_field_with_details(field: &FieldPath, ctx: &mut FilterContext) -> Opt
                 let var_name = &s[1..];
                 ctx.vars.get(var_name).cloned()
             } else {
-                resolve_json_path(ctx.data, s)
+                resolve_json_path(ctx.data, s, ctx.interpolate_lines)
             }
         },
         FieldPath::Computed(comp) => evaluate_arithmetic_with_ctx(comp, ctx),
@@ -162,7 +162,7 @@ fn evaluate_compare(cmp: &CompareExpr, ctx: &mut FilterContext) -> bool {
     has_match
 }
 
-fn compare(l: f64, op: CompareOp, r: f64) -> bool {
+pub(crate) fn compare(l: f64, op: CompareOp, r: f64) -> bool {
     match op {
         CompareOp::Eq => (l - r).abs() < 0.00001,
         CompareOp::Neq => (l - r).abs() >= 0.00001,
@@ -203,10 +203,13 @@ fn evaluate_vector(vec: &VectorExpr, ctx: &mut FilterContext) -> bool {
 
     // Check if this is a per-line operation
     match vec.function {
-        VectorOp::AvgPerLine | VectorOp::MaxPerLine | VectorOp::MinPerLine | 
+        VectorOp::AvgPerLine | VectorOp::MaxPerLine | VectorOp::MinPerLine |
         VectorOp::SumPerLine | VectorOp::CountPerLine => {
             return evaluate_vector_per_line(vec.function, &sources, &vec.as_var, ctx);
         },
+        VectorOp::ArbMargin => {
+            return evaluate_vector_arb_margin(&sources, &vec.as_var, ctx);
+        },
         _ => {}
     }
 
@@ -328,6 +331,86 @@ fn evaluate_vector_per_line(
     true
 }
 
+/// Per-line implied-probability margin across a list of complementary outcomes (e.g. the
+/// home/away legs of an AH line, or over/under of an OU line): groups by line the same way
+/// `evaluate_vector_per_line` does, takes the best (max) odds per listed outcome within each
+/// line group, and sums their implied probabilities (`1000.0 / odds`) minus 1.0. A negative
+/// margin means the best legs across books form an arbitrage; positive is the bookmakers'
+/// overround.
+fn evaluate_vector_arb_margin(
+    sources: &[FieldPath],
+    as_var: &Option<String>,
+    ctx: &mut FilterContext,
+) -> bool {
+    let resolved: Vec<ResolvedValue> = sources.iter()
+        .filter_map(|s| resolve_field(s, ctx))
+        .collect();
+
+    if resolved.is_empty() {
+        return false;
+    }
+
+    // Group by line: HashMap<line_value, Vec<(source_index, odds_value, path)>>
+    let mut by_line: HashMap<i64, Vec<(usize, f64, String)>> = HashMap::new();
+
+    for (source_idx, rv) in resolved.iter().enumerate() {
+        for (val, path) in rv.values.iter().zip(rv.paths.iter()) {
+            if *val <= 1000.0 {
+                continue;
+            }
+            if let Some(line) = extract_line_from_path(path) {
+                let line_key = (line * 1000.0).round() as i64;
+                by_line.entry(line_key).or_default().push((source_idx, *val, path.clone()));
+            }
+        }
+    }
+
+    // Only keep lines where every listed outcome is present (same intersection check as
+    // evaluate_vector_per_line).
+    let source_count = resolved.len();
+    let intersection: Vec<_> = by_line.into_iter()
+        .filter(|(_, vals)| vals.len() >= source_count)
+        .collect();
+
+    if intersection.is_empty() {
+        return false;
+    }
+
+    let mut sorted: Vec<_> = intersection.into_iter().collect();
+    sorted.sort_by_key(|(line_key, _)| *line_key);
+
+    let mut result_values = Vec::new();
+    let mut result_paths = Vec::new();
+
+    for (line_key, vals) in sorted {
+        let line = line_key as f64 / 1000.0;
+
+        // Best (max) odds per outcome within this line group.
+        let mut best_per_source: HashMap<usize, f64> = HashMap::new();
+        for (source_idx, val, _) in &vals {
+            best_per_source.entry(*source_idx)
+                .and_modify(|best| if *val > *best { *best = *val })
+                .or_insert(*val);
+        }
+
+        let margin: f64 = best_per_source.values().map(|odds| 1000.0 / odds).sum::<f64>() - 1.0;
+
+        result_values.push(margin);
+        let field_base = extract_field_base(&vals[0].2);
+        result_paths.push(format!("{}_arb_margin[{}]", field_base, line));
+    }
+
+    if let Some(var_name) = as_var {
+        ctx.vars.insert(var_name.clone(), ResolvedValue {
+            values: result_values,
+            paths: result_paths,
+            source_path: format!("${}", var_name),
+        });
+    }
+
+    true
+}
+
 /// Extract line value from path like "bookmakers.Monaco.ah_h[-0.5]"
 fn extract_line_from_path(path: &str) -> Option<f64> {
     let start = path.rfind('[')?;
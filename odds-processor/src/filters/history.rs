@@ -0,0 +1,60 @@
+// Time-series retention backing the filter DSL's `history` operator (`ArithOp::History`).
+// Each fixture keeps a per-field-path ring of raw `(timestamp_ms, value)` samples, one per
+// exact field path the filter DSL resolves to (e.g. `"bookmakers.Pinnacle.ah_h[-0.5]"`).
+// `Cache::apply_update` records a sample for every x12/AH/OU field the updated bookmaker
+// currently holds (not only the fields the update itself carried), and
+// `evaluate_history` reads them back for both lookback modes (`N` samples back, or nearest
+// sample at-or-before `now - max_age_ms`), since per-field-path keys keep AH/OU lines
+// independent for free.
+use super::context::HistoryProvider;
+use std::collections::{HashMap, VecDeque};
+
+/// How many samples to retain per field path before the oldest is evicted.
+const MAX_SAMPLES_PER_FIELD: usize = 500;
+
+/// Samples older than this are evicted opportunistically on push, independent of count.
+const MAX_SAMPLE_AGE_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Default)]
+pub struct FixtureHistory {
+    /// Oldest-first per exact field path (e.g. `"bookmakers.Pinnacle.ah_h[-0.5]"`).
+    samples: HashMap<String, VecDeque<(i64, f64)>>,
+}
+
+impl FixtureHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one reading for `field_path` at `timestamp_ms`, evicting samples that have
+    /// aged out or overflowed the ring.
+    pub fn record_sample(&mut self, field_path: String, timestamp_ms: i64, value: f64) {
+        let ring = self.samples.entry(field_path).or_default();
+        while let Some(&(ts, _)) = ring.front() {
+            if timestamp_ms - ts > MAX_SAMPLE_AGE_MS {
+                ring.pop_front();
+            } else {
+                break;
+            }
+        }
+        if ring.len() >= MAX_SAMPLES_PER_FIELD {
+            ring.pop_front();
+        }
+        ring.push_back((timestamp_ms, value));
+    }
+}
+
+impl HistoryProvider for FixtureHistory {
+    fn sample_count_back(&self, field_path: &str, count: usize) -> Option<f64> {
+        let ring = self.samples.get(field_path)?;
+        let idx = ring.len().checked_sub(1 + count)?;
+        ring.get(idx).map(|&(_, v)| v)
+    }
+
+    fn sample_at_or_before(&self, field_path: &str, now_ms: i64, max_age_ms: i64) -> Option<f64> {
+        let ring = self.samples.get(field_path)?;
+        let cutoff = now_ms - max_age_ms;
+        // Newest-first scan so we return the closest (newest) sample still at-or-before cutoff.
+        ring.iter().rev().find(|&&(ts, _)| ts <= cutoff).map(|&(_, v)| v)
+    }
+}
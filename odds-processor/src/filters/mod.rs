@@ -3,6 +3,8 @@ mod context;
 pub mod path;
 pub mod arithmetic;
 mod evaluator;
+pub mod history;
 pub use types::*;
-pub use context::{FilterContext, MatchTrace, HistoryProvider};
+pub use context::{FilterContext, FilterOptions, MatchTrace, HistoryProvider};
 pub use evaluator::evaluate;
+pub use history::FixtureHistory;
@@ -1,7 +1,7 @@
 use serde_json::Value;
 use super::types::{FieldPath, ValueOrComputed, ResolvedValue};
 use super::context::FilterContext;
-use super::arithmetic::evaluate_arithmetic;
+use super::arithmetic::{evaluate_arithmetic, evaluate_conditional};
 
 // ============================================================================
 // PUBLIC API
@@ -14,7 +14,7 @@ pub fn resolve_field(path: &FieldPath, ctx: &FilterContext) -> Option<ResolvedVa
                 let var_name = &s[1..];
                 return ctx.vars.get(var_name).cloned();
             }
-            resolve_json_path(ctx.data, s)
+            resolve_json_path(ctx.data, s, ctx.interpolate_lines)
         },
         FieldPath::Computed(comp) => {
             evaluate_arithmetic(comp, ctx)
@@ -44,6 +44,7 @@ pub fn resolve_value_or_computed(v: &ValueOrComputed, ctx: &FilterContext) -> Op
             }
         },
         ValueOrComputed::Computed(comp) => evaluate_arithmetic(comp, ctx),
+        ValueOrComputed::Conditional(cond) => evaluate_conditional(cond, ctx).map(|(result, _)| result),
         ValueOrComputed::Field(path) => resolve_field(path, ctx),
     }
 }
@@ -64,19 +65,28 @@ pub fn extract_field_path(v: &ValueOrComputed) -> Option<String> {
 // CORE PATH RESOLUTION
 // ============================================================================
 
-pub fn resolve_json_path(data: &Value, path: &str) -> Option<ResolvedValue> {
+pub fn resolve_json_path(data: &Value, path: &str, interpolate: bool) -> Option<ResolvedValue> {
     let parts: Vec<&str> = path.split('.').collect();
     let mut current = data;
     let mut current_path = String::new();
-    
+
     for (idx, part) in parts.iter().enumerate() {
         if idx > 0 {
             current_path.push('.');
         }
-        
+
         // Handle bracket syntax: ou_o[2.5], ah_h[-0.5]
         if let Some(start_bracket) = part.find('[') {
-            current = resolve_line_access(data, current, &parts, idx, part, start_bracket, &mut current_path)?;
+            let path_before_bracket = current_path.clone();
+            match resolve_line_access(data, current, &parts, idx, part, start_bracket, &mut current_path) {
+                Some(value) => current = value,
+                None if interpolate && idx == parts.len() - 1 => {
+                    return resolve_interpolated_line(
+                        data, &parts, idx, part, start_bracket, &path_before_bracket, path,
+                    );
+                },
+                None => return None,
+            }
         } else if let Some(field_value) = current.get(part) {
             current = field_value;
             current_path.push_str(part);
@@ -177,6 +187,81 @@ fn resolve_line_access<'a>(
     current.get(line_idx)
 }
 
+/// Synthesizes a value for `key[line_value]` when no stored line matches exactly, by
+/// interpolating between the two nearest neighboring lines in probability space. Only called
+/// as a fallback when `resolve_line_access` fails to find an exact match and the caller has
+/// opted in via `FilterContext::interpolate_lines`; never extrapolates beyond the stored
+/// range. The synthesized path is suffixed with `~interp` so callers can tell it apart from a
+/// real quote.
+fn resolve_interpolated_line(
+    data: &Value,
+    parts: &[&str],
+    idx: usize,
+    part: &str,
+    start_bracket: usize,
+    path_before_bracket: &str,
+    source_path: &str,
+) -> Option<ResolvedValue> {
+    let key = &part[0..start_bracket];
+    let end_bracket = part.find(']')?;
+    let line_str = &part[start_bracket+1..end_bracket];
+    let line_value: f64 = line_str.parse().ok()?;
+
+    let mut parent = data;
+    for i in 0..idx {
+        parent = parent.get(parts[i])?;
+    }
+
+    let price = interpolate_line(parent, key, line_value)?;
+    let path = format!("{}{}[{}~interp]", path_before_bracket, key, line_str);
+
+    Some(ResolvedValue {
+        values: vec![price],
+        paths: vec![path],
+        source_path: source_path.to_string(),
+    })
+}
+
+/// Interpolates a missing line's price from its two nearest stored neighbors, working in
+/// implied-probability space: `p = 1000.0 / decimal_price` (matching the scale the rest of
+/// the filter DSL assumes), linearly blended by line distance, then converted back. Returns
+/// `None` if either neighbor is missing - no extrapolation beyond the stored range.
+fn interpolate_line(parent: &Value, key: &str, requested: f64) -> Option<f64> {
+    let lines_key = if key.contains("ah") { "ah_lines" } else { "ou_lines" };
+    let lines = parent.get(lines_key)?.as_array()?;
+    let values = parent.get(key)?.as_array()?;
+
+    let mut lower: Option<(f64, f64)> = None; // (line, price)
+    let mut upper: Option<(f64, f64)> = None;
+
+    for (i, line_val) in lines.iter().enumerate() {
+        let line = line_val.as_f64()?;
+        let Some(val) = values.get(i).and_then(|v| v.as_f64()) else { continue };
+        if val <= 0.0 {
+            continue;
+        }
+
+        if line < requested && lower.map(|(l, _)| line > l).unwrap_or(true) {
+            lower = Some((line, val));
+        } else if line > requested && upper.map(|(l, _)| line < l).unwrap_or(true) {
+            upper = Some((line, val));
+        }
+    }
+
+    let (lower_line, lower_price) = lower?;
+    let (upper_line, upper_price) = upper?;
+
+    let w = (requested - lower_line) / (upper_line - lower_line);
+    let p_lower = 1000.0 / lower_price;
+    let p_upper = 1000.0 / upper_price;
+    let p_interp = p_lower + w * (p_upper - p_lower);
+    if p_interp <= 0.0 {
+        return None;
+    }
+
+    Some(1000.0 / p_interp)
+}
+
 // ============================================================================
 // AGGREGATE RESOLUTION (.ou, .ah, .x12, .fair_ou, .fair_ah, .fair_x12)
 // ============================================================================
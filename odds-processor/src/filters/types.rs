@@ -37,6 +37,7 @@ pub enum FieldPath {
 #[serde(untagged)]
 pub enum ValueOrComputed {
     Computed(Box<ComputedValue>),
+    Conditional(Box<ConditionalValue>),
     Field(Box<FieldPath>),
     Literal(Value),
 }
@@ -48,14 +49,21 @@ impl<'de> Deserialize<'de> for ValueOrComputed {
         D: serde::Deserializer<'de>,
     {
         let value = Value::deserialize(deserializer)?;
-        
+
         // Try to deserialize as ComputedValue first (has "op" field)
         if value.is_object() && value.get("op").is_some() {
             return serde_json::from_value::<ComputedValue>(value.clone())
                 .map(|c| ValueOrComputed::Computed(Box::new(c)))
                 .map_err(serde::de::Error::custom);
         }
-        
+
+        // Piecewise: {"if": {field, op, value}, "then": ..., "else": ...}
+        if value.is_object() && value.get("if").is_some() {
+            return serde_json::from_value::<ConditionalValue>(value.clone())
+                .map(|c| ValueOrComputed::Conditional(Box::new(c)))
+                .map_err(serde::de::Error::custom);
+        }
+
         // If it's a string, it could be a field path or a literal
         if let Some(s) = value.as_str() {
             // If it contains field path indicators, treat as field
@@ -63,7 +71,7 @@ impl<'de> Deserialize<'de> for ValueOrComputed {
                 return Ok(ValueOrComputed::Field(Box::new(FieldPath::Simple(s.to_string()))));
             }
         }
-        
+
         // Otherwise, treat as literal
         Ok(ValueOrComputed::Literal(value))
     }
@@ -79,7 +87,32 @@ pub struct ComputedValue {
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ArithOp {
-    Divide, Multiply, Add, Subtract, History
+    Divide, Multiply, Add, Subtract, History,
+    Power, Modulo, Min, Max,
+    /// Unary: only `left` is used, `right` is ignored.
+    Abs,
+}
+
+/// Piecewise arithmetic: `predicate` is tested per-line, and each line is routed
+/// independently to `then_expr` or `else_expr`, so a single filter expression can switch
+/// formula based on a threshold (e.g. "if ou_o is below 2.00 use one margin formula,
+/// otherwise another") instead of bolting the conditional onto the surrounding filter layer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalValue {
+    #[serde(rename = "if")]
+    pub predicate: ConditionalPredicate,
+    #[serde(rename = "then")]
+    pub then_expr: ValueOrComputed,
+    #[serde(rename = "else")]
+    pub else_expr: ValueOrComputed,
+}
+
+/// A predicate used by `ConditionalValue`: compares a field to a constant threshold.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalPredicate {
+    pub field: FieldPath,
+    pub op: CompareOp,
+    pub value: Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +141,10 @@ pub enum VectorOp {
     MinPerLine,
     SumPerLine,
     CountPerLine,
+    /// Per-line implied-probability margin across a list of complementary outcomes (e.g.
+    /// home/away, over/under): best odds per outcome within the line group, summed as
+    /// implied probability minus 1.0. Negative means arbitrage, positive means overround.
+    ArbMargin,
 }
 
 // Display implementations for trace formatting
@@ -128,6 +165,11 @@ impl std::fmt::Display for ComputedValue {
             ArithOp::Add => "+",
             ArithOp::Subtract => "-",
             ArithOp::History => "@",
+            ArithOp::Power => "^",
+            ArithOp::Modulo => "%",
+            ArithOp::Min => "min",
+            ArithOp::Max => "max",
+            ArithOp::Abs => "abs",
         };
         write!(f, "({} {} {})", self.left, op_str, self.right)
     }
@@ -137,12 +179,22 @@ impl std::fmt::Display for ValueOrComputed {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ValueOrComputed::Computed(c) => write!(f, "{}", c),
+            ValueOrComputed::Conditional(c) => write!(f, "{}", c),
             ValueOrComputed::Field(p) => write!(f, "{}", p),
             ValueOrComputed::Literal(v) => write!(f, "{}", v),
         }
     }
 }
 
+impl std::fmt::Display for ConditionalValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f, "(if {} {} {} then {} else {})",
+            self.predicate.field, self.predicate.op, self.predicate.value, self.then_expr, self.else_expr,
+        )
+    }
+}
+
 impl std::fmt::Display for CompareOp {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
@@ -1,19 +1,28 @@
+mod arbitrage;
 mod config;
 mod cache;
 mod calculations;
+mod candles;
 mod network;
 mod types;
 mod db;
 mod filters;
+mod sink;
+mod trace_sink;
 
 use axum::{routing::get, Router};
 use cache::Cache;
+use calculations::fair_odds::FairOddsMethod;
+use candles::{CandleAggregator, CandleInterval, CandleKey};
 use chrono;
 use config::Config;
+use filters::FilterOptions;
+use network::fanout::{Fanout, InProcessFanout, RedisFanout};
 use network::stream::{get_stats, ws_handler, AppState, SharedState};
 use network::tcp;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, RwLock};
 use tower_http::cors::CorsLayer;
 use tower_http::services::ServeDir;
@@ -46,7 +55,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (ws_tx, _) = broadcast::channel::<WsMessage>(1000);
 
     // Create cache
-    let cache = Arc::new(RwLock::new(Cache::new(config.max_fixtures)));
+    let fair_odds_method = FairOddsMethod::from_env_str(&config.fair_odds_method);
+    let cache = Arc::new(RwLock::new(Cache::new(config.max_fixtures, fair_odds_method)));
 
     // Hydrate cache from database
     match db::fetch_initial_odds(&pool, config.max_fixtures as i64).await {
@@ -62,26 +72,116 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    // Warm-start from the raw sink, if enabled: it carries every bookmaker's own latest
+    // update rather than just what's reflected in football_odds, so it can fill in fixtures
+    // the primary hydration above missed.
+    if config.raw_sink_warm_start {
+        match sink::fetch_latest(&pool, config.max_fixtures as i64).await {
+            Ok(updates) => {
+                let mut cache_guard = cache.write().await;
+                for update in updates {
+                    cache_guard.apply_update(update);
+                }
+                info!("✅ Cache warm-started from raw sink, now {} fixtures", cache_guard.len());
+            }
+            Err(e) => {
+                warn!("Failed to warm-start cache from raw sink: {}", e);
+            }
+        }
+    }
+
+    // Fanout: in-process by default, or Redis pub/sub so multiple instances behind a load
+    // balancer share one stream of ingested updates instead of each only seeing its own.
+    let fanout: Arc<dyn Fanout> = match config.fanout_backend.as_str() {
+        "redis" => {
+            match RedisFanout::new(&config.redis_url, config.redis_fanout_channel.clone(), config.node_id.clone(), ws_tx.clone()).await {
+                Ok(f) => {
+                    info!("📡 Redis fanout connected ({})", config.redis_url);
+                    Arc::new(f)
+                }
+                Err(e) => {
+                    warn!("Failed to connect Redis fanout, falling back to in-process: {}", e);
+                    Arc::new(InProcessFanout::new(ws_tx.clone()))
+                }
+            }
+        }
+        _ => Arc::new(InProcessFanout::new(ws_tx.clone())),
+    };
+
+    // Filter trace sink, if enabled: persists matched filter traces reported by each
+    // client's WS task. Fed through an mpsc channel since traces originate per-connection
+    // rather than off the single shared update broadcast channel.
+    let trace_tx = if config.trace_sink_enabled {
+        let (trace_tx, trace_rx) = tokio::sync::mpsc::unbounded_channel();
+        trace_sink::spawn(
+            pool.clone(),
+            trace_rx,
+            config.trace_sink_batch_size,
+            Duration::from_secs(config.trace_sink_flush_interval_secs),
+        );
+        Some(trace_tx)
+    } else {
+        None
+    };
+
     // Create app state
-    let state: SharedState = Arc::new(AppState::new(ws_tx.clone(), cache.clone()));
+    let tcp_stats = Arc::new(tcp::TcpStats::default());
+    let state: SharedState = Arc::new(AppState::new(
+        ws_tx.clone(),
+        cache.clone(),
+        pool.clone(),
+        config.normalized_odds_enabled,
+        FilterOptions {
+            interpolate_lines: config.filter_interpolate_lines,
+            record_arithmetic_failures: config.filter_record_arithmetic_failures,
+        },
+        tcp_stats.clone(),
+        fanout.clone(),
+        trace_tx,
+    ));
+
+    // Live candle aggregation (1m buckets); backfill for older history is a separate, on-demand pass.
+    let candle_aggregator = Arc::new(RwLock::new(CandleAggregator::new()));
+    let candle_pool = pool.clone();
 
     // Start database listener
     db::start_db_listener(pool.clone(), update_tx.clone());
 
+    // Append-only raw sink, if enabled. Subscribes to the same broadcast channel as
+    // everything else so it never adds latency to the live WS path.
+    if config.raw_sink_enabled {
+        sink::spawn(
+            pool.clone(),
+            update_tx.subscribe(),
+            config.raw_sink_batch_size,
+            Duration::from_secs(config.raw_sink_flush_interval_secs),
+        );
+    }
+
     // Start TCP listener for odds-engine
     let tcp_tx = update_tx.clone();
+    let tcp_listener_stats = tcp_stats.clone();
     tokio::spawn(async move {
-        if let Err(e) = tcp::start_tcp_listener(config.tcp_port, tcp_tx).await {
+        if let Err(e) = tcp::start_tcp_listener(config.tcp_port, tcp_tx, tcp_listener_stats).await {
             tracing::error!("TCP listener error: {}", e);
         }
     });
 
     // Process updates
     let process_cache = cache.clone();
-    let process_ws_tx = ws_tx.clone();
+    let process_fanout = fanout.clone();
     let process_state = state.clone();
+    let normalize_odds_enabled = config.normalized_odds_enabled;
+    let arb_enabled = config.arb_enabled;
+    let arb_config = arbitrage::ArbConfig {
+        value_bet_edge: config.arb_value_bet_edge,
+        min_scan_delay_secs: config.arb_scan_min_delay_secs as i64,
+        max_scan_delay_secs: config.arb_scan_max_delay_secs as i64,
+        fair_odds_method,
+    };
+    let mut arb_scanner = arbitrage::ArbScanner::new();
     let mut update_rx = update_tx.subscribe();
-    
+
     tokio::spawn(async move {
         let mut updates_count: u64 = 0;
         let mut last_updates_count: u64 = 0;
@@ -96,9 +196,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 cache.apply_update(update.clone()).cloned()
             };
 
+            // Feed the live candle aggregator; persist any bucket that just closed.
+            {
+                let closed = {
+                    let mut aggregator = candle_aggregator.write().await;
+                    record_candle_ticks(&mut aggregator, &update)
+                };
+                if !closed.is_empty() {
+                    let pool = candle_pool.clone();
+                    tokio::spawn(async move {
+                        for (key, candle) in closed {
+                            if let Err(e) = candles::persist_candle(&pool, &key, &candle).await {
+                                tracing::error!("Failed to persist candle: {}", e);
+                            }
+                        }
+                    });
+                }
+            }
+
             // Broadcast to WebSocket clients
             if let Some(fixture) = fixture {
-                let ws_msg = WsMessage {
+                let mut ws_msg = WsMessage {
                     msg_type: "odds_update".to_string(),
                     fixture_id: fixture.fixture_id,
                     timestamp: update.timestamp,
@@ -106,9 +224,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     end: chrono::Utc::now().timestamp_millis(),
                     bookmakers: fixture.bookmakers.clone(),
                     filter_matches: None,
+                    normalized: None,
+                    sequence: fixture.version,
+                    arb_opportunity: None,
                 };
+                if normalize_odds_enabled {
+                    calculations::normalize::attach(&mut ws_msg);
+                }
 
-                let _ = process_ws_tx.send(ws_msg);
+                process_fanout.publish(&ws_msg).await;
+
+                if arb_enabled {
+                    let now_ms = chrono::Utc::now().timestamp_millis();
+                    for opportunity in arb_scanner.scan_fixture(&fixture, &arb_config, now_ms) {
+                        let arb_msg = WsMessage {
+                            msg_type: "arb_opportunity".to_string(),
+                            fixture_id: fixture.fixture_id,
+                            timestamp: update.timestamp,
+                            start: update.start,
+                            end: now_ms,
+                            bookmakers: fixture.bookmakers.clone(),
+                            filter_matches: None,
+                            normalized: None,
+                            sequence: fixture.version,
+                            arb_opportunity: Some(opportunity),
+                        };
+                        process_fanout.publish(&arb_msg).await;
+                    }
+                }
             }
 
             // Update stats every second
@@ -125,7 +268,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 stats.updates_received = current_updates_count;
                 stats.updates_per_second = updates_delta as f64 / elapsed;
                 stats.uptime_seconds = start_time.elapsed().as_secs();
-                
+                stats.stale_updates_rejected = cache.stale_rejected();
+                stats.framing_errors = process_state.tcp_stats.framing_errors.load(Ordering::Relaxed);
+                stats.parse_errors = process_state.tcp_stats.parse_errors.load(Ordering::Relaxed);
+
                 last_stats_update = now;
                 last_updates_count = current_updates_count;
             }
@@ -136,6 +282,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let app = Router::new()
         .route("/ws", get(ws_handler))
         .route("/stats", get(get_stats))
+        .route("/candles", get(candles::get_candles))
         .nest_service("/", ServeDir::new("static"))
         .layer(CorsLayer::permissive())
         .with_state(state);
@@ -151,3 +298,55 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Decode the decimal prices carried by an `OddsUpdate` into candle ticks and feed them to the
+/// aggregator, returning any buckets that closed as a result.
+fn record_candle_ticks(aggregator: &mut CandleAggregator, update: &OddsUpdate) -> Vec<(CandleKey, candles::Candle)> {
+    let mut closed = Vec::new();
+    let scale = 10f64.powi(update.decimals);
+    let ts = update.timestamp;
+
+    let mut tick = |market: &str, outcome: &str, price: i32| {
+        if price <= 0 {
+            return;
+        }
+        let key = CandleKey {
+            fixture_id: update.fixture_id,
+            bookmaker: update.bookmaker.clone(),
+            market: market.to_string(),
+            outcome: outcome.to_string(),
+            interval: CandleInterval::OneMinute,
+        };
+        if let Some(candle) = aggregator.record(key.clone(), price as f64 / scale, ts) {
+            closed.push((key, candle));
+        }
+    };
+
+    if let Some(x12) = update.x12 {
+        tick("x12", "home", x12[0]);
+        tick("x12", "draw", x12[1]);
+        tick("x12", "away", x12[2]);
+    }
+    if let (Some(lines), Some(h), Some(a)) = (&update.ah_lines, &update.ah_h, &update.ah_a) {
+        for (i, line) in lines.iter().enumerate() {
+            if let Some(&p) = h.get(i) {
+                tick("ah", &format!("home@{}", line), p);
+            }
+            if let Some(&p) = a.get(i) {
+                tick("ah", &format!("away@{}", line), p);
+            }
+        }
+    }
+    if let (Some(lines), Some(o), Some(u)) = (&update.ou_lines, &update.ou_o, &update.ou_u) {
+        for (i, line) in lines.iter().enumerate() {
+            if let Some(&p) = o.get(i) {
+                tick("ou", &format!("over@{}", line), p);
+            }
+            if let Some(&p) = u.get(i) {
+                tick("ou", &format!("under@{}", line), p);
+            }
+        }
+    }
+
+    closed
+}
+
@@ -0,0 +1,76 @@
+use crate::types::WsMessage;
+use axum::extract::ws::Message;
+use axum::http::HeaderMap;
+use serde::Serialize;
+use tracing::warn;
+
+/// Wire format negotiated per WebSocket connection. Defaults to `Json` for backwards
+/// compatibility with existing clients; `Cbor`/`MessagePack` trade human-readability for a
+/// denser frame, worthwhile for snapshots spanning many bookmakers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WsCodec {
+    Json,
+    Cbor,
+    MessagePack,
+}
+
+impl WsCodec {
+    pub const JSON_PROTOCOL: &'static str = "json";
+    pub const CBOR_PROTOCOL: &'static str = "cbor";
+    pub const MSGPACK_PROTOCOL: &'static str = "msgpack";
+
+    /// Negotiate a codec from a client-supplied name, e.g. the `Sec-WebSocket-Protocol`
+    /// header value or the `codec` field on `ClientRequest::Subscribe`. Unknown or absent
+    /// names fall back to `Json`.
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            Self::CBOR_PROTOCOL => Self::Cbor,
+            Self::MSGPACK_PROTOCOL => Self::MessagePack,
+            _ => Self::Json,
+        }
+    }
+
+    /// Read the negotiated codec off the `Sec-WebSocket-Protocol` request header sent
+    /// during the upgrade handshake, before any `ClientRequest::Subscribe` has arrived.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        headers
+            .get(axum::http::header::SEC_WEBSOCKET_PROTOCOL)
+            .and_then(|v| v.to_str().ok())
+            .map(Self::from_name)
+            .unwrap_or(Self::Json)
+    }
+
+    pub fn protocol_name(self) -> &'static str {
+        match self {
+            Self::Json => Self::JSON_PROTOCOL,
+            Self::Cbor => Self::CBOR_PROTOCOL,
+            Self::MessagePack => Self::MSGPACK_PROTOCOL,
+        }
+    }
+
+    /// Encode an outgoing message in the negotiated wire format. Returns `None` (and logs
+    /// a warning) on a serialization failure rather than propagating, matching the
+    /// existing `if let Ok(json) = serde_json::to_string(&msg)` best-effort send pattern.
+    pub fn encode(self, msg: &WsMessage) -> Option<Message> {
+        match self {
+            Self::Json => serde_json::to_string(msg)
+                .map(Message::Text)
+                .map_err(|e| warn!("Failed to JSON-encode WS message: {}", e))
+                .ok(),
+            Self::Cbor => encode_cbor(msg)
+                .map(Message::Binary)
+                .map_err(|e| warn!("Failed to CBOR-encode WS message: {}", e))
+                .ok(),
+            Self::MessagePack => rmp_serde::to_vec_named(msg)
+                .map(Message::Binary)
+                .map_err(|e| warn!("Failed to MessagePack-encode WS message: {}", e))
+                .ok(),
+        }
+    }
+}
+
+fn encode_cbor<T: Serialize>(value: &T) -> Result<Vec<u8>, ciborium::ser::Error<std::io::Error>> {
+    let mut buf = Vec::new();
+    ciborium::into_writer(value, &mut buf)?;
+    Ok(buf)
+}
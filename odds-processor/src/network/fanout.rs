@@ -0,0 +1,168 @@
+use crate::types::{ProcessorStats, WsMessage};
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+/// Publishes ingested `WsMessage` values to every server instance's local WebSocket
+/// clients. The in-process implementation is the only source of truth on a single node;
+/// the Redis implementation lets multiple nodes behind a load balancer share one stream
+/// of updates, the way a shared cache backs a distributed build cache.
+#[async_trait]
+pub trait Fanout: Send + Sync {
+    async fn publish(&self, msg: &WsMessage);
+
+    /// Report this node's local WebSocket client count so it can be summed across the
+    /// cluster. No-op for the in-process backend, which has no cluster to aggregate.
+    async fn report_client_count(&self, _count: usize) {}
+
+    /// Fill in cluster-wide figures (currently just `ws_clients`) on top of whatever the
+    /// caller already populated from local state. No-op for the in-process backend.
+    async fn aggregate_stats(&self, _stats: &mut ProcessorStats) {}
+}
+
+/// Single-node fanout: publishing just forwards into the local broadcast channel that
+/// `send_task`s already subscribe to. This is the default and requires no external
+/// dependency.
+pub struct InProcessFanout {
+    tx: broadcast::Sender<WsMessage>,
+}
+
+impl InProcessFanout {
+    pub fn new(tx: broadcast::Sender<WsMessage>) -> Self {
+        Self { tx }
+    }
+}
+
+#[async_trait]
+impl Fanout for InProcessFanout {
+    async fn publish(&self, msg: &WsMessage) {
+        let _ = self.tx.send(msg.clone());
+    }
+}
+
+/// Cluster fanout: `publish` writes to a Redis pub/sub channel rather than the local
+/// broadcast channel directly. A background task subscribed to the same channel forwards
+/// every message it receives (this node's own publishes included, since Redis delivers
+/// PUBLISH to all subscribers of a channel) into the local broadcast channel, so
+/// `send_task`/WebSocket client code is unaware fanout is distributed at all.
+pub struct RedisFanout {
+    client: redis::Client,
+    channel: String,
+    node_id: String,
+}
+
+const STATS_CLIENTS_KEY_PREFIX: &str = "odds_processor:stats:ws_clients:";
+const STATS_TTL_SECS: i64 = 15;
+
+impl RedisFanout {
+    /// Connects to `redis_url`, spawns the background subscriber that forwards messages
+    /// from `channel` into `local_tx`, and returns the handle used for publishing.
+    pub async fn new(
+        redis_url: &str,
+        channel: String,
+        node_id: String,
+        local_tx: broadcast::Sender<WsMessage>,
+    ) -> Result<Self, redis::RedisError> {
+        let client = redis::Client::open(redis_url)?;
+
+        let sub_client = client.clone();
+        let sub_channel = channel.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = Self::run_subscriber(&sub_client, &sub_channel, &local_tx).await {
+                    error!("Redis fanout subscriber disconnected, retrying in 5s: {}", e);
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            }
+        });
+
+        Ok(Self { client, channel, node_id })
+    }
+
+    async fn run_subscriber(
+        client: &redis::Client,
+        channel: &str,
+        local_tx: &broadcast::Sender<WsMessage>,
+    ) -> Result<(), redis::RedisError> {
+        let mut pubsub = client.get_async_pubsub().await?;
+        pubsub.subscribe(channel).await?;
+        info!("📡 Redis fanout subscribed to '{}'", channel);
+
+        let mut stream = pubsub.on_message();
+        use futures::StreamExt;
+        while let Some(payload) = stream.next().await {
+            let raw: String = match payload.get_payload() {
+                Ok(raw) => raw,
+                Err(e) => {
+                    warn!("Redis fanout received non-UTF8 payload: {}", e);
+                    continue;
+                }
+            };
+            match serde_json::from_str::<WsMessage>(&raw) {
+                Ok(msg) => {
+                    let _ = local_tx.send(msg);
+                }
+                Err(e) => warn!("Redis fanout failed to deserialize message: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Fanout for RedisFanout {
+    async fn publish(&self, msg: &WsMessage) {
+        let payload = match serde_json::to_string(msg) {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Redis fanout failed to serialize message: {}", e);
+                return;
+            }
+        };
+
+        match self.client.get_multiplexed_async_connection().await {
+            Ok(mut conn) => {
+                if let Err(e) = conn.publish::<_, _, ()>(&self.channel, payload).await {
+                    error!("Redis fanout publish failed: {}", e);
+                }
+            }
+            Err(e) => error!("Redis fanout could not get connection: {}", e),
+        }
+    }
+
+    async fn report_client_count(&self, count: usize) {
+        let key = format!("{}{}", STATS_CLIENTS_KEY_PREFIX, self.node_id);
+        match self.client.get_multiplexed_async_connection().await {
+            Ok(mut conn) => {
+                // Per-node key with its own TTL, so a node that dies without deregistering
+                // drops out of the cluster-wide count once the key expires rather than
+                // lingering forever.
+                if let Err(e) = conn.set_ex::<_, _, ()>(&key, count as i64, STATS_TTL_SECS as u64).await {
+                    warn!("Redis fanout failed to report client count: {}", e);
+                }
+            }
+            Err(e) => warn!("Redis fanout could not get connection to report stats: {}", e),
+        }
+    }
+
+    async fn aggregate_stats(&self, stats: &mut ProcessorStats) {
+        match self.client.get_multiplexed_async_connection().await {
+            Ok(mut conn) => {
+                let pattern = format!("{}*", STATS_CLIENTS_KEY_PREFIX);
+                match conn.keys::<_, Vec<String>>(&pattern).await {
+                    Ok(keys) if !keys.is_empty() => match conn.mget::<_, Vec<Option<i64>>>(&keys).await {
+                        Ok(counts) => {
+                            stats.ws_clients = counts.into_iter().flatten().map(|c| c as usize).sum();
+                        }
+                        Err(e) => warn!("Redis fanout failed to read cluster client counts: {}", e),
+                    },
+                    Ok(_) => stats.ws_clients = 0,
+                    Err(e) => warn!("Redis fanout failed to list cluster client count keys: {}", e),
+                }
+            }
+            Err(e) => warn!("Redis fanout could not get connection to aggregate stats: {}", e),
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod codec;
+pub mod fanout;
+pub mod stream;
+pub mod tcp;
@@ -1,11 +1,17 @@
 use crate::types::{ProcessorStats, WsMessage, ClientState};
 use crate::cache::Cache;
-use crate::filters::{FilterExpr, evaluate, FilterContext};
+use crate::calculations::normalize;
+use crate::filters::{FilterExpr, evaluate, FilterContext, FilterOptions};
+use crate::network::codec::WsCodec;
+use crate::network::fanout::Fanout;
+use crate::network::tcp::TcpStats;
+use crate::trace_sink::{self, TraceSender};
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
         State,
     },
+    http::HeaderMap,
     response::IntoResponse,
 };
 use futures::{SinkExt, StreamExt};
@@ -21,15 +27,43 @@ pub struct AppState {
     pub cache: Arc<RwLock<Cache>>,
     pub stats: RwLock<ProcessorStats>,
     pub client_count: RwLock<usize>,
+    pub db: sqlx::PgPool,
+    pub normalize_odds: bool,
+    /// Opt-in behaviors for every `FilterContext` this node constructs; see
+    /// `filters::FilterOptions`.
+    pub filter_options: FilterOptions,
+    pub tcp_stats: Arc<TcpStats>,
+    /// Publishes ingested updates so every node behind a load balancer forwards them to
+    /// its own WebSocket clients; also aggregates `ws_clients` across the cluster when the
+    /// backend supports it (in-process is necessarily local-only).
+    pub fanout: Arc<dyn Fanout>,
+    /// Forwards matched filter traces to the trace sink for durable persistence. `None`
+    /// when trace persistence is disabled.
+    pub trace_tx: Option<TraceSender>,
 }
 
 impl AppState {
-    pub fn new(tx: broadcast::Sender<WsMessage>, cache: Arc<RwLock<Cache>>) -> Self {
+    pub fn new(
+        tx: broadcast::Sender<WsMessage>,
+        cache: Arc<RwLock<Cache>>,
+        db: sqlx::PgPool,
+        normalize_odds: bool,
+        filter_options: FilterOptions,
+        tcp_stats: Arc<TcpStats>,
+        fanout: Arc<dyn Fanout>,
+        trace_tx: Option<TraceSender>,
+    ) -> Self {
         Self {
             tx,
             cache,
             stats: RwLock::new(ProcessorStats::default()),
             client_count: RwLock::new(0),
+            db,
+            normalize_odds,
+            filter_options,
+            tcp_stats,
+            fanout,
+            trace_tx,
         }
     }
 
@@ -38,6 +72,7 @@ impl AppState {
         *count += 1;
         let mut stats = self.stats.write().await;
         stats.ws_clients = *count;
+        self.fanout.report_client_count(*count).await;
     }
 
     pub async fn decrement_clients(&self) {
@@ -45,34 +80,53 @@ impl AppState {
         *count = count.saturating_sub(1);
         let mut stats = self.stats.write().await;
         stats.ws_clients = *count;
+        self.fanout.report_client_count(*count).await;
     }
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum ClientRequest {
-    Subscribe { filter: Option<FilterExpr> },
+    Subscribe {
+        filter: Option<FilterExpr>,
+        #[serde(default)]
+        codec: Option<String>,
+    },
     UpdateFilter { filter: FilterExpr },
+    #[serde(rename = "unsubscribe")]
     RemoveFilter,
+    /// Resume after a reconnect: send only fixtures touched since `cursor` (the highest
+    /// `WsMessage.sequence` the client has seen), plus removals for fixtures that stopped
+    /// matching in the meantime, instead of the full cache snapshot.
+    Resume { cursor: u64 },
 }
 
 type WsSender = Arc<tokio::sync::Mutex<futures::stream::SplitSink<WebSocket, Message>>>;
 
-/// WebSocket upgrade handler
+/// How long `handle_socket` waits for a freshly-connected client's first message before
+/// giving up and sending the full, unfiltered snapshot.
+const INITIAL_MESSAGE_WINDOW: tokio::time::Duration = tokio::time::Duration::from_millis(200);
+
+/// WebSocket upgrade handler. The wire format is negotiated from the `Sec-WebSocket-Protocol`
+/// header (`json`/`cbor`/`msgpack`) up front, and can still be switched later via the `codec`
+/// field on `ClientRequest::Subscribe`.
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
+    headers: HeaderMap,
     State(state): State<SharedState>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(|socket| handle_socket(socket, state))
+    let codec = WsCodec::from_headers(&headers);
+    ws.protocols([WsCodec::JSON_PROTOCOL, WsCodec::CBOR_PROTOCOL, WsCodec::MSGPACK_PROTOCOL])
+        .on_upgrade(move |socket| handle_socket(socket, state, codec))
 }
 
 /// Handle individual WebSocket connection
-async fn handle_socket(socket: WebSocket, state: SharedState) {
+async fn handle_socket(socket: WebSocket, state: SharedState, codec: WsCodec) {
     let (sender, mut receiver) = socket.split();
     let mut rx = state.tx.subscribe();
 
     state.increment_clients().await;
-    info!("👤 WebSocket client connected");
+    info!("👤 WebSocket client connected ({})", codec.protocol_name());
 
     let sender: WsSender = std::sync::Arc::new(tokio::sync::Mutex::new(sender));
     let sender_clone = sender.clone();
@@ -80,11 +134,26 @@ async fn handle_socket(socket: WebSocket, state: SharedState) {
     // Shared client state
     let client_state: Arc<RwLock<ClientState>> = Arc::new(RwLock::new(ClientState::new()));
     let client_state_clone = client_state.clone();
+    let codec: Arc<RwLock<WsCodec>> = Arc::new(RwLock::new(codec));
+    let codec_clone = codec.clone();
 
-    // Send initial snapshot (no filter)
-    send_filtered_snapshot(&state.cache, &sender, None).await;
+    // Give a just-connected client a brief window to send its first request (e.g.
+    // `Resume { cursor }`) before falling back to a full snapshot, so a resuming client
+    // isn't sent the full snapshot and then a redundant resume delta on top of it. If
+    // nothing arrives in time, fall back to the original unconditional snapshot.
+    let first_message = match tokio::time::timeout(INITIAL_MESSAGE_WINDOW, receiver.next()).await {
+        Ok(msg) => msg,
+        Err(_) => {
+            let initial_codec = *codec.read().await;
+            send_filtered_snapshot(&state.cache, &sender, None, state.normalize_odds, state.filter_options, initial_codec, state.trace_tx.as_ref()).await;
+            None
+        }
+    };
 
     let cache_clone = state.cache.clone();
+    let normalize_odds = state.normalize_odds;
+    let filter_options = state.filter_options;
+    let trace_tx = state.trace_tx.clone();
     let send_task = tokio::spawn(async move {
         loop {
             tokio::select! {
@@ -98,10 +167,16 @@ async fn handle_socket(socket: WebSocket, state: SharedState) {
                                 let mut state = client_state_clone.write().await;
                                 if let Some(expr) = &state.filter {
                                     if let Ok(value) = serde_json::to_value(&msg) {
-                                        let mut ctx = FilterContext::new(&value);
+                                        let cache_guard = cache_clone.read().await;
+                                        let history = cache_guard.fixtures.get(&fixture_id).map(|f| &f.history);
+                                        let mut ctx = match history {
+                                            Some(h) => FilterContext::with_history_and_options(&value, h, filter_options),
+                                            None => FilterContext::with_options(&value, filter_options),
+                                        };
                                         let matches_now = evaluate(expr, &mut ctx);
                                         let was_matching = state.matching_fixtures.contains(&fixture_id);
                                         let traces = ctx.get_traces();
+                                        trace_sink::record(trace_tx.as_ref(), fixture_id, msg.timestamp, &traces);
 
                                         match (matches_now, was_matching) {
                                             (true, false) => {
@@ -138,9 +213,13 @@ async fn handle_socket(socket: WebSocket, state: SharedState) {
                                 if !traces.is_empty() {
                                     msg.filter_matches = Some(traces);
                                 }
-                                if let Ok(json) = serde_json::to_string(&msg) {
+                                if normalize_odds {
+                                    normalize::attach(&mut msg);
+                                }
+                                let codec = *codec_clone.read().await;
+                                if let Some(frame) = codec.encode(&msg) {
                                     let mut s = sender_clone.lock().await;
-                                    if s.send(Message::Text(json)).await.is_err() {
+                                    if s.send(frame).await.is_err() {
                                         break;
                                     }
                                 }
@@ -151,9 +230,10 @@ async fn handle_socket(socket: WebSocket, state: SharedState) {
                                 let cache = cache_clone.read().await;
                                 if let Some(fixture) = cache.fixtures.get(&fixture_id) {
                                     let removal_msg = fixture.to_odds_removed_message();
-                                    if let Ok(json) = serde_json::to_string(&removal_msg) {
+                                    let codec = *codec_clone.read().await;
+                                    if let Some(frame) = codec.encode(&removal_msg) {
                                         let mut s = sender_clone.lock().await;
-                                        if s.send(Message::Text(json)).await.is_err() {
+                                        if s.send(frame).await.is_err() {
                                             break;
                                         }
                                     }
@@ -174,31 +254,56 @@ async fn handle_socket(socket: WebSocket, state: SharedState) {
         }
     });
 
-    // Handle incoming messages
-    while let Some(Ok(msg)) = receiver.next().await {
+    // Handle incoming messages, starting with whichever message (if any) was already
+    // read while waiting out the initial-message window above.
+    let mut pending = first_message;
+    loop {
+        let next = match pending.take() {
+            Some(msg) => msg,
+            None => match receiver.next().await {
+                Some(msg) => msg,
+                None => break,
+            },
+        };
+        let Ok(msg) = next else { break };
+
         if let Message::Text(text) = msg {
             match serde_json::from_str::<ClientRequest>(&text) {
                 Ok(req) => {
                     let mut client_state_guard = client_state.write().await;
                     match req {
-                        ClientRequest::Subscribe { filter: new_filter } => {
+                        ClientRequest::Subscribe { filter: new_filter, codec: new_codec } => {
                             client_state_guard.filter = new_filter;
                             client_state_guard.matching_fixtures.clear(); // Clear tracking when subscribing
-                            info!("✅ Client subscribed with filter");
+                            if let Some(name) = new_codec {
+                                let negotiated = WsCodec::from_name(&name);
+                                *codec.write().await = negotiated;
+                                info!("✅ Client subscribed with filter ({})", negotiated.protocol_name());
+                            } else {
+                                info!("✅ Client subscribed with filter");
+                            }
                             // Send snapshot with new filter
-                            send_filtered_snapshot(&state.cache, &sender, Some(&mut client_state_guard)).await;
+                            let active_codec = *codec.read().await;
+                            send_filtered_snapshot(&state.cache, &sender, Some(&mut client_state_guard), state.normalize_odds, state.filter_options, active_codec, state.trace_tx.as_ref()).await;
                         },
                         ClientRequest::UpdateFilter { filter: new_filter } => {
                             client_state_guard.filter = Some(new_filter);
                             client_state_guard.matching_fixtures.clear(); // Clear tracking when updating filter
                             info!("🔄 Client updated filter");
-                            send_filtered_snapshot(&state.cache, &sender, Some(&mut client_state_guard)).await;
+                            let active_codec = *codec.read().await;
+                            send_filtered_snapshot(&state.cache, &sender, Some(&mut client_state_guard), state.normalize_odds, state.filter_options, active_codec, state.trace_tx.as_ref()).await;
                         },
                         ClientRequest::RemoveFilter => {
                             client_state_guard.filter = None;
                             client_state_guard.matching_fixtures.clear(); // Clear tracking when removing filter
                             info!("❌ Client removed filter");
-                            send_filtered_snapshot(&state.cache, &sender, Some(&mut client_state_guard)).await;
+                            let active_codec = *codec.read().await;
+                            send_filtered_snapshot(&state.cache, &sender, Some(&mut client_state_guard), state.normalize_odds, state.filter_options, active_codec, state.trace_tx.as_ref()).await;
+                        }
+                        ClientRequest::Resume { cursor } => {
+                            info!("🔁 Client resuming from cursor {}", cursor);
+                            let active_codec = *codec.read().await;
+                            send_resume_delta(&state.cache, &sender, &mut client_state_guard, state.normalize_odds, state.filter_options, active_codec, cursor, state.trace_tx.as_ref()).await;
                         }
                     }
                 },
@@ -217,7 +322,11 @@ async fn handle_socket(socket: WebSocket, state: SharedState) {
 async fn send_filtered_snapshot<'a>(
     cache: &Arc<RwLock<Cache>>,
     sender: &WsSender,
-    mut client_state: Option<&mut tokio::sync::RwLockWriteGuard<'a, ClientState>>
+    mut client_state: Option<&mut tokio::sync::RwLockWriteGuard<'a, ClientState>>,
+    normalize_odds: bool,
+    filter_options: FilterOptions,
+    codec: WsCodec,
+    trace_tx: Option<&TraceSender>,
 ) {
     let cache = cache.read().await;
     let count = cache.fixtures.len();
@@ -232,7 +341,7 @@ async fn send_filtered_snapshot<'a>(
         let filter_ref = client_state.as_ref().and_then(|cs| cs.filter.as_ref());
         let (should_send, traces) = if let Some(expr) = filter_ref {
             if let Ok(value) = serde_json::to_value(&base_msg) {
-                let mut ctx = FilterContext::new(&value);
+                let mut ctx = FilterContext::with_history_and_options(&value, &fixture.history, filter_options);
                 let result = evaluate(expr, &mut ctx);
 
                 (result, ctx.get_traces())
@@ -243,6 +352,7 @@ async fn send_filtered_snapshot<'a>(
         } else {
             (true, vec![])
         };
+        trace_sink::record(trace_tx, fixture.fixture_id, base_msg.timestamp, &traces);
 
         if should_send {
             // Track this fixture as matching for the client
@@ -251,14 +361,18 @@ async fn send_filtered_snapshot<'a>(
             }
 
             // Create message with traces if filter matched
-            let msg = if !traces.is_empty() {
+            let mut msg = if !traces.is_empty() {
                 fixture.to_ws_message_with_traces("odds_snapshot", traces)
             } else {
                 base_msg
             };
 
-            if let Ok(json) = serde_json::to_string(&msg) {
-                if s.send(Message::Text(json)).await.is_err() {
+            if normalize_odds {
+                normalize::attach(&mut msg);
+            }
+
+            if let Some(frame) = codec.encode(&msg) {
+                if s.send(frame).await.is_err() {
                     break;
                 }
                 sent_count += 1;
@@ -268,10 +382,129 @@ async fn send_filtered_snapshot<'a>(
     info!("✅ Sent {}/{} fixtures in snapshot", sent_count, count);
 }
 
+/// Resumption handshake: send only fixtures touched since `cursor`, plus removals for
+/// fixtures that stopped matching (or were evicted entirely) since the client last saw
+/// them, then a `resume_ack` carrying the fresh cursor to resume from next time.
+async fn send_resume_delta<'a>(
+    cache: &Arc<RwLock<Cache>>,
+    sender: &WsSender,
+    client_state: &mut tokio::sync::RwLockWriteGuard<'a, ClientState>,
+    normalize_odds: bool,
+    filter_options: FilterOptions,
+    codec: WsCodec,
+    cursor: u64,
+    trace_tx: Option<&TraceSender>,
+) {
+    let cache = cache.read().await;
+    let mut s = sender.lock().await;
+    let mut sent_count = 0;
+
+    // Fixtures the client was previously matching that have since been evicted from the
+    // cache entirely never show up in the version scan below, so they need a synthetic
+    // removal here instead.
+    let vanished: Vec<i64> = client_state
+        .matching_fixtures
+        .iter()
+        .copied()
+        .filter(|fixture_id| !cache.fixtures.contains_key(fixture_id))
+        .collect();
+    for fixture_id in vanished {
+        client_state.matching_fixtures.remove(&fixture_id);
+        let now = chrono::Utc::now().timestamp_millis();
+        let removal_msg = WsMessage {
+            msg_type: "odds_removed".to_string(),
+            fixture_id,
+            timestamp: now,
+            start: now,
+            end: now,
+            bookmakers: std::collections::HashMap::new(),
+            filter_matches: None,
+            normalized: None,
+            sequence: cache.current_sequence(),
+            arb_opportunity: None,
+        };
+        if let Some(frame) = codec.encode(&removal_msg) {
+            if s.send(frame).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    for fixture in cache.fixtures.values() {
+        if fixture.version <= cursor {
+            // Unchanged since the client's cursor - it already has whatever it had before.
+            continue;
+        }
+
+        let was_matching = client_state.matching_fixtures.contains(&fixture.fixture_id);
+        let base_msg = fixture.to_ws_message("odds_update");
+
+        let (matches_now, traces) = if let Some(expr) = &client_state.filter {
+            if let Ok(value) = serde_json::to_value(&base_msg) {
+                let mut ctx = FilterContext::with_history_and_options(&value, &fixture.history, filter_options);
+                let result = evaluate(expr, &mut ctx);
+                (result, ctx.get_traces())
+            } else {
+                warn!("Failed to serialize message for filter evaluation");
+                (true, vec![])
+            }
+        } else {
+            (true, vec![])
+        };
+        trace_sink::record(trace_tx, fixture.fixture_id, base_msg.timestamp, &traces);
+
+        if matches_now {
+            client_state.matching_fixtures.insert(fixture.fixture_id);
+            let mut msg = if !traces.is_empty() {
+                fixture.to_ws_message_with_traces("odds_update", traces)
+            } else {
+                base_msg
+            };
+            if normalize_odds {
+                normalize::attach(&mut msg);
+            }
+            if let Some(frame) = codec.encode(&msg) {
+                if s.send(frame).await.is_err() {
+                    return;
+                }
+                sent_count += 1;
+            }
+        } else if was_matching {
+            client_state.matching_fixtures.remove(&fixture.fixture_id);
+            let removal_msg = fixture.to_odds_removed_message();
+            if let Some(frame) = codec.encode(&removal_msg) {
+                if s.send(frame).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    let fresh_cursor = cache.current_sequence();
+    let now = chrono::Utc::now().timestamp_millis();
+    let ack = WsMessage {
+        msg_type: "resume_ack".to_string(),
+        fixture_id: 0,
+        timestamp: now,
+        start: now,
+        end: now,
+        bookmakers: std::collections::HashMap::new(),
+        filter_matches: None,
+        normalized: None,
+        sequence: fresh_cursor,
+        arb_opportunity: None,
+    };
+    if let Some(frame) = codec.encode(&ack) {
+        let _ = s.send(frame).await;
+    }
+
+    info!("🔁 Sent {} changed fixtures since cursor {} (fresh cursor {})", sent_count, cursor, fresh_cursor);
+}
 
 /// Get current stats
 pub async fn get_stats(State(state): State<SharedState>) -> impl IntoResponse {
-    let stats = state.stats.read().await;
-    axum::Json(stats.clone())
+    let mut stats = state.stats.read().await.clone();
+    state.fanout.aggregate_stats(&mut stats).await;
+    axum::Json(stats)
 }
 
@@ -1,13 +1,41 @@
 use crate::types::OddsUpdate;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::net::TcpListener;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::broadcast;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-/// Start TCP listener for odds-engine updates
+/// First byte sent by a client that wants length-prefixed MessagePack framing instead of
+/// the legacy newline-delimited JSON protocol. It can never be the first byte of a JSON
+/// document (whitespace or `{`), so connections that don't send it fall back cleanly to
+/// the line protocol.
+const MESSAGEPACK_HANDSHAKE: u8 = 0x02;
+
+/// Reject frames declaring a length above this; caps how much a malformed or malicious
+/// sender can make us allocate for a single message.
+const MAX_FRAME_LEN: u32 = 16 * 1024 * 1024;
+
+/// Counters for the ingest path's failure modes, surfaced into `ProcessorStats`.
+#[derive(Default)]
+pub struct TcpStats {
+    pub framing_errors: AtomicU64,
+    pub parse_errors: AtomicU64,
+}
+
+/// Which protocol a connection negotiated via its handshake byte (or lack thereof).
+enum Framing {
+    NewlineJson,
+    LengthPrefixedMessagePack,
+}
+
+/// Start TCP listener for odds-engine updates. Each connection is sniffed for the
+/// MessagePack handshake byte before any data is consumed, so line-protocol and
+/// length-prefixed senders can connect to the same port.
 pub async fn start_tcp_listener(
     port: u16,
     tx: broadcast::Sender<OddsUpdate>,
+    stats: Arc<TcpStats>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let addr = format!("127.0.0.1:{}", port);
     let listener = TcpListener::bind(&addr).await?;
@@ -15,26 +43,32 @@ pub async fn start_tcp_listener(
 
     loop {
         match listener.accept().await {
-            Ok((socket, addr)) => {
-                info!("🔗 New connection from odds-engine: {}", addr);
+            Ok((socket, peer)) => {
+                info!("🔗 New connection from odds-engine: {}", peer);
                 let tx = tx.clone();
-                
+                let stats = stats.clone();
+
                 tokio::spawn(async move {
-                    let reader = BufReader::new(socket);
-                    let mut lines = reader.lines();
-
-                    while let Ok(Some(line)) = lines.next_line().await {
-                        match serde_json::from_str::<OddsUpdate>(&line) {
-                            Ok(update) => {
-                                let _ = tx.send(update);
-                            }
-                            Err(e) => {
-                                error!("Failed to parse update: {}", e);
-                            }
+                    let mut reader = BufReader::new(socket);
+
+                    let framing = match reader.fill_buf().await {
+                        Ok(buf) if buf.first() == Some(&MESSAGEPACK_HANDSHAKE) => {
+                            reader.consume(1);
+                            Framing::LengthPrefixedMessagePack
                         }
+                        Ok(_) => Framing::NewlineJson,
+                        Err(e) => {
+                            warn!("Failed to read from {}: {}", peer, e);
+                            return;
+                        }
+                    };
+
+                    match framing {
+                        Framing::NewlineJson => read_newline_json(reader, &tx, &stats).await,
+                        Framing::LengthPrefixedMessagePack => read_length_prefixed_msgpack(reader, &tx, &stats).await,
                     }
 
-                    info!("🔌 Connection closed: {}", addr);
+                    info!("🔌 Connection closed: {}", peer);
                 });
             }
             Err(e) => {
@@ -44,4 +78,76 @@ pub async fn start_tcp_listener(
     }
 }
 
+async fn read_newline_json(
+    reader: BufReader<TcpStream>,
+    tx: &broadcast::Sender<OddsUpdate>,
+    stats: &TcpStats,
+) {
+    let mut lines = reader.lines();
+
+    loop {
+        match lines.next_line().await {
+            Ok(Some(line)) => match serde_json::from_str::<OddsUpdate>(&line) {
+                Ok(update) => {
+                    let _ = tx.send(update);
+                }
+                Err(e) => {
+                    stats.parse_errors.fetch_add(1, Ordering::Relaxed);
+                    error!("Failed to parse update: {}", e);
+                }
+            },
+            Ok(None) => break,
+            Err(e) => {
+                stats.framing_errors.fetch_add(1, Ordering::Relaxed);
+                error!("Error reading newline-delimited connection: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// Read 4-byte big-endian length-prefixed frames, each carrying a MessagePack-encoded
+/// `OddsUpdate`. Roughly halves bytes-on-wire for the numeric-heavy `x12`/`ah_*`/`ou_*`
+/// arrays versus the JSON framings above, and skips JSON parsing entirely.
+async fn read_length_prefixed_msgpack(
+    mut reader: BufReader<TcpStream>,
+    tx: &broadcast::Sender<OddsUpdate>,
+    stats: &TcpStats,
+) {
+    let mut buf = Vec::new();
+
+    loop {
+        let len = match reader.read_u32().await {
+            Ok(len) => len,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => {
+                stats.framing_errors.fetch_add(1, Ordering::Relaxed);
+                error!("Error reading frame length: {}", e);
+                break;
+            }
+        };
+
+        if len > MAX_FRAME_LEN {
+            stats.framing_errors.fetch_add(1, Ordering::Relaxed);
+            error!("Rejecting oversized frame of {} bytes (max {})", len, MAX_FRAME_LEN);
+            break;
+        }
+
+        buf.resize(len as usize, 0);
+        if let Err(e) = reader.read_exact(&mut buf).await {
+            stats.framing_errors.fetch_add(1, Ordering::Relaxed);
+            error!("Error reading frame body: {}", e);
+            break;
+        }
 
+        match rmp_serde::from_slice::<OddsUpdate>(&buf) {
+            Ok(update) => {
+                let _ = tx.send(update);
+            }
+            Err(e) => {
+                stats.parse_errors.fetch_add(1, Ordering::Relaxed);
+                error!("Failed to parse MessagePack update: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+// Append-only persistence of the raw OddsUpdate stream. Every accepted update is written
+// to `raw_odds_updates` with both the odds-engine timestamp and a server-side received-at
+// time, so replay, auditing, and end-to-end latency analysis stay possible after the fact.
+// Also backs warm-start: on boot the most recent row per (fixture_id, bookmaker) can be
+// turned back into `OddsUpdate`s to repopulate the in-memory cache.
+use crate::types::OddsUpdate;
+use serde_json::json;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+/// Subscribe to the update broadcast channel and flush accepted updates to Postgres in
+/// batches, so the durable write never adds per-message latency to the live WS path.
+pub fn spawn(
+    pool: PgPool,
+    mut rx: broadcast::Receiver<OddsUpdate>,
+    batch_size: usize,
+    flush_interval: Duration,
+) {
+    tokio::spawn(async move {
+        info!(
+            "🗄️ Starting raw odds sink (batch_size={}, flush_interval={:?})",
+            batch_size, flush_interval
+        );
+        let mut buffer: Vec<OddsUpdate> = Vec::with_capacity(batch_size);
+        let mut ticker = interval(flush_interval);
+
+        loop {
+            tokio::select! {
+                result = rx.recv() => {
+                    match result {
+                        Ok(update) => {
+                            buffer.push(update);
+                            if buffer.len() >= batch_size {
+                                flush(&pool, &mut buffer).await;
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            warn!("Raw odds sink lagged behind the update stream by {} messages", n);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+                _ = ticker.tick() => {
+                    if !buffer.is_empty() {
+                        flush(&pool, &mut buffer).await;
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn flush(pool: &PgPool, buffer: &mut Vec<OddsUpdate>) {
+    let received_at = chrono::Utc::now().timestamp_millis();
+    let count = buffer.len();
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start raw sink transaction: {}", e);
+            return;
+        }
+    };
+
+    for update in buffer.drain(..) {
+        let payload = json!({
+            "x12": update.x12,
+            "ah_lines": update.ah_lines,
+            "ah_h": update.ah_h,
+            "ah_a": update.ah_a,
+            "ou_lines": update.ou_lines,
+            "ou_o": update.ou_o,
+            "ou_u": update.ou_u,
+            "ids": update.ids,
+            "max_stakes": update.max_stakes,
+            "latest_t": update.latest_t,
+        });
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO raw_odds_updates
+                (fixture_id, bookie_id, bookmaker, decimals, engine_timestamp, start_timestamp, received_at, payload)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(update.fixture_id)
+        .bind(update.bookie_id)
+        .bind(&update.bookmaker)
+        .bind(update.decimals)
+        .bind(update.timestamp)
+        .bind(update.start)
+        .bind(received_at)
+        .bind(payload)
+        .execute(&mut *tx)
+        .await;
+
+        if let Err(e) = result {
+            error!("Failed to insert raw odds update: {}", e);
+        }
+    }
+
+    if let Err(e) = tx.commit().await {
+        error!("Failed to commit raw sink batch of {}: {}", count, e);
+    }
+}
+
+/// Warm-start: reconstruct the most recent `OddsUpdate` per (fixture_id, bookmaker) for the
+/// `limit` most recently active fixtures, mirroring `db::fetch_initial_odds`'s two-step shape.
+pub async fn fetch_latest(pool: &PgPool, limit: i64) -> Result<Vec<OddsUpdate>, sqlx::Error> {
+    let fixture_ids_query = r#"
+        SELECT fixture_id
+        FROM raw_odds_updates
+        GROUP BY fixture_id
+        ORDER BY MAX(received_at) DESC
+        LIMIT $1
+    "#;
+
+    let fixture_ids: Vec<i64> = sqlx::query(fixture_ids_query)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|r| r.get("fixture_id"))
+        .collect();
+
+    if fixture_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT ON (fixture_id, bookmaker)
+            fixture_id, bookie_id, bookmaker, decimals, engine_timestamp, start_timestamp, payload
+        FROM raw_odds_updates
+        WHERE fixture_id = ANY($1)
+        ORDER BY fixture_id, bookmaker, engine_timestamp DESC
+        "#,
+    )
+    .bind(&fixture_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_update).collect())
+}
+
+fn row_to_update(row: &PgRow) -> OddsUpdate {
+    let payload: serde_json::Value = row.get("payload");
+    let field = |key: &str| payload.get(key).cloned().filter(|v| !v.is_null());
+
+    OddsUpdate {
+        fixture_id: row.get("fixture_id"),
+        bookie_id: row.get("bookie_id"),
+        bookmaker: row.get("bookmaker"),
+        timestamp: row.get("engine_timestamp"),
+        start: row.get("start_timestamp"),
+        decimals: row.get("decimals"),
+        x12: field("x12").and_then(|v| serde_json::from_value(v).ok()),
+        ah_lines: field("ah_lines").and_then(|v| serde_json::from_value(v).ok()),
+        ah_h: field("ah_h").and_then(|v| serde_json::from_value(v).ok()),
+        ah_a: field("ah_a").and_then(|v| serde_json::from_value(v).ok()),
+        ou_lines: field("ou_lines").and_then(|v| serde_json::from_value(v).ok()),
+        ou_o: field("ou_o").and_then(|v| serde_json::from_value(v).ok()),
+        ou_u: field("ou_u").and_then(|v| serde_json::from_value(v).ok()),
+        ids: field("ids"),
+        max_stakes: field("max_stakes"),
+        latest_t: field("latest_t"),
+        revoked: false,
+    }
+}
@@ -0,0 +1,141 @@
+// Durable persistence of matched filter traces (`MatchTrace`). Unlike `sink.rs`, which
+// subscribes to the single shared update broadcast channel in `main`, traces are produced
+// per-client inside `network/stream.rs` as each connection evaluates its own filter, so this
+// sink is fed through an mpsc channel instead: every client task that gets a match forwards
+// it here rather than writing to Postgres inline on the hot WS send path.
+use crate::filters::{arithmetic::parse_bookmaker_path, MatchTrace};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+/// One matched `MatchTrace`, flattened with the fixture/bookmaker context the trace itself
+/// doesn't carry, ready to insert into `filter_match_traces`.
+#[derive(Debug, Clone)]
+pub struct TraceRecord {
+    pub fixture_id: i64,
+    pub bookmaker: Option<String>,
+    pub op: String,
+    pub threshold: Value,
+    pub left_path: Option<String>,
+    pub left_value: Option<Value>,
+    pub right_path: Option<String>,
+    pub right_value: Option<Value>,
+    pub calculation_op: Option<String>,
+    pub matched_at: i64,
+}
+
+pub type TraceSender = mpsc::UnboundedSender<TraceRecord>;
+
+/// Derive a `TraceRecord` from a matched `MatchTrace`, inferring `bookmaker` from the left
+/// operand's field path (e.g. `"bookmakers.Pinnacle.x12_h"`) the same way `arithmetic.rs`'s
+/// `history` operator does.
+fn to_record(fixture_id: i64, matched_at: i64, trace: &MatchTrace) -> TraceRecord {
+    let bookmaker = trace
+        .left_operand
+        .as_ref()
+        .and_then(|o| parse_bookmaker_path(&o.path))
+        .map(|(bookie, _)| bookie.to_string());
+
+    TraceRecord {
+        fixture_id,
+        bookmaker,
+        op: trace.op.clone(),
+        threshold: trace.threshold.clone(),
+        left_path: trace.left_operand.as_ref().map(|o| o.path.clone()),
+        left_value: trace.left_operand.as_ref().map(|o| o.value.clone()),
+        right_path: trace.right_operand.as_ref().map(|o| o.path.clone()),
+        right_value: trace.right_operand.as_ref().map(|o| o.value.clone()),
+        calculation_op: trace.calculation_op.clone(),
+        matched_at,
+    }
+}
+
+/// Forward every matched trace for `fixture_id` onto `tx`, if tracing is enabled at all.
+/// Silently does nothing when `tx` is `None`, so call sites don't need to branch on config.
+pub fn record(tx: Option<&TraceSender>, fixture_id: i64, matched_at: i64, traces: &[MatchTrace]) {
+    let Some(tx) = tx else { return };
+    for trace in traces {
+        if tx.send(to_record(fixture_id, matched_at, trace)).is_err() {
+            break;
+        }
+    }
+}
+
+/// Receive trace records over `rx` and flush them to Postgres in batches, so persisting a
+/// match never adds latency to the per-client WS send path.
+pub fn spawn(pool: PgPool, mut rx: mpsc::UnboundedReceiver<TraceRecord>, batch_size: usize, flush_interval: Duration) {
+    tokio::spawn(async move {
+        info!(
+            "🗄️ Starting filter trace sink (batch_size={}, flush_interval={:?})",
+            batch_size, flush_interval
+        );
+        let mut buffer: Vec<TraceRecord> = Vec::with_capacity(batch_size);
+        let mut ticker = interval(flush_interval);
+
+        loop {
+            tokio::select! {
+                record = rx.recv() => {
+                    match record {
+                        Some(record) => {
+                            buffer.push(record);
+                            if buffer.len() >= batch_size {
+                                flush(&pool, &mut buffer).await;
+                            }
+                        }
+                        None => break,
+                    }
+                }
+                _ = ticker.tick() => {
+                    if !buffer.is_empty() {
+                        flush(&pool, &mut buffer).await;
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn flush(pool: &PgPool, buffer: &mut Vec<TraceRecord>) {
+    let count = buffer.len();
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start trace sink transaction: {}", e);
+            return;
+        }
+    };
+
+    for record in buffer.drain(..) {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO filter_match_traces
+                (fixture_id, bookmaker, op, threshold, left_path, left_value, right_path, right_value, calculation_op, matched_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(record.fixture_id)
+        .bind(record.bookmaker)
+        .bind(record.op)
+        .bind(record.threshold)
+        .bind(record.left_path)
+        .bind(record.left_value)
+        .bind(record.right_path)
+        .bind(record.right_value)
+        .bind(record.calculation_op)
+        .bind(record.matched_at)
+        .execute(&mut *tx)
+        .await;
+
+        if let Err(e) = result {
+            warn!("Failed to insert filter match trace: {}", e);
+        }
+    }
+
+    if let Err(e) = tx.commit().await {
+        error!("Failed to commit trace sink batch of {}: {}", count, e);
+    }
+}
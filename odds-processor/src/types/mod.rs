@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use crate::filters::{FilterExpr, MatchTrace};
+use crate::calculations::normalize::NormalizedOdds;
 
 /// Incoming update from odds-engine via TCP
 /// Matches the same data format stored in football_odds table
@@ -38,6 +39,12 @@ pub struct OddsUpdate {
 
     // Latest timestamps per market type (matches latest_t column in DB)
     pub latest_t: Option<Value>,
+
+    /// Set when this update actually withdraws the bookmaker's price (a `revoke`
+    /// notification) rather than carrying a fresher one. Mirrors the external
+    /// `FillUpdateStatus::{New, Revoke}` design; all other fields are ignored when true.
+    #[serde(default)]
+    pub revoked: bool,
 }
 
 fn default_decimals() -> i32 {
@@ -90,6 +97,14 @@ pub struct FixtureData {
     pub fixture_id: i64,
     pub bookmakers: HashMap<String, BookmakerOdds>,
     pub last_update: i64,
+    /// `Cache`'s sequence number as of this fixture's last touch. Carried onto every
+    /// `WsMessage` about it so a reconnecting client can resume from a cursor instead of
+    /// re-fetching the whole snapshot.
+    pub version: u64,
+    /// Per-bookmaker snapshots plus per-field-path numeric samples, backing the filter
+    /// DSL's `history` operator. Not part of the wire format.
+    #[serde(skip)]
+    pub history: crate::filters::FixtureHistory,
 }
 
 /// Client state for WebSocket connections with filtering
@@ -114,6 +129,8 @@ impl FixtureData {
             fixture_id,
             bookmakers: HashMap::new(),
             last_update: 0,
+            version: 0,
+            history: crate::filters::FixtureHistory::new(),
         }
     }
 
@@ -127,6 +144,9 @@ impl FixtureData {
             end: now,
             bookmakers: self.bookmakers.clone(),
             filter_matches: None,
+            normalized: None,
+            sequence: self.version,
+            arb_opportunity: None,
         }
     }
 
@@ -140,6 +160,9 @@ impl FixtureData {
             end: now,
             bookmakers: self.bookmakers.clone(),
             filter_matches: if traces.is_empty() { None } else { Some(traces) },
+            normalized: None,
+            sequence: self.version,
+            arb_opportunity: None,
         }
     }
 
@@ -153,6 +176,9 @@ impl FixtureData {
             end: now,
             bookmakers: HashMap::new(),
             filter_matches: None,
+            normalized: None,
+            sequence: self.version,
+            arb_opportunity: None,
         }
     }
 }
@@ -171,6 +197,19 @@ pub struct WsMessage {
     /// Only populated when a filter is active and the fixture matches
     #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_matches: Option<Vec<MatchTrace>>,
+    /// Canonical decimal odds / implied / fair probability view of `bookmakers`,
+    /// keyed the same way. Only populated when `NORMALIZED_ODDS` is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalized: Option<HashMap<String, NormalizedOdds>>,
+    /// Monotonic cache sequence number as of this message. A client that reconnects can
+    /// send `ClientRequest::Resume` with the highest `sequence` it has seen to receive
+    /// only what changed since, instead of the full snapshot. `resume_ack` messages (empty
+    /// `bookmakers`, sentinel `fixture_id` of 0) carry the fresh cursor to resume from next.
+    pub sequence: u64,
+    /// Populated only when `msg_type == "arb_opportunity"`: the detected arb/value-bet,
+    /// its legs, stake split and expected return.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arb_opportunity: Option<crate::arbitrage::ArbOpportunity>,
 }
 
 /// Stats for monitoring
@@ -181,4 +220,9 @@ pub struct ProcessorStats {
     pub updates_per_second: f64,
     pub ws_clients: usize,
     pub uptime_seconds: u64,
+    pub stale_updates_rejected: u64,
+    /// Malformed/truncated frames on the TCP ingest channel (both wire formats).
+    pub framing_errors: u64,
+    /// Frames that framed correctly but failed to deserialize into an `OddsUpdate`.
+    pub parse_errors: u64,
 }